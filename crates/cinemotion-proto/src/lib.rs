@@ -27,6 +27,26 @@ impl TryInto<bytes::Bytes> for Event {
     }
 }
 
+impl TryFrom<bytes::Bytes> for Event {
+    type Error = self::Error;
+
+    fn try_from(value: bytes::Bytes) -> Result<Self, Self::Error> {
+        match prost::Message::decode(value) {
+            Ok(msg) => Ok(msg),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl TryInto<bytes::Bytes> for Command {
+    type Error = self::Error;
+    fn try_into(self) -> Result<bytes::Bytes, Self::Error> {
+        let mut buf = bytes::BytesMut::new();
+        self.encode(&mut buf)?;
+        Ok(buf.freeze())
+    }
+}
+
 impl TryFrom<bytes::Bytes> for Command {
     type Error = self::Error;
 
@@ -11,6 +11,7 @@ fn test_echo_message_size() {
             }
             .into(),
         ),
+        request_id: None,
     };
     let mut buf = Vec::new();
     message.encode(&mut buf).unwrap();
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use arc_swap::ArcSwapOption;
+use rand::Rng;
+use tokio::time::Instant;
+
+use crate::connection::Role;
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "auth_test.rs"]
+mod auth_test;
+
+/// The number of digits in a pairing PIN.
+pub const PIN_LENGTH: usize = 6;
+
+/// The wrong credentials an address may present before it is rate limited.
+pub const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+/// How long failed attempts count against an address.
+pub const FAILED_ATTEMPTS_WINDOW: Duration = Duration::from_secs(60);
+
+/// Checks the credentials presented by peers before they join a session.
+///
+/// A peer is admitted with either the current pairing PIN or one of the
+/// pre-shared join tokens, and gets the role of the credential it presented.
+/// An authenticator without a PIN or tokens admits every peer as an operator.
+///
+/// Addresses that keep presenting wrong credentials are rate limited, so the
+/// PIN cannot be guessed before it rotates.
+#[derive(Default)]
+pub struct Authenticator {
+    pin: ArcSwapOption<String>,
+    pin_role: Role,
+    tokens: HashMap<String, Role>,
+    failures: Mutex<HashMap<IpAddr, FailedAttempts>>,
+}
+
+/// The wrong credentials an address presented since the first of them.
+struct FailedAttempts {
+    count: u32,
+    since: Instant,
+}
+
+impl Authenticator {
+    /// Create an authenticator that admits every peer.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Create an authenticator that admits peers presenting one of the tokens
+    /// with the role of that token.
+    pub fn with_tokens<I>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = (String, Role)>,
+    {
+        Self {
+            tokens: tokens
+                .into_iter()
+                .filter(|(token, _)| !token.is_empty())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the role of peers that join with the pairing PIN.
+    pub fn with_pin_role(mut self, role: Role) -> Self {
+        self.pin_role = role;
+        self
+    }
+
+    /// Whether peers must present a credential to join.
+    pub fn is_enabled(&self) -> bool {
+        self.pin.load().is_some() || !self.tokens.is_empty()
+    }
+
+    /// The current pairing PIN, if one is set.
+    pub fn pin(&self) -> Option<String> {
+        self.pin.load().as_deref().cloned()
+    }
+
+    /// Replace the pairing PIN with a new random one and return it.
+    ///
+    /// Peers that already joined stay connected, only new peers need the new PIN.
+    pub fn rotate_pin(&self) -> String {
+        let pin = format!(
+            "{:0width$}",
+            rand::thread_rng().gen_range(0..10u32.pow(PIN_LENGTH as u32)),
+            width = PIN_LENGTH
+        );
+        self.pin.store(Some(Arc::new(pin.clone())));
+        pin
+    }
+
+    /// Check the credential presented by a peer and return the role it grants.
+    pub fn verify(&self, credential: Option<&str>) -> Result<Role> {
+        if !self.is_enabled() {
+            return Ok(Role::Operator);
+        }
+        let Some(credential) = credential.filter(|c| !c.is_empty()) else {
+            return Err(Error::Unauthorized(
+                "a join token or PIN is required".into(),
+            ));
+        };
+        let pin_matches = self
+            .pin
+            .load()
+            .as_deref()
+            .is_some_and(|pin| constant_time_eq(pin, credential));
+        // Check every token so the time taken does not reveal which one matched.
+        let token_role =
+            self.tokens.iter().fold(None, |found, (token, role)| {
+                match constant_time_eq(token, credential) {
+                    true => Some(*role),
+                    false => found,
+                }
+            });
+        match (token_role, pin_matches) {
+            (Some(role), _) => Ok(role),
+            (None, true) => Ok(self.pin_role),
+            (None, false) => Err(Error::Unauthorized("invalid join token or PIN".into())),
+        }
+    }
+
+    /// Check the credential presented by a peer at an address, like `verify`.
+    ///
+    /// An address that presented `MAX_FAILED_ATTEMPTS` wrong credentials within
+    /// `FAILED_ATTEMPTS_WINDOW` is rejected without checking until the window
+    /// passes. Missing credentials do not count, only wrong ones.
+    pub fn verify_peer(&self, peer: Option<IpAddr>, credential: Option<&str>) -> Result<Role> {
+        let Some(peer) = peer.filter(|_| self.is_enabled()) else {
+            return self.verify(credential);
+        };
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, attempts| now - attempts.since < FAILED_ATTEMPTS_WINDOW);
+        if failures
+            .get(&peer)
+            .is_some_and(|attempts| attempts.count >= MAX_FAILED_ATTEMPTS)
+        {
+            return Err(Error::RateLimited(format!(
+                "too many wrong credentials from {peer}, try again later"
+            )));
+        }
+        let result = self.verify(credential);
+        match &result {
+            Ok(_) => {
+                failures.remove(&peer);
+            }
+            Err(_) if credential.is_some_and(|c| !c.is_empty()) => {
+                failures
+                    .entry(peer)
+                    .or_insert(FailedAttempts {
+                        count: 0,
+                        since: now,
+                    })
+                    .count += 1;
+            }
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,78 @@
+use super::*;
+
+#[test]
+fn test_disabled_admits_everyone() {
+    let auth = Authenticator::disabled();
+    assert!(!auth.is_enabled());
+    assert_eq!(auth.verify(None), Ok(Role::Operator));
+    assert_eq!(auth.verify(Some("anything")), Ok(Role::Operator));
+}
+
+#[test]
+fn test_tokens() {
+    let auth = Authenticator::with_tokens([
+        ("stage-a".to_string(), Role::Operator),
+        ("phones".to_string(), Role::Controller),
+        ("".to_string(), Role::Operator),
+    ]);
+    assert!(auth.is_enabled());
+    assert_eq!(auth.verify(Some("stage-a")), Ok(Role::Operator));
+    assert_eq!(auth.verify(Some("phones")), Ok(Role::Controller));
+    assert!(matches!(
+        auth.verify(Some("stage-b")),
+        Err(Error::Unauthorized(_))
+    ));
+    assert!(matches!(auth.verify(Some("")), Err(Error::Unauthorized(_))));
+    assert!(matches!(auth.verify(None), Err(Error::Unauthorized(_))));
+}
+
+#[test]
+fn test_rotate_pin() {
+    let auth = Authenticator::disabled().with_pin_role(Role::Controller);
+    let pin = auth.rotate_pin();
+    assert_eq!(pin.len(), PIN_LENGTH);
+    assert!(pin.chars().all(|c| c.is_ascii_digit()));
+    assert!(auth.is_enabled());
+    assert_eq!(auth.verify(Some(&pin)), Ok(Role::Controller));
+
+    // Keep rotating until the PIN changes, there is a small chance it repeats.
+    let next = std::iter::repeat_with(|| auth.rotate_pin())
+        .find(|next| next != &pin)
+        .unwrap();
+    assert_eq!(auth.pin(), Some(next.clone()));
+    assert!(auth.verify(Some(&next)).is_ok());
+    assert!(matches!(
+        auth.verify(Some(&pin)),
+        Err(Error::Unauthorized(_))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wrong_credentials_are_rate_limited() {
+    let auth = Authenticator::with_tokens([("stage-a".to_string(), Role::Operator)]);
+    let peer = Some(IpAddr::from([192, 168, 1, 20]));
+    let other = Some(IpAddr::from([192, 168, 1, 21]));
+
+    // Missing credentials do not count against the address.
+    for _ in 0..MAX_FAILED_ATTEMPTS {
+        assert!(matches!(
+            auth.verify_peer(peer, None),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+    for _ in 0..MAX_FAILED_ATTEMPTS {
+        assert!(matches!(
+            auth.verify_peer(peer, Some("123456")),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+    assert!(matches!(
+        auth.verify_peer(peer, Some("stage-a")),
+        Err(Error::RateLimited(_))
+    ));
+    assert_eq!(auth.verify_peer(other, Some("stage-a")), Ok(Role::Operator));
+    assert_eq!(auth.verify_peer(None, Some("stage-a")), Ok(Role::Operator));
+
+    tokio::time::advance(FAILED_ATTEMPTS_WINDOW).await;
+    assert_eq!(auth.verify_peer(peer, Some("stage-a")), Ok(Role::Operator));
+}
@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
 use clap::{ArgAction, Parser};
 
+mod config;
+mod discover;
+mod scenes;
 mod start;
 
 /// A server for receiving and processing streamed motion data.
@@ -27,10 +30,22 @@ enum Command {
     /// Print the version information.
     Version,
     // Start the cinemotion broker service
-    Start(start::StartCmd),
+    Start(Box<start::StartCmd>),
+    /// Find the cinemotion servers on the local network.
+    Discover(discover::DiscoverCmd),
+    /// Manage the saved scenes.
+    Scenes(scenes::ScenesCmd),
 }
 
 impl Command {
+    /// The log filter configured for the command, if any.
+    pub fn log_filter(&self) -> Result<Option<String>> {
+        match self {
+            Self::Start(cmd) => Ok(cmd.config()?.log_filter()),
+            _ => Ok(None),
+        }
+    }
+
     pub fn run(&self) -> Result<i32> {
         let rt = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -46,23 +61,30 @@ impl Command {
                 Ok(0)
             }
             Self::Start(cmd) => cmd.run().await,
+            Self::Discover(cmd) => cmd.run().await,
+            Self::Scenes(cmd) => cmd.run(),
         }
     }
 }
 
 fn main() -> Result<()> {
     let opts = Opt::parse();
-    configure_logging(i32::from(opts.verbose) - i32::from(opts.quiet));
+    configure_logging(
+        i32::from(opts.verbose) - i32::from(opts.quiet),
+        opts.command.log_filter()?,
+    );
     let code = opts.command.run()?;
     std::process::exit(code);
 }
 
-pub fn configure_logging(verbosity: i32) {
+pub fn configure_logging(verbosity: i32, log_filter: Option<String>) {
     let base_config = match verbosity {
         n if n <= -3 => String::new(),
         -2 => "cinemotion=error".to_string(),
         -1 => "cinemotion=warn".to_string(),
-        0 => std::env::var("CINEMOTION_LOG").unwrap_or_else(|_| "cinemotion=info".to_string()),
+        0 => log_filter
+            .or_else(|| std::env::var("CINEMOTION_LOG").ok())
+            .unwrap_or_else(|| "cinemotion=info".to_string()),
         1 => "cinemotion=debug".to_string(),
         2 => "cinemotion=trace".to_string(),
         _ => "trace".to_string(),
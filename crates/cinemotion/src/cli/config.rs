@@ -0,0 +1,263 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use cinemotion::connection::{HeartbeatOptions, Role};
+use cinemotion::data::OfflinePolicy;
+use cinemotion::webrtc::WebRTCOptions;
+use serde::Deserialize;
+
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod config_test;
+
+/// The file name of the config file looked up in the config directory.
+pub const CONFIG_FILE: &str = "cinemotion.toml";
+
+const MAX_TICK_RATE: f64 = 1000.0;
+const MAX_EVENT_PIPE_CAPACITY: usize = 1 << 20;
+
+/// The settings of the `start` command, read from a TOML file and
+/// overridden by the command line flags.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// A level like `debug` or a full tracing filter like `cinemotion=debug,quinn=warn`.
+    pub log_level: Option<String>,
+    pub runtime: RuntimeConfig,
+    pub http: HttpConfig,
+    pub quic: QuicConfig,
+    pub mdns: MdnsConfig,
+    pub auth: AuthConfig,
+    pub webrtc: WebRTCOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    /// Engine ticks per second.
+    pub tick_rate: f64,
+    /// The number of events each connection may lag behind before it misses events.
+    pub event_pipe_capacity: usize,
+    /// What bound properties do while their controller is disconnected.
+    pub offline_policy: OfflinePolicy,
+    /// Seconds between heartbeats sent to every connection.
+    pub heartbeat_interval: f64,
+    /// Seconds a connection may be silent before it is closed.
+    pub idle_timeout: f64,
+    /// Seconds a connection may take to open before it is closed.
+    pub open_timeout: f64,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        let heartbeat = HeartbeatOptions::default();
+        Self {
+            tick_rate: 60.0,
+            event_pipe_capacity: cinemotion::events::DEFAULT_EVENT_PIPE_CAPACITY,
+            offline_policy: OfflinePolicy::default(),
+            heartbeat_interval: heartbeat.interval.as_secs_f64(),
+            idle_timeout: heartbeat.idle_timeout.as_secs_f64(),
+            open_timeout: heartbeat.open_timeout.as_secs_f64(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub enabled: bool,
+    pub address: SocketAddr,
+    /// State events per second streamed to `GET /events` subscribers.
+    pub event_rate: f64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            address: SocketAddr::from(([0, 0, 0, 0], cinemotion::DEFAULT_WEB_PORT)),
+            event_rate: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuicConfig {
+    pub enabled: bool,
+    pub address: SocketAddr,
+}
+
+impl Default for QuicConfig {
+    fn default() -> Self {
+        Self {
+            // The quic transport is still experimental and has to be enabled explicitly.
+            enabled: false,
+            address: SocketAddr::from(([0, 0, 0, 0], cinemotion::DEFAULT_QUIC_PORT)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MdnsConfig {
+    pub enabled: bool,
+    /// The instance name that tells servers on the network apart, defaults to
+    /// `cinemotion-<hostname>`.
+    pub name: Option<String>,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            name: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// The pre-shared join tokens accepted in place of the PIN.
+    pub tokens: Vec<TokenConfig>,
+    /// The role of peers that join with the pairing PIN.
+    pub pin_role: Role,
+    /// Seconds between pairing PIN rotations, the PIN never rotates when not set.
+    pub pin_rotation: Option<f64>,
+}
+
+/// A join token and the role of the peers presenting it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    pub token: String,
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl Config {
+    /// Read the config from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// Parse the config from a TOML string.
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Check that the settings are usable before any service starts.
+    pub fn validate(&self) -> Result<()> {
+        let tick_rate = self.runtime.tick_rate;
+        if !tick_rate.is_finite() || tick_rate <= 0.0 || tick_rate > MAX_TICK_RATE {
+            bail!("runtime.tick_rate must be above 0 and at most {MAX_TICK_RATE} ticks per second, got {tick_rate}");
+        }
+        let capacity = self.runtime.event_pipe_capacity;
+        if capacity == 0 || capacity > MAX_EVENT_PIPE_CAPACITY {
+            bail!("runtime.event_pipe_capacity must be between 1 and {MAX_EVENT_PIPE_CAPACITY}, got {capacity}");
+        }
+        let event_rate = self.http.event_rate;
+        if !event_rate.is_finite() || event_rate <= 0.0 || event_rate > MAX_TICK_RATE {
+            bail!("http.event_rate must be above 0 and at most {MAX_TICK_RATE} events per second, got {event_rate}");
+        }
+        for (key, seconds) in [
+            (
+                "runtime.heartbeat_interval",
+                self.runtime.heartbeat_interval,
+            ),
+            ("runtime.idle_timeout", self.runtime.idle_timeout),
+            ("runtime.open_timeout", self.runtime.open_timeout),
+        ]
+        .into_iter()
+        .chain(
+            self.auth
+                .pin_rotation
+                .map(|seconds| ("auth.pin_rotation", seconds)),
+        ) {
+            if !Duration::try_from_secs_f64(seconds).is_ok_and(|duration| !duration.is_zero()) {
+                bail!("{key} must be a number of seconds above 0, got {seconds}");
+            }
+        }
+        if self.runtime.idle_timeout <= self.runtime.heartbeat_interval {
+            bail!(
+                "runtime.idle_timeout must be longer than runtime.heartbeat_interval, got {} and {}",
+                self.runtime.idle_timeout,
+                self.runtime.heartbeat_interval
+            );
+        }
+        if self.auth.tokens.iter().any(|token| token.token.is_empty()) {
+            bail!("auth.tokens must not be empty");
+        }
+        if !self.http.enabled && !self.quic.enabled {
+            bail!("at least one of the http and quic services must be enabled");
+        }
+        if let Some(name) = &self.mdns.name {
+            // DNS-SD instance names are a single DNS label.
+            if name.is_empty() || name.len() > 63 {
+                bail!("mdns.name must be between 1 and 63 bytes, got {name:?}");
+            }
+        }
+        self.webrtc.validate().context("invalid webrtc settings")?;
+        if let Some(level) = &self.log_level {
+            tracing_subscriber::EnvFilter::try_new(log_filter(level))
+                .with_context(|| format!("invalid log_level {level:?}"))?;
+        }
+        Ok(())
+    }
+
+    /// Check that the enabled services can bind their addresses, so two
+    /// instances on one host fail clearly instead of colliding.
+    pub fn check_addresses(&self) -> Result<()> {
+        if self.http.enabled {
+            std::net::TcpListener::bind(self.http.address)
+                .with_context(|| format!("http.address {} is not available", self.http.address))?;
+        }
+        if self.quic.enabled {
+            std::net::UdpSocket::bind(self.quic.address)
+                .with_context(|| format!("quic.address {} is not available", self.quic.address))?;
+        }
+        Ok(())
+    }
+
+    /// The time between engine ticks.
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.runtime.tick_rate)
+    }
+
+    /// How often connections are pinged and how long they may be silent.
+    pub fn heartbeat(&self) -> HeartbeatOptions {
+        HeartbeatOptions {
+            interval: Duration::from_secs_f64(self.runtime.heartbeat_interval),
+            idle_timeout: Duration::from_secs_f64(self.runtime.idle_timeout),
+            open_timeout: Duration::from_secs_f64(self.runtime.open_timeout),
+        }
+    }
+
+    /// The time between pairing PIN rotations.
+    pub fn pin_rotation(&self) -> Option<Duration> {
+        self.auth.pin_rotation.map(Duration::from_secs_f64)
+    }
+
+    /// The minimum time between state events streamed over http.
+    pub fn event_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.http.event_rate)
+    }
+
+    /// The tracing filter for the configured log level.
+    pub fn log_filter(&self) -> Option<String> {
+        self.log_level.as_deref().map(log_filter)
+    }
+}
+
+/// A bare level applies to cinemotion only, anything else is used as a filter.
+fn log_filter(level: &str) -> String {
+    match level.parse::<tracing::Level>() {
+        Ok(level) => format!("cinemotion={}", level.as_str().to_lowercase()),
+        Err(_) => level.to_string(),
+    }
+}
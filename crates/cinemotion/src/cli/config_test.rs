@@ -0,0 +1,172 @@
+use super::*;
+
+#[test]
+fn test_parse_defaults() {
+    let config = Config::parse("").unwrap();
+    assert_eq!(config, Config::default());
+    assert!(config.validate().is_ok());
+    assert_eq!(config.http.address.port(), cinemotion::DEFAULT_WEB_PORT);
+    assert_eq!(config.quic.address.port(), cinemotion::DEFAULT_QUIC_PORT);
+    assert!(!config.quic.enabled);
+}
+
+#[test]
+fn test_parse() {
+    let config = Config::parse(
+        r#"
+        log_level = "debug"
+
+        [runtime]
+        tick_rate = 120
+        event_pipe_capacity = 256
+        offline_policy = "fade:1.5"
+        heartbeat_interval = 0.5
+        idle_timeout = 5
+        open_timeout = 20
+
+        [http]
+        address = "127.0.0.1:8080"
+
+        [quic]
+        enabled = true
+
+        [auth]
+        pin_role = "controller"
+        pin_rotation = 300
+        tokens = [{ token = "secret" }, { token = "monitor", role = "viewer" }]
+        "#,
+    )
+    .unwrap();
+    assert!(config.validate().is_ok());
+    assert_eq!(config.log_filter().as_deref(), Some("cinemotion=debug"));
+    assert_eq!(config.tick_interval(), Duration::from_secs_f64(1.0 / 120.0));
+    assert_eq!(config.runtime.event_pipe_capacity, 256);
+    assert_eq!(config.http.address, "127.0.0.1:8080".parse().unwrap());
+    assert!(config.http.enabled);
+    assert!(config.quic.enabled);
+    assert_eq!(config.quic.address.port(), cinemotion::DEFAULT_QUIC_PORT);
+    assert_eq!(
+        config.runtime.offline_policy,
+        OfflinePolicy::Fade(Duration::from_millis(1500))
+    );
+    assert_eq!(
+        config.heartbeat(),
+        HeartbeatOptions {
+            interval: Duration::from_millis(500),
+            idle_timeout: Duration::from_secs(5),
+            open_timeout: Duration::from_secs(20),
+        }
+    );
+    assert_eq!(config.auth.pin_role, Role::Controller);
+    assert_eq!(config.pin_rotation(), Some(Duration::from_secs(300)));
+    assert_eq!(
+        config.auth.tokens,
+        vec![
+            TokenConfig {
+                token: "secret".into(),
+                role: Role::Operator,
+            },
+            TokenConfig {
+                token: "monitor".into(),
+                role: Role::Viewer,
+            },
+        ]
+    );
+    assert!(Config::parse("[runtime]\noffline_policy = \"drift\"\n").is_err());
+}
+
+#[test]
+fn test_parse_webrtc() {
+    let config = Config::parse(
+        r#"
+        [webrtc]
+        udp_ports = "50000-50100"
+        interfaces = ["eth0"]
+        nat_ips = ["203.0.113.7"]
+
+        [[webrtc.ice_servers]]
+        urls = ["stun:stun.example.com:3478"]
+
+        [[webrtc.ice_servers]]
+        urls = ["turn:turn.example.com:3478?transport=udp"]
+        username = "stage"
+        credential = "secret"
+        "#,
+    )
+    .unwrap();
+    assert!(config.validate().is_ok());
+    let webrtc = &config.webrtc;
+    assert_eq!(webrtc.udp_ports.unwrap().to_string(), "50000-50100");
+    assert_eq!(webrtc.interfaces, vec!["eth0"]);
+    assert_eq!(
+        webrtc.nat_ips,
+        vec!["203.0.113.7".parse::<std::net::IpAddr>().unwrap()]
+    );
+    assert_eq!(webrtc.ice_servers.len(), 2);
+    assert_eq!(webrtc.ice_servers[1].username, "stage");
+
+    assert!(Config::parse("[webrtc]\nudp_ports = \"50100-50000\"\n").is_err());
+    let err = Config::parse("[[webrtc.ice_servers]]\nurls = [\"turn:turn.example.com\"]\n")
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(err.to_string().contains("webrtc"), "{err}");
+}
+
+#[test]
+fn test_parse_rejects_unknown_fields() {
+    let err = Config::parse("[http]\nport = 80\n").unwrap_err();
+    assert!(err.to_string().contains("unknown field"), "{err}");
+}
+
+#[test]
+fn test_validate() {
+    let invalid = |edit: fn(&mut Config)| {
+        let mut config = Config::default();
+        edit(&mut config);
+        config.validate().is_err()
+    };
+    assert!(invalid(|c| c.runtime.tick_rate = 0.0));
+    assert!(invalid(|c| c.runtime.tick_rate = f64::NAN));
+    assert!(invalid(|c| c.runtime.tick_rate = 10_000.0));
+    assert!(invalid(|c| c.runtime.event_pipe_capacity = 0));
+    assert!(invalid(|c| c.http.event_rate = 0.0));
+    assert!(invalid(|c| {
+        c.http.enabled = false;
+        c.quic.enabled = false;
+        c.mdns.enabled = false;
+    }));
+    assert!(invalid(|c| c.mdns.name = Some(String::new())));
+    assert!(invalid(|c| c.mdns.name = Some("x".repeat(64))));
+    assert!(invalid(|c| c.log_level = Some("cinemotion=[".into())));
+    assert!(invalid(|c| c.http.enabled = false));
+    assert!(invalid(|c| c.runtime.heartbeat_interval = 0.0));
+    assert!(invalid(|c| c.runtime.open_timeout = f64::INFINITY));
+    assert!(invalid(
+        |c| c.runtime.idle_timeout = c.runtime.heartbeat_interval
+    ));
+    assert!(invalid(|c| c.auth.pin_rotation = Some(-1.0)));
+    assert!(invalid(|c| c.auth.tokens = vec![TokenConfig {
+        token: String::new(),
+        role: Role::Viewer,
+    }]));
+    assert!(!invalid(|c| {
+        c.http.enabled = false;
+        c.quic.enabled = true;
+    }));
+    assert!(!invalid(|c| c.mdns.name = Some("stage-a".into())));
+    assert!(!invalid(
+        |c| c.log_level = Some("cinemotion=trace,quinn=warn".into())
+    ));
+}
+
+#[test]
+fn test_check_addresses_detects_collisions() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut config = Config::default();
+    config.http.address = listener.local_addr().unwrap();
+    config.quic.enabled = false;
+
+    let err = config.check_addresses().unwrap_err();
+    assert!(err.to_string().contains("http.address"), "{err}");
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+
+/// Find the cinemotion servers on the local network.
+#[derive(Args)]
+pub struct DiscoverCmd {
+    /// Seconds to browse the network for.
+    #[clap(long, default_value = "3", value_parser = crate::start::parse_seconds)]
+    timeout: Duration,
+}
+
+impl DiscoverCmd {
+    pub async fn run(&self) -> Result<i32> {
+        let servers = cinemotion::discovery::discover(self.timeout).await?;
+        if servers.is_empty() {
+            println!("no servers found");
+            return Ok(1);
+        }
+        for server in servers {
+            let addresses: Vec<String> = server.addresses.iter().map(|a| a.to_string()).collect();
+            println!("{} ({})", server.instance_name, server.hostname);
+            println!("  addresses:   {}", addresses.join(", "));
+            println!(
+                "  version:     {} (protocol {}{})",
+                server.version.as_deref().unwrap_or("unknown"),
+                server
+                    .protocol_version
+                    .map_or("unknown".to_string(), |v| v.to_string()),
+                match server.is_compatible() {
+                    true => "",
+                    false => ", incompatible",
+                }
+            );
+            for endpoint in &server.endpoints {
+                println!(
+                    "  {:<12} port {}{}",
+                    format!("{}:", endpoint.transport),
+                    endpoint.port,
+                    endpoint.path.as_deref().unwrap_or_default()
+                );
+            }
+            if let Some(fingerprint) = &server.fingerprint {
+                println!("  fingerprint: {fingerprint}");
+            }
+        }
+        Ok(0)
+    }
+}
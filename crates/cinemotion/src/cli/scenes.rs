@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use cinemotion::presets::{SceneStore, SCENES_DIR};
+use cinemotion::{Name, Scene};
+use clap::{Args, Subcommand};
+
+#[cfg(test)]
+#[path = "scenes_test.rs"]
+mod scenes_test;
+
+/// Manage the scenes saved with the `SaveScene` command.
+///
+/// The scenes are JSON files in the `scenes` directory of the config
+/// directory, a running server picks up changes when a scene is loaded.
+#[derive(Args)]
+pub struct ScenesCmd {
+    /// The directory the scenes are saved in, see `start --config-dir`.
+    #[clap(long, global = true)]
+    config_dir: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: ScenesCommand,
+}
+
+#[derive(Subcommand)]
+enum ScenesCommand {
+    /// List the saved scenes.
+    List,
+    /// Print a saved scene as JSON.
+    Show {
+        /// The name of the scene.
+        name: String,
+    },
+    /// Save a scene from a JSON file, like one printed by `show`.
+    ///
+    /// Replaces a saved scene of the same name.
+    Save {
+        /// The name to save the scene under.
+        name: String,
+        /// The JSON file of the scene.
+        file: PathBuf,
+    },
+    /// Delete a saved scene.
+    Delete {
+        /// The name of the scene.
+        name: String,
+    },
+}
+
+/// The scenes saved in the config directory.
+pub fn store(config_dir: &Path) -> SceneStore {
+    SceneStore::in_directory(config_dir.join(SCENES_DIR))
+}
+
+/// Read a scene from a JSON file and save it under the name.
+fn import(scenes: &mut SceneStore, name: &str, file: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let mut scene: Scene = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a scene", file.display()))?;
+    scene.name = Name::from(name);
+    scenes.save(&scene)?;
+    Ok(())
+}
+
+impl ScenesCmd {
+    pub fn run(&self) -> Result<i32> {
+        let dir = self
+            .config_dir
+            .clone()
+            .or_else(cinemotion::tls::config_dir)
+            .context("no config directory, pass --config-dir")?;
+        let mut scenes = store(&dir);
+        match &self.command {
+            ScenesCommand::List => {
+                let names = scenes.list()?;
+                if names.is_empty() {
+                    println!("no scenes saved");
+                }
+                for name in names {
+                    println!("{name}");
+                }
+            }
+            ScenesCommand::Show { name } => {
+                let scene = scenes.load(&Name::from(name.as_str()))?;
+                println!("{}", serde_json::to_string_pretty(&scene)?);
+            }
+            ScenesCommand::Save { name, file } => {
+                import(&mut scenes, name, file)?;
+                println!("saved {name}");
+            }
+            ScenesCommand::Delete { name } => {
+                scenes.delete(&Name::from(name.as_str()))?;
+                println!("deleted {name}");
+            }
+        }
+        Ok(0)
+    }
+}
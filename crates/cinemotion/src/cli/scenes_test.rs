@@ -0,0 +1,36 @@
+use cinemotion::name;
+
+use super::*;
+
+#[test]
+fn test_import() {
+    let temp = tempfile::tempdir().unwrap();
+    let file = temp.path().join("crane.json");
+    let mut scene = Scene::default();
+    scene.name = name!("exported");
+    std::fs::write(&file, serde_json::to_string(&scene).unwrap()).unwrap();
+
+    let mut scenes = SceneStore::in_memory();
+    import(&mut scenes, "crane", &file).unwrap();
+    assert_eq!(scenes.list().unwrap(), vec![name!("crane")]);
+    assert_eq!(
+        scenes.load(&name!("crane")).unwrap().objects(),
+        scene.objects()
+    );
+
+    // Invalid names and files that are no scenes are not saved.
+    assert!(import(&mut scenes, ".hidden", &file).is_err());
+    std::fs::write(&file, "{\"objects\": 3}").unwrap();
+    assert!(import(&mut scenes, "broken", &file).is_err());
+    assert!(import(&mut scenes, "missing", &temp.path().join("missing.json")).is_err());
+
+    // Objects have to be keyed by their own names.
+    let mut mismatched = Scene::default();
+    mismatched.objects_mut().insert(
+        name!("crane"),
+        cinemotion::SceneObject::new(name!("camera"), Default::default()),
+    );
+    std::fs::write(&file, serde_json::to_string(&mismatched).unwrap()).unwrap();
+    assert!(import(&mut scenes, "mismatched", &file).is_err());
+    assert_eq!(scenes.list().unwrap(), vec![name!("crane")]);
+}
@@ -1,51 +1,365 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
+use cinemotion::auth::Authenticator;
+use cinemotion::connection::Role;
+use cinemotion::events::event_pipe_with_capacity;
+use cinemotion::presets::SceneStore;
+use cinemotion::services::api::RestApi;
+use cinemotion::services::mdns::Advertisement;
 use cinemotion::services::runtime::{RuntimeOptions, RuntimeService};
+use cinemotion::services::sse::EventStream;
+use cinemotion::tls::Identity;
+use cinemotion::webrtc::options::{IceServer, PortRange};
 use cinemotion::webrtc::SignalingRelay;
 use clap::Args;
+
+use crate::config::{Config, TokenConfig, CONFIG_FILE};
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 
+#[cfg(test)]
+#[path = "start_test.rs"]
+mod start_test;
+
 /// Start the cinemotion broker services.
 #[derive(Args)]
 pub struct StartCmd {
+    /// The TOML config file, defaults to `cinemotion.toml` in the config directory.
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// The address the http service binds to.
     #[clap(long = "address")]
-    server_bind_address: Option<std::net::SocketAddr>,
+    server_bind_address: Option<SocketAddr>,
+
+    /// The address the quic service binds to.
+    #[clap(long)]
+    quic_address: Option<SocketAddr>,
+
+    /// Do not start the http service.
+    #[clap(long)]
+    no_http: bool,
+
+    /// Start the experimental quic service.
+    #[clap(long)]
+    quic: bool,
+
+    /// Do not advertise the service over mDNS.
+    #[clap(long)]
+    no_mdns: bool,
+
+    /// The mDNS instance name, defaults to `cinemotion-<hostname>`.
+    #[clap(long)]
+    mdns_name: Option<String>,
+
+    /// Engine ticks per second.
+    #[clap(long)]
+    tick_rate: Option<f64>,
+
+    /// The number of events each connection may lag behind before it misses events.
+    #[clap(long)]
+    event_pipe_capacity: Option<usize>,
+
+    /// State events per second streamed to `GET /events` subscribers.
+    #[clap(long)]
+    event_rate: Option<f64>,
+
+    /// A STUN or TURN server url used to gather WebRTC candidates, may be repeated.
+    ///
+    /// TURN servers need credentials and are configured in the config file.
+    #[clap(long = "ice-server")]
+    ice_servers: Vec<String>,
+
+    /// The UDP ports WebRTC connections bind to, written as `<min>-<max>`.
+    #[clap(long)]
+    udp_ports: Option<PortRange>,
+
+    /// A network interface to gather WebRTC candidates on, may be repeated.
+    #[clap(long = "interface")]
+    interfaces: Vec<String>,
+
+    /// The public address of a 1:1 NAT offered to WebRTC peers, may be repeated.
+    #[clap(long = "nat-ip")]
+    nat_ips: Vec<IpAddr>,
+
+    /// A level like `debug` or a tracing filter, the verbosity flags take precedence.
+    #[clap(long)]
+    log_level: Option<String>,
+
+    /// What bound properties do while their controller is disconnected.
+    ///
+    /// One of `hold`, `reset` or `fade:<seconds>`.
+    #[clap(long)]
+    offline_policy: Option<cinemotion::data::OfflinePolicy>,
+
+    /// Seconds between heartbeats sent to every connection.
+    #[clap(long, value_parser = parse_seconds)]
+    heartbeat_interval: Option<Duration>,
+
+    /// Seconds a connection may be silent before it is closed.
+    #[clap(long, value_parser = parse_seconds)]
+    idle_timeout: Option<Duration>,
+
+    /// Seconds a connection may take to open before it is closed.
+    #[clap(long, value_parser = parse_seconds)]
+    open_timeout: Option<Duration>,
+
+    /// A pre-shared join token accepted in place of the PIN, may be repeated.
+    ///
+    /// Written as `<token>` or `<token>:<role>` where the role is one of
+    /// `operator`, `controller` or `viewer`, tokens are operators by default.
+    /// A token may contain colons as long as it does not end in a role.
+    #[clap(long = "token", value_parser = parse_token)]
+    tokens: Vec<(String, Role)>,
+
+    /// The role of peers that join with the pairing PIN.
+    #[clap(long)]
+    pin_role: Option<Role>,
+
+    /// Seconds between pairing PIN rotations, the PIN never rotates when not set.
+    #[clap(long, value_parser = parse_seconds)]
+    pin_rotation: Option<Duration>,
+
+    /// The directory the server identity and saved scenes are persisted in.
+    ///
+    /// Defaults to `$XDG_CONFIG_HOME/cinemotion` or `~/.config/cinemotion`.
+    #[clap(long)]
+    config_dir: Option<PathBuf>,
+
+    /// The saved scene to start with, see `cinemotion scenes list`.
+    #[clap(long)]
+    scene: Option<String>,
+
+    /// A PEM certificate chain to present instead of the generated one.
+    #[clap(long, requires = "key")]
+    cert: Option<PathBuf>,
+
+    /// The PEM private key of the certificate given with `--cert`.
+    #[clap(long, requires = "cert")]
+    key: Option<PathBuf>,
+
+    /// Let any peer join without a PIN or token.
+    #[clap(long, conflicts_with_all = ["tokens", "pin_rotation", "pin_role"])]
+    no_auth: bool,
+}
+
+/// Parse a positive, finite number of seconds.
+pub fn parse_seconds(s: &str) -> std::result::Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| format!("invalid number of seconds, expected more than zero: {s}"))
+}
+
+/// Parse `<token>:<role>`, a suffix that is no role is part of the token.
+fn parse_token(s: &str) -> std::result::Result<(String, Role), String> {
+    match s.rsplit_once(':') {
+        Some((token, role)) if !token.is_empty() => match role.parse() {
+            Ok(role) => Ok((token.to_string(), role)),
+            Err(_) => Ok((s.to_string(), Role::Operator)),
+        },
+        _ => Ok((s.to_string(), Role::Operator)),
+    }
 }
 
 impl StartCmd {
+    /// Load the config file and apply the command line overrides.
+    pub fn config(&self) -> Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => match self.config_dir().map(|dir| dir.join(CONFIG_FILE)) {
+                Some(path) if path.exists() => Config::load(&path)?,
+                _ => Config::default(),
+            },
+        };
+        if let Some(address) = self.server_bind_address {
+            config.http.address = address;
+        }
+        if let Some(address) = self.quic_address {
+            config.quic.address = address;
+        }
+        config.http.enabled &= !self.no_http;
+        config.quic.enabled |= self.quic;
+        config.mdns.enabled &= !self.no_mdns;
+        if self.mdns_name.is_some() {
+            config.mdns.name = self.mdns_name.clone();
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            config.runtime.tick_rate = tick_rate;
+        }
+        if let Some(capacity) = self.event_pipe_capacity {
+            config.runtime.event_pipe_capacity = capacity;
+        }
+        if let Some(event_rate) = self.event_rate {
+            config.http.event_rate = event_rate;
+        }
+        if !self.ice_servers.is_empty() {
+            config.webrtc.ice_servers = self.ice_servers.iter().map(IceServer::new).collect();
+        }
+        if self.udp_ports.is_some() {
+            config.webrtc.udp_ports = self.udp_ports;
+        }
+        if !self.interfaces.is_empty() {
+            config.webrtc.interfaces = self.interfaces.clone();
+        }
+        if !self.nat_ips.is_empty() {
+            config.webrtc.nat_ips = self.nat_ips.clone();
+        }
+        if self.log_level.is_some() {
+            config.log_level = self.log_level.clone();
+        }
+        if let Some(policy) = self.offline_policy {
+            config.runtime.offline_policy = policy;
+        }
+        if let Some(interval) = self.heartbeat_interval {
+            config.runtime.heartbeat_interval = interval.as_secs_f64();
+        }
+        if let Some(timeout) = self.idle_timeout {
+            config.runtime.idle_timeout = timeout.as_secs_f64();
+        }
+        if let Some(timeout) = self.open_timeout {
+            config.runtime.open_timeout = timeout.as_secs_f64();
+        }
+        if !self.tokens.is_empty() {
+            config.auth.tokens = self
+                .tokens
+                .iter()
+                .map(|(token, role)| TokenConfig {
+                    token: token.clone(),
+                    role: *role,
+                })
+                .collect();
+        }
+        if let Some(role) = self.pin_role {
+            config.auth.pin_role = role;
+        }
+        if let Some(rotation) = self.pin_rotation {
+            config.auth.pin_rotation = Some(rotation.as_secs_f64());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn config_dir(&self) -> Option<PathBuf> {
+        self.config_dir.clone().or_else(cinemotion::tls::config_dir)
+    }
+
+    /// The scenes saved in the config directory, or in memory without one.
+    fn scene_store(&self) -> SceneStore {
+        match self.config_dir() {
+            Some(dir) => crate::scenes::store(&dir),
+            None => {
+                tracing::warn!("no config directory, saved scenes are lost on exit");
+                SceneStore::in_memory()
+            }
+        }
+    }
+
+    /// Load the user provided identity or the persisted one.
+    fn identity(&self) -> Result<Identity> {
+        if let (Some(cert), Some(key)) = (&self.cert, &self.key) {
+            return Ok(Identity::from_pem_files(cert, key)?);
+        }
+        let dir = self
+            .config_dir()
+            .ok_or_else(|| anyhow::anyhow!("no config directory, pass --config-dir"))?;
+        Ok(Identity::load_or_generate(&dir)?)
+    }
+
     pub async fn run(&self) -> Result<i32> {
         tracing::info!("starting...");
+        let config = self.config()?;
+        config.check_addresses()?;
+        let identity = self.identity()?;
+        let fingerprint = identity.fingerprint();
+        println!("certificate fingerprint: {fingerprint}");
+
         let mut services: Vec<Pin<Box<dyn cinemotion::services::Service>>> = vec![];
         let (cancel_tx, mut cancel_rx) = tokio::sync::mpsc::channel(1);
 
+        let scene_store = self.scene_store();
+        let initial_scene = match &self.scene {
+            Some(name) => Some(scene_store.load(&name.as_str().into())?),
+            None => None,
+        };
+
         let (sender, reciever) = cinemotion::messages::message_pipe();
-        let relay = SignalingRelay::new(sender.clone());
+        let event_pipe = event_pipe_with_capacity(config.runtime.event_pipe_capacity);
+        let auth = Arc::new(match self.no_auth {
+            true => {
+                tracing::warn!("authentication is disabled, any peer on the network can join");
+                Authenticator::disabled()
+            }
+            false => Authenticator::with_tokens(
+                config
+                    .auth
+                    .tokens
+                    .iter()
+                    .map(|token| (token.token.clone(), token.role)),
+            )
+            .with_pin_role(config.auth.pin_role),
+        });
+        let pin_task = (!self.no_auth).then(|| spawn_pin_rotation(&auth, config.pin_rotation()));
+        let relay = SignalingRelay::new(sender.clone())
+            .with_authenticator(Arc::clone(&auth))
+            .with_options(config.webrtc.clone());
 
         tracing::info!("configure runtime services");
         let runtime = Box::pin(RuntimeService::new(RuntimeOptions {
-            message_pipe: (sender, reciever),
+            message_pipe: (sender.clone(), reciever),
+            offline_policy: config.runtime.offline_policy,
+            heartbeat: config.heartbeat(),
+            tick_interval: config.tick_interval(),
+            event_pipe: event_pipe.clone(),
+            scene_store,
+            initial_scene,
         }));
         services.push(runtime);
 
-        // Build the default binding addr for the signaling server.
-        let bind_addr = self.server_bind_address.unwrap_or(
-            format!("0.0.0.0:{}", cinemotion::DEFAULT_WEB_PORT)
-                .parse()
-                .unwrap(),
-        );
-        tracing::info!("broadcasting service on: http://{}", bind_addr);
+        if config.quic.enabled {
+            tracing::debug!("configure quic service");
+            services.push(Box::pin(cinemotion::services::quic::QuicService::new(
+                config.quic.address,
+                sender.clone(),
+                Arc::clone(&auth),
+                identity,
+            )));
+        }
 
-        tracing::debug!("configure the mdns service");
-        services.push(Box::pin(cinemotion::services::mdns::MdnsService::new(
-            bind_addr.port(),
-        )));
+        if config.mdns.enabled {
+            tracing::debug!("configure the mdns service");
+            let advertisement = Advertisement {
+                instance_name: config
+                    .mdns
+                    .name
+                    .clone()
+                    .unwrap_or_else(Advertisement::default_instance_name),
+                http_port: config.http.enabled.then_some(config.http.address.port()),
+                quic_port: config.quic.enabled.then_some(config.quic.address.port()),
+                fingerprint: Some(fingerprint.clone()),
+            };
+            services.push(Box::pin(cinemotion::services::mdns::MdnsService::new(
+                advertisement,
+            )));
+        }
 
-        tracing::debug!("configure http service");
-        services.push(Box::pin(cinemotion::services::http::HttpService::new(
-            bind_addr, relay,
-        )));
+        if config.http.enabled {
+            tracing::info!("broadcasting service on: http://{}", config.http.address);
+            services.push(Box::pin(cinemotion::services::http::HttpService::new(
+                config.http.address,
+                relay,
+                RestApi::new(sender.clone(), Arc::clone(&auth)),
+                EventStream::new(event_pipe, Arc::clone(&auth))
+                    .with_state_interval(config.event_interval()),
+                Some(fingerprint),
+            )));
+        }
 
         let interrupt_task = tokio::task::spawn(async move {
             tracing::debug!("listening for interrupt signals...");
@@ -80,7 +394,31 @@ impl StartCmd {
         {}
 
         interrupt_task.abort();
+        if let Some(pin_task) = pin_task {
+            pin_task.abort();
+        }
 
         Ok(0)
     }
 }
+
+/// Print the pairing PIN and keep rotating it on the given interval.
+fn spawn_pin_rotation(
+    auth: &Arc<Authenticator>,
+    rotation: Option<Duration>,
+) -> tokio::task::JoinHandle<()> {
+    println!("pairing PIN: {}", auth.rotate_pin());
+    let auth = Arc::clone(auth);
+    tokio::task::spawn(async move {
+        let Some(rotation) = rotation else {
+            return;
+        };
+        let mut interval = tokio::time::interval(rotation);
+        // The first tick completes immediately and the PIN was just set.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            println!("pairing PIN rotated: {}", auth.rotate_pin());
+        }
+    })
+}
@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn test_parse_seconds() {
+    assert_eq!(parse_seconds("1.5"), Ok(Duration::from_millis(1500)));
+    assert_eq!(parse_seconds("10"), Ok(Duration::from_secs(10)));
+    for invalid in ["0", "0.0", "-1", "NaN", "inf", "", "soon"] {
+        assert!(
+            parse_seconds(invalid).is_err(),
+            "{invalid} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_parse_token() {
+    assert_eq!(
+        parse_token("secret"),
+        Ok(("secret".to_string(), Role::Operator))
+    );
+    assert_eq!(
+        parse_token("monitor:viewer"),
+        Ok(("monitor".to_string(), Role::Viewer))
+    );
+    // Colons that do not introduce a role belong to the token.
+    assert_eq!(
+        parse_token("abc:def"),
+        Ok(("abc:def".to_string(), Role::Operator))
+    );
+    assert_eq!(
+        parse_token("a:b:controller"),
+        Ok(("a:b".to_string(), Role::Controller))
+    );
+}
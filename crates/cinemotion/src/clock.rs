@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+#[path = "clock_test.rs"]
+mod clock_test;
+
+/// Return the current server time in microseconds since the unix epoch.
+///
+/// This is the time base used for all timestamps exchanged with clients.
+pub fn now_micros() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_micros() as i64,
+        Err(err) => -(err.duration().as_micros() as i64),
+    }
+}
+
+/// The number of exchanges used to estimate the clock of a device.
+const SYNC_WINDOW: usize = 8;
+
+/// Estimates the clock offset and drift of a device relative to the server.
+///
+/// The estimate is built from NTP-style exchanges where `t0` is the server time a
+/// request was sent, `t1` and `t2` are the device times it was received and answered,
+/// and `t3` is the server time the answer was received.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ClockSync {
+    samples: VecDeque<SyncSample>,
+    offset: Option<i64>,
+    drift: Option<f64>,
+    reference: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SyncSample {
+    at: i64,
+    offset: i64,
+    delay: i64,
+}
+
+impl ClockSync {
+    /// Record the timestamps of a completed exchange and update the estimate.
+    ///
+    /// The timestamps come from the device, an exchange that cannot have happened,
+    /// like one answered before it was sent, is ignored and `false` returned.
+    pub fn record(&mut self, t0: i64, t1: i64, t2: i64, t3: i64) -> bool {
+        // Widen the timestamps so no combination of them can overflow.
+        let (t0, t1, t2, t3) = (t0 as i128, t1 as i128, t2 as i128, t3 as i128);
+        let round_trip = t3 - t0;
+        let processing = t2 - t1;
+        if round_trip < 0 || processing < 0 || processing > round_trip {
+            return false;
+        }
+        let (Ok(offset), Ok(delay), Ok(at)) = (
+            i64::try_from(((t1 - t0) + (t2 - t3)) / 2),
+            i64::try_from(round_trip - processing),
+            i64::try_from(t0 + round_trip / 2),
+        ) else {
+            return false;
+        };
+        self.samples.push_back(SyncSample { at, offset, delay });
+        if self.samples.len() > SYNC_WINDOW {
+            self.samples.pop_front();
+        }
+        self.estimate();
+        true
+    }
+
+    /// The estimated offset of the device clock from the server clock in microseconds.
+    pub fn offset(&self) -> Option<i64> {
+        self.offset
+    }
+
+    /// The estimated drift of the device clock in parts per million.
+    pub fn drift_ppm(&self) -> Option<f64> {
+        self.drift.map(|drift| drift * 1_000_000.0)
+    }
+
+    /// Map a device timestamp in microseconds into server time.
+    ///
+    /// The timestamp is returned unchanged until an exchange has been recorded.
+    pub fn to_server_time(&self, device_time: i64) -> i64 {
+        let Some(offset) = self.offset else {
+            return device_time;
+        };
+        let elapsed = device_time as f64 - offset as f64 - self.reference as f64;
+        let correction = self.drift.map_or(0.0, |drift| drift * elapsed) as i64;
+        device_time
+            .saturating_sub(offset)
+            .saturating_sub(correction)
+    }
+
+    fn estimate(&mut self) {
+        // The exchange with the smallest round trip has the least asymmetric
+        // network delay and so the most trustworthy offset.
+        let Some(best) = self.samples.iter().min_by_key(|sample| sample.delay) else {
+            return;
+        };
+        self.offset = Some(best.offset);
+        self.reference = best.at;
+
+        // Fit a line through the offsets over time to estimate the drift.
+        let count = self.samples.len() as f64;
+        let mean_at = self.samples.iter().map(|s| s.at as f64).sum::<f64>() / count;
+        let mean_offset = self.samples.iter().map(|s| s.offset as f64).sum::<f64>() / count;
+        let (covariance, variance) =
+            self.samples
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), sample| {
+                    let dt = sample.at as f64 - mean_at;
+                    let doffset = sample.offset as f64 - mean_offset;
+                    (covariance + dt * doffset, variance + dt * dt)
+                });
+        self.drift = match variance > 0.0 {
+            true => Some(covariance / variance),
+            false => None,
+        };
+    }
+}
@@ -0,0 +1,76 @@
+use super::*;
+
+#[test]
+fn test_clock_sync_offset() {
+    let mut clock = ClockSync::default();
+    assert_eq!(clock.to_server_time(5_000), 5_000);
+
+    // The device clock is 1000us ahead with 50us of delay each way.
+    clock.record(0, 1_050, 1_060, 110);
+
+    assert_eq!(clock.offset(), Some(1_000));
+    assert_eq!(clock.drift_ppm(), None);
+    assert_eq!(clock.to_server_time(6_000), 5_000);
+}
+
+#[test]
+fn test_clock_sync_prefers_smallest_delay() {
+    let mut clock = ClockSync::default();
+    clock.record(0, 1_050, 1_060, 110);
+    // A slow exchange with asymmetric delay skews the offset.
+    clock.record(1_000, 2_500, 2_510, 1_600);
+
+    assert_eq!(clock.offset(), Some(1_000));
+}
+
+#[test]
+fn test_clock_sync_drift() {
+    let mut clock = ClockSync::default();
+    // The device clock gains 10us every second.
+    for second in 0..4 {
+        let t0 = second * 1_000_000;
+        let offset = 1_000 + second * 10;
+        clock.record(t0, t0 + offset + 50, t0 + offset + 60, t0 + 110);
+    }
+
+    let drift = clock.drift_ppm().expect("drift should be estimated");
+    assert!((drift - 10.0).abs() < 0.01, "unexpected drift {drift}");
+
+    // Ten seconds after the reference exchange the offset has grown by 100us.
+    let offset = clock.offset().unwrap();
+    let server_time = 10_000_055;
+    let device_time = server_time + offset + 100;
+    assert!((clock.to_server_time(device_time) - server_time).abs() <= 1);
+}
+
+#[test]
+fn test_clock_sync_rejects_implausible_exchanges() {
+    let mut clock = ClockSync::default();
+    // Answered before it was sent.
+    assert!(!clock.record(1_000, 1_050, 1_060, 900));
+    // The device took longer to answer than the whole round trip.
+    assert!(!clock.record(0, 1_000, 2_000, 110));
+    // The device replied before it received the request.
+    assert!(!clock.record(0, 1_060, 1_050, 110));
+    assert_eq!(clock.offset(), None);
+
+    assert!(clock.record(0, 1_050, 1_060, 110));
+    assert_eq!(clock.offset(), Some(1_000));
+}
+
+#[test]
+fn test_clock_sync_extreme_values() {
+    let mut clock = ClockSync::default();
+    assert!(!clock.record(i64::MAX, i64::MIN, i64::MAX, i64::MIN));
+    assert!(!clock.record(i64::MIN, i64::MAX, i64::MAX, 0));
+    assert!(clock.record(0, i64::MAX, i64::MAX, 0));
+    assert!(clock.offset().is_some());
+
+    // Mapping extreme device times saturates instead of overflowing.
+    assert_eq!(clock.to_server_time(i64::MIN), i64::MIN);
+    clock.to_server_time(i64::MAX);
+
+    let mut clock = ClockSync::default();
+    assert!(clock.record(0, i64::MIN, i64::MIN, 0));
+    assert_eq!(clock.to_server_time(i64::MAX), i64::MAX);
+}
@@ -24,13 +24,13 @@ impl Connection {
         uid: usize,
         message_pipe: MessagePipeTx,
         mut event_pipe: EventPipeRx,
-        mut agent: Box<dyn ConnectionAgent + Send + Sync>,
+        agent: Box<dyn ConnectionAgent + Send + Sync>,
     ) -> Self {
-        agent.initialize(Self::make_send(uid, message_pipe));
-
+        let send_fn = Self::make_send(uid, message_pipe);
         let agent = Arc::new(Mutex::new(agent));
         let shared_agent = Arc::clone(&agent);
         let task = tokio::spawn(async move {
+            shared_agent.lock().await.initialize(send_fn).await;
             loop {
                 let event = match event_pipe.recv().await {
                     Ok(event) => event,
@@ -51,9 +51,15 @@ impl Connection {
         Connection { uid, task, agent }
     }
 
+    /// Stop forwarding events and close the agent.
+    pub async fn close(&self) {
+        self.task.abort();
+        self.agent.lock().await.close().await;
+    }
+
     fn make_send(uid: usize, message_pipe: MessagePipeTx) -> SendHandlerFn {
-        Box::new(move |command: Payload| {
-            let message = Message::with_command(uid, command);
+        Box::new(move |command: Payload, request_id: Option<u64>| {
+            let message = Message::with_command(uid, command).with_request_id(request_id);
             if let Err(err) = message_pipe.send(message) {
                 let msg = format!(
                     "connection {} failed to send message, pipe broken. err={err}",
@@ -70,7 +76,8 @@ impl Connection {
 
 impl Drop for Connection {
     fn drop(&mut self) {
+        // The agent is closed by `close`, dropping may happen outside of a
+        // runtime where it could not be awaited.
         self.task.abort();
-        self.agent.blocking_lock().close();
     }
 }
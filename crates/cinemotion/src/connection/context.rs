@@ -1,7 +1,30 @@
+use tokio::time::Instant;
+
+use super::{LatencyStats, Role};
+use crate::clock::ClockSync;
 use crate::Name;
 
 #[derive(Default, Debug, Clone)]
 pub struct Context {
     pub uid: usize,
     pub name: Option<Name>,
+    /// What the connection is allowed to do.
+    pub role: Role,
+    /// Whether the agent reported the connection as open.
+    pub opened: bool,
+    /// When the connection was added.
+    pub connected_at: Option<Instant>,
+    /// When a message was last received from the connection.
+    pub last_seen: Option<Instant>,
+    /// The timestamp of the last ping sent to the connection, until it is
+    /// answered.
+    pub ping_timestamp: Option<i64>,
+    /// The round trip time statistics of the connection.
+    pub latency: LatencyStats,
+    /// The estimated clock of the device on the connection.
+    pub clock: ClockSync,
+    /// The protocol version the client initialized with.
+    pub protocol_version: Option<u32>,
+    /// The capabilities both the client and the server support.
+    pub capabilities: Vec<String>,
 }
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Options for detecting dead connections.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeartbeatOptions {
+    /// How often a ping is sent to every connection.
+    pub interval: Duration,
+    /// How long a connection may be silent before it is closed.
+    pub idle_timeout: Duration,
+    /// How long a connection may take to open before it is closed.
+    pub open_timeout: Duration,
+}
+
+impl Default for HeartbeatOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            idle_timeout: Duration::from_secs(10),
+            open_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Round trip time statistics for a connection.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    /// The most recently measured round trip time.
+    pub last: Option<Duration>,
+    /// The smallest measured round trip time.
+    pub min: Option<Duration>,
+    /// The largest measured round trip time.
+    pub max: Option<Duration>,
+    /// The smoothed round trip time.
+    pub average: Option<Duration>,
+    /// The number of round trips measured.
+    pub samples: u64,
+}
+
+impl LatencyStats {
+    /// Record a measured round trip time.
+    pub fn record(&mut self, rtt: Duration) {
+        self.last = Some(rtt);
+        self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+        self.max = Some(self.max.map_or(rtt, |max| max.max(rtt)));
+        // Smooth the average the same way TCP smooths its round trip time.
+        self.average = Some(match self.average {
+            Some(average) => average.mul_f64(7.0 / 8.0) + rtt.mul_f64(1.0 / 8.0),
+            None => rtt,
+        });
+        self.samples += 1;
+    }
+}
@@ -1,13 +1,19 @@
 mod agent;
 mod connection;
 mod context;
+mod heartbeat;
+mod role;
 
 use crate::messages::Payload;
 use crate::Result;
 
 pub const LOCAL_CONN_ID: usize = 0;
-pub type SendHandlerFn = Box<dyn (FnMut(Payload) -> Result<()>) + Send + Sync>;
+/// Sends a command to the engine, along with the request id the client asked
+/// it to be acknowledged with.
+pub type SendHandlerFn = Box<dyn (FnMut(Payload, Option<u64>) -> Result<()>) + Send + Sync>;
 
 pub use agent::*;
 pub use connection::*;
 pub use context::*;
+pub use heartbeat::*;
+pub use role::*;
@@ -0,0 +1,82 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::messages::ClientCommand;
+
+/// What a connection is allowed to do, assigned when the peer authenticates.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Directs the session: changes the mode and edits the scene.
+    #[default]
+    Operator,
+    /// A motion device that can only register itself and send samples.
+    Controller,
+    /// Only receives events.
+    Viewer,
+}
+
+impl Role {
+    /// Whether a connection with this role may issue the command.
+    pub fn permits(&self, command: &ClientCommand) -> bool {
+        match command {
+            // Keeping the connection alive is allowed for everyone.
+            ClientCommand::Echo(_) | ClientCommand::Pong(_) | ClientCommand::TimeSync(_) => true,
+            // Reading is allowed for everyone like the REST API.
+            ClientCommand::ListScenes(_) => true,
+            ClientCommand::Init(_) | ClientCommand::SampleMotion(_) => {
+                matches!(self, Self::Operator | Self::Controller)
+            }
+            ClientCommand::ChangeMode(_)
+            | ClientCommand::AddSceneObject(_)
+            | ClientCommand::ClearScene(_)
+            | ClientCommand::DeleteSceneObject(_)
+            | ClientCommand::UpdateSceneObject(_)
+            | ClientCommand::RenameSceneObject(_)
+            | ClientCommand::BindProperty(_)
+            | ClientCommand::UnbindProperty(_)
+            | ClientCommand::SetPropertyValue(_)
+            | ClientCommand::AddProperty(_)
+            | ClientCommand::RemoveProperty(_)
+            | ClientCommand::AdjustProperty(_)
+            | ClientCommand::SaveScene(_)
+            | ClientCommand::LoadScene(_)
+            | ClientCommand::Undo(_)
+            | ClientCommand::Redo(_) => matches!(self, Self::Operator),
+            ClientCommand::Batch(batch) => batch.0.iter().all(|command| self.permits(command)),
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Operator => write!(f, "operator"),
+            Self::Controller => write!(f, "controller"),
+            Self::Viewer => write!(f, "viewer"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    /// Parse a role from its name.
+    ///
+    /// ```
+    /// use cinemotion::connection::Role;
+    ///
+    /// assert_eq!("viewer".parse::<Role>(), Ok(Role::Viewer));
+    /// assert_eq!("director".parse::<Role>(), Ok(Role::Operator));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "operator" | "director" => Ok(Self::Operator),
+            "controller" => Ok(Self::Controller),
+            "viewer" => Ok(Self::Viewer),
+            _ => Err(format!("unknown role: {s}")),
+        }
+    }
+}
@@ -1,30 +1,110 @@
-use crate::Name;
+use crate::{Error, Name, Result};
 use cinemotion_proto as proto;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::Property;
+use super::{Property, Sample, Value};
+
+#[cfg(test)]
+#[path = "controllers_test.rs"]
+mod controllers_test;
 
 /// Represents a controller in the system.
 ///
 /// A controller is a source of motion in the system. It can be used to control
 /// the motion of a scene object by binding a property to a controller property.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Controller {
     /// The name of the controller used for users.
     pub name: Name,
     /// The properties of the controller that hold motion state.
     pub properties: HashMap<Name, Property>,
+    /// A stable identifier supplied by the device used to recognize it when it reconnects.
+    pub device_id: Option<Name>,
+    /// Whether the controller is currently connected.
+    pub status: ControllerStatus,
+    /// The server time in microseconds of the last sample that carried a device timestamp.
+    pub sampled_at: Option<i64>,
+}
+
+impl Controller {
+    /// Create a new connected controller with the given properties.
+    pub fn new(name: Name, properties: HashMap<Name, Property>) -> Self {
+        Self {
+            name,
+            properties,
+            device_id: None,
+            status: ControllerStatus::Connected,
+            sampled_at: None,
+        }
+    }
+
+    /// Return whether the controller is the same device as the other controller.
+    ///
+    /// Controllers without a device id are never considered the same device.
+    pub fn is_same_device(&self, other: &Controller) -> bool {
+        self.device_id.is_some() && self.device_id == other.device_id
+    }
+
+    /// Validate the property declarations of the controller.
+    pub fn validate(&self) -> Result<()> {
+        self.properties
+            .values()
+            .try_for_each(|property| property.validate_schema())
+    }
+
+    /// Validate that every value in the sample matches a declared property.
+    ///
+    /// This will error with `UnknownProperty` if the sample contains a property that
+    /// was not declared and with the property's validation error if a value is invalid.
+    pub fn validate_sample(&self, sample: &Sample) -> Result<()> {
+        for (name, value) in sample.properties() {
+            let Some(property) = self.properties.get(name) else {
+                return Err(Error::UnknownProperty(format!("{}.{}", self.name, name)));
+            };
+            property.validate(value)?;
+        }
+        Ok(())
+    }
+
+    /// Validate that a scene object property holding the given value can be bound
+    /// to the named property of the controller.
+    pub fn validate_binding(&self, property: &Name, value: &Value) -> Result<()> {
+        let Some(property) = self.properties.get(property) else {
+            return Err(Error::InvalidBinding(format!(
+                "controller {} has no property {}",
+                self.name, property
+            )));
+        };
+        if property.value_type() != value.value_type() {
+            return Err(Error::InvalidBinding(format!(
+                "cannot bind a {} value to {}.{} which is a {} value",
+                value.value_type(),
+                self.name,
+                property.name,
+                property.value_type()
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl From<proto::ControllerDef> for Controller {
     fn from(value: proto::ControllerDef) -> Self {
+        let mut schemas = value.schemas;
         Self {
             name: value.name.into(),
+            device_id: Some(value.device_id)
+                .filter(|id| !id.is_empty())
+                .map(Into::into),
+            status: ControllerStatus::Connected,
+            sampled_at: None,
             properties: value
                 .properties
                 .into_iter()
                 .map(|(name, value)| {
-                    let property = Property::with_default_value(name.clone().into(), value.into());
+                    let schema = schemas.remove(&name).map(Into::into).unwrap_or_default();
+                    let property = Property::with_schema(name.clone().into(), value.into(), schema);
                     (name.into(), property)
                 })
                 .collect(),
@@ -36,6 +116,14 @@ impl From<proto::Controller> for Controller {
     fn from(value: proto::Controller) -> Self {
         Self {
             name: value.name.into(),
+            device_id: Some(value.device_id)
+                .filter(|id| !id.is_empty())
+                .map(Into::into),
+            status: match value.connected {
+                true => ControllerStatus::Connected,
+                false => ControllerStatus::Disconnected,
+            },
+            sampled_at: Some(value.sampled_at).filter(|at| *at != 0),
             properties: value
                 .properties
                 .into_iter()
@@ -49,6 +137,9 @@ impl From<Controller> for proto::Controller {
     fn from(value: Controller) -> Self {
         Self {
             name: value.name.to_string(),
+            device_id: value.device_id.map(|id| id.to_string()).unwrap_or_default(),
+            connected: value.status.is_connected(),
+            sampled_at: value.sampled_at.unwrap_or_default(),
             properties: value
                 .properties
                 .into_iter()
@@ -57,3 +148,68 @@ impl From<Controller> for proto::Controller {
         }
     }
 }
+
+/// The connection status of a controller.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerStatus {
+    #[default]
+    Connected,
+    Disconnected,
+}
+
+impl ControllerStatus {
+    /// Returns true if the controller is connected.
+    pub fn is_connected(&self) -> bool {
+        *self == Self::Connected
+    }
+}
+
+/// Determines what happens to the properties of a controller while it is disconnected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum OfflinePolicy {
+    /// Hold the last received values until the controller reconnects.
+    #[default]
+    Hold,
+    /// Reset the properties to their default values immediately.
+    Reset,
+    /// Linearly fade the properties to their default values over the given duration.
+    Fade(std::time::Duration),
+}
+
+/// Parse an offline policy from `hold`, `reset` or `fade:<seconds>`.
+///
+/// ```
+/// use cinemotion::data::OfflinePolicy;
+/// let policy: OfflinePolicy = "fade:1.5".parse().unwrap();
+/// assert_eq!(policy, OfflinePolicy::Fade(std::time::Duration::from_millis(1500)));
+/// ```
+///
+impl std::str::FromStr for OfflinePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "hold" => Ok(Self::Hold),
+            None if s == "reset" => Ok(Self::Reset),
+            Some(("fade", seconds)) => seconds
+                .parse::<f64>()
+                .ok()
+                .and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds).ok())
+                .map(Self::Fade)
+                .ok_or_else(|| format!("invalid fade duration: {seconds}")),
+            _ => Err(format!(
+                "invalid offline policy '{s}', expected hold, reset or fade:<seconds>"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for OfflinePolicy {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+use crate::data::{PropertySchema, Range};
+use crate::name;
+
+fn make_controller() -> Controller {
+    Controller {
+        name: name!("phone"),
+        properties: HashMap::from([
+            (
+                name!("position"),
+                Property::with_default_value(name!("position"), (0.0, 0.0, 0.0).into()),
+            ),
+            (
+                name!("zoom"),
+                Property::with_schema(
+                    name!("zoom"),
+                    1.0.into(),
+                    PropertySchema {
+                        range: Some(Range::new(0.0, 2.0)),
+                        ..Default::default()
+                    },
+                ),
+            ),
+        ]),
+        device_id: None,
+        status: ControllerStatus::Connected,
+        sampled_at: None,
+    }
+}
+
+#[test]
+fn test_validate_sample_rejects_unknown_property() {
+    let controller = make_controller();
+    let sample = Sample::new(HashMap::from([(name!("rotation"), 1.0.into())]));
+
+    assert!(matches!(
+        controller.validate_sample(&sample),
+        Err(Error::UnknownProperty(_))
+    ));
+}
+
+#[test]
+fn test_validate_sample() {
+    let controller = make_controller();
+    let sample = Sample::new(HashMap::from([
+        (name!("position"), (1.0, 2.0, 3.0).into()),
+        (name!("zoom"), 1.5.into()),
+    ]));
+    assert!(controller.validate_sample(&sample).is_ok());
+
+    let sample = Sample::new(HashMap::from([(name!("zoom"), 3.0.into())]));
+    assert!(matches!(
+        controller.validate_sample(&sample),
+        Err(Error::ValueOutOfRange(_))
+    ));
+}
+
+#[test]
+fn test_validate_binding() {
+    let controller = make_controller();
+
+    assert!(controller
+        .validate_binding(&name!("position"), &Value::vec3())
+        .is_ok());
+    assert!(matches!(
+        controller.validate_binding(&name!("position"), &1.0.into()),
+        Err(Error::InvalidBinding(_))
+    ));
+    assert!(matches!(
+        controller.validate_binding(&name!("missing"), &1.0.into()),
+        Err(Error::InvalidBinding(_))
+    ));
+}
+
+#[test]
+fn test_controller_from_definition_with_schema() {
+    let def = proto::ControllerDef {
+        name: "phone".into(),
+        properties: HashMap::from([(
+            "zoom".into(),
+            proto::PropertyValue {
+                value: Some(proto::property_value::Value::FloatValue(1.0)),
+            },
+        )]),
+        schemas: HashMap::from([(
+            "zoom".into(),
+            proto::PropertySchema {
+                range: Some(proto::Range { min: 0.0, max: 2.0 }),
+                unit: "x".into(),
+                semantic: "".into(),
+            },
+        )]),
+        device_id: "".into(),
+    };
+    let controller: Controller = def.into();
+    let property = controller
+        .properties
+        .get(&name!("zoom"))
+        .expect("zoom property should exist");
+
+    assert_eq!(property.schema.range, Some(Range::new(0.0, 2.0)));
+    assert_eq!(property.schema.unit.as_deref(), Some("x"));
+    assert_eq!(property.schema.semantic, None);
+}
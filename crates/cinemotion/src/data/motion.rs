@@ -1,6 +1,8 @@
 use cinemotion_proto::proto;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Mode {
     Idle,
     Live,
@@ -12,7 +14,7 @@ impl Mode {
     pub fn is_idle(&self) -> bool {
         *self == Self::Idle
     }
-    /// Returns true if mode is live (or recording)
+    /// Returns true if mode is live
     pub fn is_live(&self) -> bool {
         *self == Self::Live
     }
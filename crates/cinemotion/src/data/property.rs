@@ -1,13 +1,18 @@
 use super::value::*;
-use crate::{Name, Result};
+use crate::{Error, Name, Result};
 use cinemotion_proto as proto;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+#[path = "property_test.rs"]
+mod property_test;
 
 /// Represents a property on a controller.
 ///
 /// A property is the primary way to communcation motion from a controller.
 /// When a controller is updated, the property value will be updated and the default value
 /// will replace the current value when the motion state is reset.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Property {
     /// The name of the property.
     pub name: Name,
@@ -15,6 +20,8 @@ pub struct Property {
     pub value: Value,
     /// The default value of the property to use when the motion state is reset.
     pub default_value: Value,
+    /// The constraints and metadata declared for the property.
+    pub schema: PropertySchema,
 }
 
 impl Property {
@@ -23,10 +30,64 @@ impl Property {
             name,
             value: default_value.clone(),
             default_value,
+            schema: PropertySchema::default(),
+        }
+    }
+
+    pub fn with_schema(name: Name, default_value: Value, schema: PropertySchema) -> Self {
+        Self {
+            schema,
+            ..Self::with_default_value(name, default_value)
+        }
+    }
+
+    /// Return the type of value the property holds.
+    ///
+    /// The type is implied by the default value declared by the controller.
+    pub fn value_type(&self) -> ValueType {
+        self.default_value.value_type()
+    }
+
+    /// Validate that the given value can be assigned to the property.
+    ///
+    /// This will error with `InvalidValue` if the value has a different type and
+    /// `ValueOutOfRange` if any of its components fall outside of the declared range.
+    pub fn validate(&self, value: &Value) -> Result<()> {
+        if value.value_type() != self.value_type() {
+            return Err(Error::InvalidValue(format!(
+                "property {} expects a {} value but got a {} value",
+                self.name,
+                self.value_type(),
+                value.value_type()
+            )));
+        }
+        let Some(range) = &self.schema.range else {
+            return Ok(());
+        };
+        match value.scalars().into_iter().find(|v| !range.contains(*v)) {
+            Some(v) => Err(Error::ValueOutOfRange(format!(
+                "value {v} for property {} is outside of range [{}, {}]",
+                self.name, range.min, range.max
+            ))),
+            None => Ok(()),
         }
     }
 
+    /// Validate the schema of the property against its own default value.
+    pub fn validate_schema(&self) -> Result<()> {
+        if let Some(range) = &self.schema.range {
+            if range.min > range.max {
+                return Err(Error::InvalidValue(format!(
+                    "property {} declares an empty range [{}, {}]",
+                    self.name, range.min, range.max
+                )));
+            }
+        }
+        self.validate(&self.default_value)
+    }
+
     pub fn update(&mut self, value: &Value) -> Result<()> {
+        self.validate(value)?;
         self.value.update(value)
     }
 
@@ -41,6 +102,7 @@ impl From<Property> for proto::Property {
             name: value.name.to_string(),
             value: Some(value.value.into()),
             default_value: Some(value.default_value.into()),
+            schema: Some(value.schema.into()),
         }
     }
 }
@@ -51,12 +113,80 @@ impl From<proto::Property> for Property {
             name: value.name.into(),
             value: value.value.unwrap().into(),
             default_value: value.default_value.unwrap().into(),
+            schema: value.schema.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+/// The optional constraints and metadata a controller declares for a property.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertySchema {
+    /// The inclusive range each scalar component of the value must be within.
+    pub range: Option<Range>,
+    /// The unit the value is expressed in (e.g. "cm", "deg").
+    pub unit: Option<String>,
+    /// A hint about what the value represents (e.g. "position", "focal_length").
+    pub semantic: Option<String>,
+}
+
+impl From<proto::PropertySchema> for PropertySchema {
+    fn from(value: proto::PropertySchema) -> Self {
+        Self {
+            range: value.range.map(Into::into),
+            unit: Some(value.unit).filter(|s| !s.is_empty()),
+            semantic: Some(value.semantic).filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+impl From<PropertySchema> for proto::PropertySchema {
+    fn from(value: PropertySchema) -> Self {
+        Self {
+            range: value.range.map(Into::into),
+            unit: value.unit.unwrap_or_default(),
+            semantic: value.semantic.unwrap_or_default(),
+        }
+    }
+}
+
+/// An inclusive range of scalar values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Range {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Return whether the given value is within the range.
+    pub fn contains(&self, value: f64) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+impl From<proto::Range> for Range {
+    fn from(value: proto::Range) -> Self {
+        Self {
+            min: value.min,
+            max: value.max,
+        }
+    }
+}
+
+impl From<Range> for proto::Range {
+    fn from(value: Range) -> Self {
+        Self {
+            min: value.min,
+            max: value.max,
         }
     }
 }
 
 /// A helper struct for representing a property binding address.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PropertyReference {
     /// The namespace of the controller that has the property.
     pub namespace: Name,
@@ -70,7 +200,8 @@ pub struct PropertyReference {
 /// The property link can either be unbound, meaning the property not attached
 /// to a controller, or bound, meaning the property is attached to a controller property.
 ///
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum PropertyLink {
     /// An unbound property does not reference a controller property for updates.
     Unbound {
@@ -0,0 +1,75 @@
+use super::*;
+use crate::name;
+
+#[test]
+fn test_property_validate_rejects_mismatched_type() {
+    let property = Property::with_default_value(name!("focus"), 1.0.into());
+
+    assert!(matches!(
+        property.validate(&(1.0, 2.0, 3.0).into()),
+        Err(Error::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn test_property_validate_range() {
+    let property = Property::with_schema(
+        name!("position"),
+        (0.0, 0.0, 0.0).into(),
+        PropertySchema {
+            range: Some(Range::new(-10.0, 10.0)),
+            ..Default::default()
+        },
+    );
+
+    assert!(property.validate(&(-10.0, 0.0, 10.0).into()).is_ok());
+    assert!(matches!(
+        property.validate(&(0.0, 10.5, 0.0).into()),
+        Err(Error::ValueOutOfRange(_))
+    ));
+}
+
+#[test]
+fn test_property_update_does_not_apply_invalid_value() {
+    let mut property = Property::with_schema(
+        name!("zoom"),
+        1.0.into(),
+        PropertySchema {
+            range: Some(Range::new(0.0, 2.0)),
+            unit: Some("x".into()),
+            semantic: None,
+        },
+    );
+
+    assert!(property.update(&5.0.into()).is_err());
+    assert_eq!(property.value, Value::Float(1.0));
+}
+
+#[test]
+fn test_property_validate_schema() {
+    let property = Property::with_schema(
+        name!("zoom"),
+        5.0.into(),
+        PropertySchema {
+            range: Some(Range::new(0.0, 2.0)),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        property.validate_schema(),
+        Err(Error::ValueOutOfRange(_))
+    ));
+
+    let property = Property::with_schema(
+        name!("zoom"),
+        1.0.into(),
+        PropertySchema {
+            range: Some(Range::new(2.0, 0.0)),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        property.validate_schema(),
+        Err(Error::InvalidValue(_))
+    ));
+}
@@ -7,22 +7,38 @@ use crate::Name;
 #[derive(Debug, PartialEq, Clone)]
 pub struct Sample {
     properties: HashMap<Name, Value>,
+    timestamp: Option<i64>,
 }
 
 impl Sample {
     pub fn new(properties: HashMap<Name, Value>) -> Self {
-        Self { properties }
+        Self {
+            properties,
+            timestamp: None,
+        }
     }
 
     pub fn empty() -> Self {
         Self {
             properties: HashMap::new(),
+            timestamp: None,
         }
     }
 
+    /// Set the device time the sample was taken in microseconds.
+    pub fn with_timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
     pub fn properties(&self) -> &HashMap<Name, Value> {
         &self.properties
     }
+
+    /// The device time the sample was taken in microseconds, if known.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
 }
 
 impl From<proto::Sample> for Sample {
@@ -33,6 +49,7 @@ impl From<proto::Sample> for Sample {
                 .into_iter()
                 .map(|(name, value)| (name.into(), value.into()))
                 .collect(),
+            timestamp: Some(value.timestamp).filter(|timestamp| *timestamp != 0),
         }
     }
 }
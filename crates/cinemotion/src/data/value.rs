@@ -1,7 +1,30 @@
 use crate::{Error, Result};
 use cinemotion_proto as proto;
+use serde::{Deserialize, Serialize};
+
+/// The type of a value without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Float,
+    Vec3,
+    Vec4,
+    Matrix44,
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Float => "float",
+            Self::Vec3 => "vec3",
+            Self::Vec4 => "vec4",
+            Self::Matrix44 => "matrix44",
+        };
+        write!(f, "{name}")
+    }
+}
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Value {
     Float(f64),
     Vec3(Vec3),
@@ -14,6 +37,56 @@ impl Value {
         Self::Vec3(Vec3::default())
     }
 
+    /// Return the type of the value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Float(_) => ValueType::Float,
+            Self::Vec3(_) => ValueType::Vec3,
+            Self::Vec4(_) => ValueType::Vec4,
+            Self::Matrix44(_) => ValueType::Matrix44,
+        }
+    }
+
+    /// Return all the scalar components of the value.
+    pub fn scalars(&self) -> Vec<f64> {
+        match self {
+            Self::Float(value) => vec![*value],
+            Self::Vec3(value) => vec![value.x, value.y, value.z],
+            Self::Vec4(value) => vec![value.x, value.y, value.z, value.w],
+            Self::Matrix44(value) => [&value.row0, &value.row1, &value.row2, &value.row3]
+                .into_iter()
+                .flat_map(|row| [row.x, row.y, row.z, row.w])
+                .collect(),
+        }
+    }
+
+    /// Linearly interpolate between this value and the other value.
+    ///
+    /// This will error with `InvalidValue` if the values have different types.
+    pub fn lerp(&self, other: &Self, t: f64) -> Result<Self> {
+        if self.value_type() != other.value_type() {
+            return Err(Error::InvalidValue("value has different type".into()));
+        }
+        let scalars: Vec<f64> = self
+            .scalars()
+            .into_iter()
+            .zip(other.scalars())
+            .map(|(a, b)| a + (b - a) * t)
+            .collect();
+        let value = match self.value_type() {
+            ValueType::Float => Self::Float(scalars[0]),
+            ValueType::Vec3 => Self::Vec3((scalars[0], scalars[1], scalars[2]).into()),
+            ValueType::Vec4 => Self::Vec4((scalars[0], scalars[1], scalars[2], scalars[3]).into()),
+            ValueType::Matrix44 => Self::Matrix44(Matrix44 {
+                row0: [scalars[0], scalars[1], scalars[2], scalars[3]].into(),
+                row1: [scalars[4], scalars[5], scalars[6], scalars[7]].into(),
+                row2: [scalars[8], scalars[9], scalars[10], scalars[11]].into(),
+                row3: [scalars[12], scalars[13], scalars[14], scalars[15]].into(),
+            }),
+        };
+        Ok(value)
+    }
+
     pub fn update(&mut self, other: &Self) -> Result<()> {
         match (self, other) {
             (Self::Float(ref mut this), Self::Float(them)) => {
@@ -132,7 +205,7 @@ impl From<Value> for proto::PropertyValue {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -183,7 +256,7 @@ impl std::cmp::PartialEq<(f64, f64, f64)> for &Vec3 {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vec4 {
     pub x: f64,
     pub y: f64,
@@ -253,7 +326,7 @@ impl std::cmp::PartialEq<(f64, f64, f64)> for &Vec4 {
 // The matrix is represented a column major where each sub-tuple
 // repsents a column.
 //
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Matrix44 {
     pub row0: Vec4,
     pub row1: Vec4,
@@ -5,6 +5,16 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WebRTCSessionDescriptor {
     pub payload: String,
+    /// The join token or pairing PIN presented by the peer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Answer before the ICE candidates are gathered and exchange them over
+    /// `PATCH /sessions/{id}` instead.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trickle: bool,
+    /// The id of the signaling session, set on answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 impl WebRTCSessionDescriptor {
@@ -13,12 +23,32 @@ impl WebRTCSessionDescriptor {
     pub fn new(desc_raw: &str) -> Self {
         Self {
             payload: BASE64_STANDARD_NO_PAD.encode(desc_raw),
+            token: None,
+            trickle: false,
+            session_id: None,
         }
     }
 
     /// Create a new decriptor with the given encoded payload.
     pub fn new_encode(payload: String) -> Self {
-        Self { payload }
+        Self {
+            payload,
+            token: None,
+            trickle: false,
+            session_id: None,
+        }
+    }
+
+    /// Trickle the ICE candidates instead of sending them with the descriptors.
+    pub fn with_trickle(mut self) -> Self {
+        self.trickle = true;
+        self
+    }
+
+    /// Attach a join token or pairing PIN to the descriptor.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
     }
 
     /// Return the decoded session desriptor payload.
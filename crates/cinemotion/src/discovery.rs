@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+
+use crate::services::mdns::{SERVICE_TYPE_TCP, SERVICE_TYPE_UDP};
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "discovery_test.rs"]
+mod discovery_test;
+
+/// A way of connecting to a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Transport {
+    /// WebRTC, signaled over the http service.
+    WebRtc,
+    /// QUIC.
+    Quic,
+}
+
+impl Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WebRtc => write!(f, "webrtc"),
+            Self::Quic => write!(f, "quic"),
+        }
+    }
+}
+
+/// A transport a server accepts connections on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub transport: Transport,
+    pub port: u16,
+    /// The http path of the signaling root, only set for WebRTC.
+    pub path: Option<String>,
+}
+
+/// A cinemotion server found on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Server {
+    /// The name that tells servers on the network apart.
+    pub instance_name: String,
+    pub hostname: String,
+    pub addresses: Vec<IpAddr>,
+    /// The wire protocol version of the server.
+    pub protocol_version: Option<u32>,
+    /// The version of the server.
+    pub version: Option<String>,
+    pub endpoints: Vec<Endpoint>,
+    /// The certificate fingerprint to pin when connecting.
+    pub fingerprint: Option<String>,
+}
+
+impl Server {
+    /// Build a server from its advertised TXT records.
+    pub fn from_txt(
+        instance_name: String,
+        hostname: String,
+        addresses: Vec<IpAddr>,
+        txt: &HashMap<String, String>,
+    ) -> Self {
+        let port = |key: &str| txt.get(key).and_then(|port| port.parse::<u16>().ok());
+        let mut endpoints = vec![];
+        if let Some(port) = port("webrtc") {
+            endpoints.push(Endpoint {
+                transport: Transport::WebRtc,
+                port,
+                path: Some(txt.get("path").cloned().unwrap_or_else(|| "/".into())),
+            });
+        }
+        if let Some(port) = port("quic") {
+            endpoints.push(Endpoint {
+                transport: Transport::Quic,
+                port,
+                path: None,
+            });
+        }
+        Self {
+            instance_name,
+            hostname,
+            addresses,
+            protocol_version: txt.get("proto").and_then(|proto| proto.parse().ok()),
+            version: txt.get("version").cloned(),
+            endpoints,
+            fingerprint: txt.get("fingerprint").cloned(),
+        }
+    }
+
+    /// The endpoint of a transport, if the server offers it.
+    pub fn endpoint(&self, transport: Transport) -> Option<&Endpoint> {
+        self.endpoints
+            .iter()
+            .find(|endpoint| endpoint.transport == transport)
+    }
+
+    /// Whether the server speaks the protocol version of this crate.
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == Some(crate::PROTOCOL_VERSION)
+    }
+
+    fn merge(&mut self, other: Server) {
+        for address in other.addresses {
+            if !self.addresses.contains(&address) {
+                self.addresses.push(address);
+            }
+        }
+        for endpoint in other.endpoints {
+            if self.endpoint(endpoint.transport).is_none() {
+                self.endpoints.push(endpoint);
+            }
+        }
+        self.endpoints.sort_by_key(|endpoint| endpoint.transport);
+    }
+}
+
+/// Browse the local network for servers for the given time.
+///
+/// Servers advertise a service type per transport, the records of one
+/// instance are merged into a single server.
+pub async fn discover(timeout: Duration) -> Result<Vec<Server>> {
+    let daemon = ServiceDaemon::new().map_err(discovery_error)?;
+    let tcp = daemon
+        .browse(&service_domain(SERVICE_TYPE_TCP))
+        .map_err(discovery_error)?;
+    let udp = daemon
+        .browse(&service_domain(SERVICE_TYPE_UDP))
+        .map_err(discovery_error)?;
+
+    let mut servers: BTreeMap<String, Server> = BTreeMap::new();
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    loop {
+        let event = tokio::select! {
+            _ = &mut deadline => break,
+            event = tcp.recv_async() => event,
+            event = udp.recv_async() => event,
+        };
+        let Ok(event) = event else {
+            break;
+        };
+        if let ServiceEvent::ServiceResolved(info) = event {
+            let txt = info
+                .get_properties()
+                .iter()
+                .map(|property| (property.key().to_string(), property.val_str().to_string()))
+                .collect();
+            let server = Server::from_txt(
+                instance_name(info.get_fullname(), info.get_type()),
+                info.get_hostname().to_string(),
+                info.get_addresses().iter().copied().collect(),
+                &txt,
+            );
+            match servers.get_mut(&server.instance_name) {
+                Some(existing) => existing.merge(server),
+                None => {
+                    servers.insert(server.instance_name.clone(), server);
+                }
+            }
+        }
+    }
+
+    if let Err(err) = daemon.shutdown() {
+        tracing::debug!(%err, "failed to shut down the discovery daemon");
+    }
+    Ok(servers.into_values().collect())
+}
+
+/// The fully qualified DNS-SD domain of a service type.
+fn service_domain(service_type: &str) -> String {
+    format!("{service_type}.local.")
+}
+
+/// The instance part of a full service name like `stage-a._cinemotion._tcp.local.`.
+fn instance_name(fullname: &str, service_domain: &str) -> String {
+    fullname
+        .strip_suffix(service_domain)
+        .map(|name| name.trim_end_matches('.'))
+        .unwrap_or(fullname)
+        .to_string()
+}
+
+fn discovery_error(err: mdns_sd::Error) -> Error {
+    Error::DiscoveryFailed(err.to_string())
+}
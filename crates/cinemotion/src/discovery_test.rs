@@ -0,0 +1,91 @@
+use super::*;
+use crate::services::mdns::Advertisement;
+
+fn txt(records: Vec<String>) -> HashMap<String, String> {
+    records
+        .into_iter()
+        .filter_map(|record| {
+            let (key, value) = record.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[test]
+fn test_from_txt_reads_the_advertisement() {
+    let advertisement = Advertisement {
+        instance_name: "stage-a".into(),
+        http_port: Some(7272),
+        quic_port: Some(4567),
+        fingerprint: Some("AB:CD".into()),
+    };
+    let server = Server::from_txt(
+        "stage-a".into(),
+        "host.local.".into(),
+        vec!["192.168.1.2".parse().unwrap()],
+        &txt(advertisement.txt_records()),
+    );
+
+    assert!(server.is_compatible());
+    assert_eq!(server.version.as_deref(), Some(crate::VERSION));
+    assert_eq!(server.fingerprint.as_deref(), Some("AB:CD"));
+    assert_eq!(
+        server.endpoint(Transport::WebRtc),
+        Some(&Endpoint {
+            transport: Transport::WebRtc,
+            port: 7272,
+            path: Some("/".into()),
+        })
+    );
+    assert_eq!(server.endpoint(Transport::Quic).map(|e| e.port), Some(4567));
+}
+
+#[test]
+fn test_from_txt_tolerates_missing_records() {
+    let server = Server::from_txt(
+        "old".into(),
+        "host.local.".into(),
+        vec![],
+        &txt(vec!["quic=not-a-port".into()]),
+    );
+    assert!(!server.is_compatible());
+    assert!(server.endpoints.is_empty());
+    assert_eq!(server.version, None);
+}
+
+#[test]
+fn test_merge() {
+    let mut server = Server::from_txt(
+        "stage-a".into(),
+        "host.local.".into(),
+        vec!["192.168.1.2".parse().unwrap()],
+        &txt(vec!["quic=4567".into()]),
+    );
+    server.merge(Server::from_txt(
+        "stage-a".into(),
+        "host.local.".into(),
+        vec!["192.168.1.2".parse().unwrap(), "10.0.0.2".parse().unwrap()],
+        &txt(vec!["webrtc=7272".into(), "quic=4567".into()]),
+    ));
+    assert_eq!(server.addresses.len(), 2);
+    assert_eq!(
+        server
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.transport)
+            .collect::<Vec<_>>(),
+        vec![Transport::WebRtc, Transport::Quic]
+    );
+}
+
+#[test]
+fn test_instance_name() {
+    assert_eq!(
+        instance_name("stage-a._cinemotion._tcp.local.", "_cinemotion._tcp.local."),
+        "stage-a"
+    );
+    assert_eq!(
+        instance_name("unexpected", "_cinemotion._tcp.local."),
+        "unexpected"
+    );
+}
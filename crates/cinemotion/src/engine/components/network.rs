@@ -9,6 +9,8 @@ pub trait NetworkComponent: Send + Sync {
     fn context_mut(&mut self, conn_id: usize) -> &mut connection::Context;
     /// Get the context for a connection id.
     fn context(&self, conn_id: usize) -> Option<&connection::Context>;
+    /// Get the contexts of all the managed connections.
+    fn contexts(&self) -> Vec<&connection::Context>;
     /// Create and add a connection to manage
     async fn add_connection(&mut self, options: AddConnection) -> Result<()>;
     /// Close the connection and stop communicating with the peer.
@@ -1,10 +1,15 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use tokio::sync::Mutex;
+use tokio::time::Instant;
 
 use super::components::network;
-use super::Observer;
-use crate::{data, events, messages, Event, Result, State};
+use super::{History, Observer, DEFAULT_HISTORY_DEPTH};
+use crate::connection::{HeartbeatOptions, Role, LOCAL_CONN_ID};
+use crate::presets::SceneStore;
+use crate::takes::Recorder;
+use crate::{clock, data, events, messages, Error, Event, Name, Result, SceneObject, State};
 
 #[cfg(test)]
 #[path = "engine_test.rs"]
@@ -14,6 +19,10 @@ pub struct Builder {
     initial_state: Option<State>,
     engine_observer: Option<Arc<Mutex<dyn Observer>>>,
     network_component: Option<Box<dyn network::NetworkComponent>>,
+    offline_policy: data::OfflinePolicy,
+    heartbeat: Option<HeartbeatOptions>,
+    history_depth: usize,
+    scene_store: SceneStore,
 }
 
 impl Builder {
@@ -22,6 +31,10 @@ impl Builder {
             initial_state: None,
             engine_observer: None,
             network_component: None,
+            offline_policy: data::OfflinePolicy::default(),
+            heartbeat: None,
+            history_depth: DEFAULT_HISTORY_DEPTH,
+            scene_store: SceneStore::default(),
         }
     }
     pub fn with_inital_state(mut self, state: State) -> Self {
@@ -38,6 +51,30 @@ impl Builder {
         self
     }
 
+    /// Set the policy applied to the properties of controllers while they are disconnected.
+    pub fn with_offline_policy(mut self, policy: data::OfflinePolicy) -> Self {
+        self.offline_policy = policy;
+        self
+    }
+
+    /// Enable heartbeats and close connections that fail to open or go silent.
+    pub fn with_heartbeat(mut self, options: HeartbeatOptions) -> Self {
+        self.heartbeat = Some(options);
+        self
+    }
+
+    /// Set how many scene edits can be undone.
+    pub fn with_history_depth(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self
+    }
+
+    /// Set where scenes are saved, they are kept in memory by default.
+    pub fn with_scene_store(mut self, store: SceneStore) -> Self {
+        self.scene_store = store;
+        self
+    }
+
     pub fn build(self) -> Result<Engine> {
         let state = self.initial_state.unwrap_or_default();
         let network = self
@@ -50,6 +87,14 @@ impl Builder {
             current_state: state,
             observer,
             network,
+            offline_policy: self.offline_policy,
+            fading: HashMap::new(),
+            owners: HashMap::new(),
+            heartbeat: self.heartbeat,
+            last_ping: None,
+            history: History::new(self.history_depth),
+            scenes: self.scene_store,
+            recorder: Recorder::default(),
         })
     }
 }
@@ -65,6 +110,21 @@ pub struct Engine {
     current_state: State,
     observer: Option<Arc<Mutex<dyn Observer>>>,
     network: Box<dyn network::NetworkComponent>,
+    offline_policy: data::OfflinePolicy,
+    fading: HashMap<Name, FadeOut>,
+    /// The connection each controller was last initialized on.
+    owners: HashMap<Name, usize>,
+    heartbeat: Option<HeartbeatOptions>,
+    last_ping: Option<Instant>,
+    history: History,
+    scenes: SceneStore,
+    recorder: Recorder,
+}
+
+/// Tracks a disconnected controller whose properties are fading to their defaults.
+struct FadeOut {
+    since: Instant,
+    from: HashMap<Name, data::Value>,
 }
 
 impl Engine {
@@ -77,7 +137,14 @@ impl Engine {
             observer.lock().await.on_message(&message);
         }
         let source_id = message.source_id;
+        if source_id != LOCAL_CONN_ID && self.network.context(source_id).is_some() {
+            self.network.context_mut(source_id).last_seen = Some(Instant::now());
+        }
         let command = message.command;
+        let command_name = match &command {
+            messages::Payload::Client(client_command) => Some(client_command.name()),
+            _ => None,
+        };
         let result = match command {
             messages::Payload::Client(client_command) => {
                 self.handle_client_command(source_id, client_command).await
@@ -91,22 +158,33 @@ impl Engine {
                 Ok(())
             }
         };
-        match result {
-            Ok(_) => Ok(()),
-            Err(err) => {
+        let body: events::EventBody = match (result, message.request_id) {
+            (Ok(_), None) => return Ok(()),
+            (Ok(_), Some(request_id)) => events::AckEvent(request_id).into(),
+            (Err(err), request_id) => {
                 tracing::error!("error applying message: {}", err);
-                self.send(Event {
-                    target: Some(source_id),
-                    body: events::ErrorEvent(err).into(),
-                })
-                .await?;
-                Ok(())
+                let error = events::ErrorEvent {
+                    error: err,
+                    command: command_name,
+                };
+                events::EventBody::rejection(error, request_id)
             }
-        }
+        };
+        self.send(Event {
+            target: Some(source_id),
+            body,
+        })
+        .await
     }
 
     pub async fn tick(&mut self) -> Result<()> {
+        self.check_liveness().await?;
+        self.fade_offline_controllers();
         self.render().await?;
+        if self.active_state.mode.is_recording() {
+            self.recorder
+                .capture(&self.active_state.scene, clock::now_micros());
+        }
 
         if let Some(observer) = &self.observer {
             observer.lock().await.on_state_change(&self.active_state);
@@ -124,10 +202,113 @@ impl Engine {
         &mut self,
         source_id: usize,
         client_command: crate::messages::ClientCommand,
+    ) -> Result<()> {
+        // Connections without a context, like the local one, are trusted.
+        if let Some(context) = self.network.context(source_id) {
+            if !context.role.permits(&client_command) {
+                return Err(Error::PermissionDenied(format!(
+                    "a connection with the {} role cannot issue {}",
+                    context.role,
+                    client_command.name()
+                )));
+            }
+        }
+        // Scene edits are recorded once applied, a batch as a single edit.
+        let label = client_command.name();
+        let before = client_command
+            .is_scene_edit()
+            .then(|| self.active_state.scene.clone());
+        // A run of adjustments to one property, like dragging a slider while
+        // live, is undone as one edit so it does not flood the history.
+        let merge_key = match &client_command {
+            messages::ClientCommand::AdjustProperty(adjust) => {
+                Some(format!("{}.{}", adjust.object, adjust.property))
+            }
+            _ => None,
+        };
+        match client_command {
+            messages::ClientCommand::Batch(batch) => self.handle_batch(batch, source_id).await,
+            command => self.dispatch_client_command(source_id, command).await,
+        }?;
+        if let Some(before) = before {
+            if before != self.active_state.scene {
+                let changed = match merge_key {
+                    Some(key) => self.history.record_merged(label, key, before),
+                    None => {
+                        self.history.record(label, before);
+                        true
+                    }
+                };
+                if changed {
+                    self.send_history().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the commands of the batch in order, restoring the state from
+    /// before the batch when one of them fails.
+    async fn handle_batch(&mut self, batch: messages::Batch, source_id: usize) -> Result<()> {
+        if let Some(command) = batch.0.iter().find(|command| {
+            matches!(
+                command,
+                messages::ClientCommand::Echo(_)
+                    | messages::ClientCommand::Pong(_)
+                    | messages::ClientCommand::TimeSync(_)
+                    | messages::ClientCommand::Init(_)
+                    | messages::ClientCommand::Undo(_)
+                    | messages::ClientCommand::Redo(_)
+                    | messages::ClientCommand::SaveScene(_)
+                    | messages::ClientCommand::ListScenes(_)
+                    // Mode changes start and finish takes, which a rollback
+                    // of the state would not undo.
+                    | messages::ClientCommand::ChangeMode(_)
+                    | messages::ClientCommand::Batch(_)
+            )
+        }) {
+            return Err(Error::BadCommand(format!(
+                "{} cannot be batched",
+                command.name()
+            )));
+        }
+        let snapshot = self.active_state.clone();
+        for command in batch.0 {
+            if let Err(err) = self.dispatch_client_command(source_id, command).await {
+                self.active_state = snapshot;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    async fn dispatch_client_command(
+        &mut self,
+        source_id: usize,
+        client_command: crate::messages::ClientCommand,
     ) -> Result<()> {
         match client_command {
+            messages::ClientCommand::Batch(_) => {
+                Err(Error::BadCommand("batches cannot be nested".to_string()))
+            }
             messages::ClientCommand::Echo(message) => self.handle_echo(source_id, message).await,
-            messages::ClientCommand::Init(init) => self.handle_init(init, source_id),
+            messages::ClientCommand::Pong(pong) => {
+                self.handle_pong(pong, source_id);
+                Ok(())
+            }
+            messages::ClientCommand::TimeSync(sync) => {
+                self.handle_time_sync(sync, source_id);
+                Ok(())
+            }
+            messages::ClientCommand::Init(init) => {
+                let name = init.peer.name.clone();
+                self.handle_init(init, source_id)?;
+                self.send(Event {
+                    target: None,
+                    body: events::ControllerJoinedEvent(name).into(),
+                })
+                .await
+            }
             messages::ClientCommand::UpdateSceneObject(update) => {
                 self.handle_update_scene_obj(update)
             }
@@ -138,6 +319,24 @@ impl Engine {
                 Ok(())
             }
             messages::ClientCommand::DeleteSceneObject(name) => self.handle_delete_scene_obj(name),
+            messages::ClientCommand::RenameSceneObject(rename) => {
+                self.handle_rename_scene_obj(rename)
+            }
+            messages::ClientCommand::BindProperty(bind) => self.handle_bind_property(bind),
+            messages::ClientCommand::UnbindProperty(unbind) => self.handle_unbind_property(unbind),
+            messages::ClientCommand::SetPropertyValue(set) => self.handle_set_property_value(set),
+            messages::ClientCommand::AddProperty(add) => self.handle_add_property(add),
+            messages::ClientCommand::RemoveProperty(remove) => self.handle_remove_property(remove),
+            messages::ClientCommand::AdjustProperty(adjust) => self.handle_adjust_property(adjust),
+            messages::ClientCommand::SaveScene(save) => self.handle_save_scene(save).await,
+            messages::ClientCommand::LoadScene(load) => self.handle_load_scene(load),
+            messages::ClientCommand::ListScenes(_) => {
+                let scenes = self.scenes.list()?;
+                self.send(Event::new(source_id, events::SceneListEvent(scenes).into()))
+                    .await
+            }
+            messages::ClientCommand::Undo(_) => self.handle_undo().await,
+            messages::ClientCommand::Redo(_) => self.handle_redo().await,
             messages::ClientCommand::ChangeMode(mode_change) => {
                 self.handle_mode_change(mode_change)
             }
@@ -152,39 +351,76 @@ impl Engine {
         }
         let sample = sample.0;
         let Some(context) = self.network.context(source_id) else {
-            tracing::error!("context not found for id: {}", source_id);
-            return Ok(());
+            return Err(Error::UnknownController(format!(
+                "no controller is initialized for connection {source_id}"
+            )));
         };
-        let Some(name) = context.name.as_ref() else {
-            tracing::error!("no name is assigned to the connection {source_id}");
-            return Ok(());
+        let Some(name) = context.name.clone() else {
+            return Err(Error::UnknownController(format!(
+                "no controller is initialized for connection {source_id}"
+            )));
         };
-        let Some(controller) = self.active_state.controllers.get_mut(name) else {
-            tracing::error!("controller not found for name: {}", name);
-            return Ok(());
+        if !self.owns_controller(&name, source_id) {
+            return Err(Error::UnknownController(format!(
+                "{name} has reconnected on another connection"
+            )));
+        }
+        let sampled_at = sample
+            .timestamp()
+            .map(|timestamp| context.clock.to_server_time(timestamp));
+        let Some(controller) = self.active_state.controllers.get_mut(&name) else {
+            return Err(Error::UnknownController(name.to_string()));
         };
-        for (property_name, value) in sample.properties() {
-            let Some(property) = controller.properties.get_mut(property_name) else {
-                tracing::error!(
-                    "property not found for name: {}.{}",
-                    name.to_string(),
-                    property_name
-                );
-                continue;
-            };
 
-            if let Err(err) = property.update(value) {
-                tracing::error!(
-                    "error updating property: {}.{}: {}",
-                    name.to_string(),
-                    property_name,
-                    err
-                );
+        // Validate the whole sample before applying it so a bad sample is
+        // never partially applied.
+        controller.validate_sample(&sample)?;
+        for (property_name, value) in sample.properties() {
+            if let Some(property) = controller.properties.get_mut(property_name) {
+                property.update(value)?;
             }
         }
+        if sampled_at.is_some() {
+            controller.sampled_at = sampled_at;
+        }
         Ok(())
     }
 
+    fn handle_pong(&mut self, pong: messages::Pong, source_id: usize) {
+        if self.network.context(source_id).is_none() {
+            return;
+        }
+        // The timestamp comes from the client, only the first pong echoing the
+        // last ping sent to the connection gives a plausible round trip.
+        let now = clock::now_micros();
+        let context = self.network.context_mut(source_id);
+        let Some(sent) = context.ping_timestamp.filter(|sent| *sent == pong.0) else {
+            tracing::debug!(
+                "ignoring pong with unexpected timestamp {} from connection {}",
+                pong.0,
+                source_id
+            );
+            return;
+        };
+        context.ping_timestamp = None;
+        let rtt = std::time::Duration::from_micros(now.abs_diff(sent));
+        context.latency.record(rtt);
+    }
+
+    fn handle_time_sync(&mut self, sync: messages::TimeSync, source_id: usize) {
+        if self.network.context(source_id).is_none() {
+            return;
+        }
+        let t3 = clock::now_micros();
+        let clock = &mut self.network.context_mut(source_id).clock;
+        if !clock.record(sync.t0, sync.t1, sync.t2, t3) {
+            tracing::debug!(
+                "ignoring time sync with implausible timestamps from connection {}",
+                source_id
+            );
+        }
+    }
+
     fn handle_mode_change(&mut self, mode_change: messages::ChangeMode) -> Result<()> {
         let is_sample_mode = !self.active_state.mode.is_idle();
         if is_sample_mode && mode_change.0.is_idle() {
@@ -192,10 +428,221 @@ impl Engine {
             // because the will be updated when the engine renders.
             reset_controller_properties(&mut self.active_state);
         }
+        match (self.recorder.is_recording(), mode_change.0.is_recording()) {
+            (false, true) => {
+                let name = self.recorder.start(clock::now_micros());
+                tracing::info!("recording {}", name);
+            }
+            (true, false) => {
+                if let Some(take) = self.recorder.finish() {
+                    tracing::info!("recorded {} with {} frames", take.name, take.frames);
+                }
+            }
+            _ => {}
+        }
         self.active_state.mode = mode_change.0;
         Ok(())
     }
 
+    fn handle_rename_scene_obj(&mut self, rename: messages::RenameSceneObject) -> Result<()> {
+        self.ensure_idle_mode()?;
+        if rename.new_name.is_empty() {
+            return Err(Error::InvalidSceneObject(
+                "object names must not be empty".into(),
+            ));
+        }
+        let objects = self.active_state.scene.objects_mut();
+        if !objects.contains_key(&rename.name) {
+            return Err(Error::InvalidSceneObject(format!(
+                "object {} does not exist",
+                rename.name
+            )));
+        }
+        if rename.new_name == rename.name {
+            return Ok(());
+        }
+        if objects.contains_key(&rename.new_name) {
+            return Err(Error::InvalidSceneObject(format!(
+                "object {} already exists",
+                rename.new_name
+            )));
+        }
+        let mut object = objects.remove(&rename.name).expect("the object exists");
+        let properties = std::mem::take(object.properties_mut());
+        objects.insert(
+            rename.new_name.clone(),
+            SceneObject::new(rename.new_name, properties),
+        );
+        Ok(())
+    }
+
+    fn handle_bind_property(&mut self, bind: messages::BindProperty) -> Result<()> {
+        self.ensure_idle_mode()?;
+        self.bind_property(&bind.object, &bind.property, bind.binding)
+    }
+
+    fn handle_unbind_property(&mut self, unbind: messages::UnbindProperty) -> Result<()> {
+        self.ensure_idle_mode()?;
+        self.unbind_property(&unbind.object, &unbind.property)
+    }
+
+    fn handle_set_property_value(&mut self, set: messages::SetPropertyValue) -> Result<()> {
+        self.ensure_idle_mode()?;
+        self.set_property_value(&set.object, &set.property, &set.value)
+    }
+
+    /// Adjust a property in any mode, the structure of the scene stays the same.
+    fn handle_adjust_property(&mut self, adjust: messages::AdjustProperty) -> Result<()> {
+        match adjust.adjustment {
+            messages::Adjustment::SetValue(value) => {
+                self.set_property_value(&adjust.object, &adjust.property, &value)
+            }
+            messages::Adjustment::Bind(binding) => {
+                self.bind_property(&adjust.object, &adjust.property, binding)
+            }
+            messages::Adjustment::Unbind => self.unbind_property(&adjust.object, &adjust.property),
+        }
+    }
+
+    fn bind_property(
+        &mut self,
+        object: &Name,
+        property: &Name,
+        binding: data::PropertyReference,
+    ) -> Result<()> {
+        let value = self
+            .active_state
+            .scene
+            .property_mut(object, property)?
+            .value()
+            .clone();
+        if let Some(controller) = self.active_state.controllers.get(&binding.namespace) {
+            controller.validate_binding(&binding.property, &value)?;
+        }
+        *self.active_state.scene.property_mut(object, property)? =
+            data::PropertyLink::Bound { value, binding };
+        Ok(())
+    }
+
+    fn unbind_property(&mut self, object: &Name, property: &Name) -> Result<()> {
+        let link = self.active_state.scene.property_mut(object, property)?;
+        *link = data::PropertyLink::unbound(link.value().clone());
+        Ok(())
+    }
+
+    fn set_property_value(
+        &mut self,
+        object: &Name,
+        property: &Name,
+        value: &data::Value,
+    ) -> Result<()> {
+        let link = self.active_state.scene.property_mut(object, property)?;
+        let data::PropertyLink::Unbound { value: current } = link else {
+            return Err(Error::InvalidBinding(format!(
+                "{object}.{property} is bound to a controller, unbind it to set its value"
+            )));
+        };
+        current.update(value)
+    }
+
+    fn handle_add_property(&mut self, add: messages::AddProperty) -> Result<()> {
+        self.ensure_idle_mode()?;
+        if let data::PropertyLink::Bound { value, binding } = &add.link {
+            if let Some(controller) = self.active_state.controllers.get(&binding.namespace) {
+                controller.validate_binding(&binding.property, value)?;
+            }
+        }
+        let Some(object) = self.active_state.scene.object_mut(&add.object) else {
+            return Err(Error::InvalidSceneObject(format!(
+                "object {} does not exist",
+                add.object
+            )));
+        };
+        if object.property(&add.property).is_some() {
+            return Err(Error::InvalidSceneObject(format!(
+                "{} already has a property {}",
+                add.object, add.property
+            )));
+        }
+        object.properties_mut().insert(add.property, add.link);
+        Ok(())
+    }
+
+    fn handle_remove_property(&mut self, remove: messages::RemoveProperty) -> Result<()> {
+        self.ensure_idle_mode()?;
+        let Some(object) = self.active_state.scene.object_mut(&remove.object) else {
+            return Err(Error::InvalidSceneObject(format!(
+                "object {} does not exist",
+                remove.object
+            )));
+        };
+        match object.properties_mut().remove(&remove.property) {
+            Some(_) => Ok(()),
+            None => Err(Error::UnknownProperty(format!(
+                "{} has no property {}",
+                remove.object, remove.property
+            ))),
+        }
+    }
+
+    async fn handle_save_scene(&mut self, save: messages::SaveScene) -> Result<()> {
+        let mut scene = self.active_state.scene.clone();
+        scene.name = save.0;
+        self.scenes.save(&scene)?;
+        self.active_state.scene.name = scene.name;
+        let scenes = self.scenes.list()?;
+        self.send(Event {
+            target: None,
+            body: events::SceneListEvent(scenes).into(),
+        })
+        .await
+    }
+
+    fn handle_load_scene(&mut self, load: messages::LoadScene) -> Result<()> {
+        self.ensure_idle_mode()?;
+        let scene = self.scenes.load(&load.0)?;
+        for object in scene.objects().values() {
+            self.validate_bindings(object)?;
+        }
+        self.active_state.scene = scene;
+        Ok(())
+    }
+
+    async fn handle_undo(&mut self) -> Result<()> {
+        self.ensure_idle_mode()?;
+        let Some(scene) = self.history.undo(self.active_state.scene.clone()) else {
+            return Err(Error::BadCommand(
+                "there is no scene edit to undo".to_string(),
+            ));
+        };
+        self.active_state.scene = scene;
+        self.send_history().await
+    }
+
+    async fn handle_redo(&mut self) -> Result<()> {
+        self.ensure_idle_mode()?;
+        let Some(scene) = self.history.redo(self.active_state.scene.clone()) else {
+            return Err(Error::BadCommand(
+                "there is no scene edit to redo".to_string(),
+            ));
+        };
+        self.active_state.scene = scene;
+        self.send_history().await
+    }
+
+    /// Let everyone know which scene edits can be undone and redone.
+    async fn send_history(&mut self) -> Result<()> {
+        self.send(Event {
+            target: None,
+            body: events::HistoryChangedEvent {
+                undo: self.history.undo_labels(),
+                redo: self.history.redo_labels(),
+            }
+            .into(),
+        })
+        .await
+    }
+
     fn handle_delete_scene_obj(&mut self, name: messages::DeleteSceneObject) -> Result<()> {
         self.ensure_idle_mode()?;
         self.active_state.scene.objects_mut().remove(&name.0);
@@ -205,6 +652,7 @@ impl Engine {
     fn handle_add_scene_obj(&mut self, object: messages::AddSceneObject) -> Result<()> {
         self.ensure_idle_mode()?;
         let object = object.0;
+        self.validate_bindings(&object)?;
         let name = object.name().clone();
         let objects = self.active_state.scene.objects_mut();
         match objects.contains_key(&name) {
@@ -221,6 +669,7 @@ impl Engine {
     fn handle_update_scene_obj(&mut self, update: messages::UpdateSceneObject) -> Result<()> {
         self.ensure_idle_mode()?;
         let scene_object = update.0;
+        self.validate_bindings(&scene_object)?;
         let name = scene_object.name().clone();
         let objects = self.active_state.scene.objects_mut();
         match objects.contains_key(&name) {
@@ -235,16 +684,143 @@ impl Engine {
     }
 
     fn handle_init(&mut self, init: messages::Init, source_id: usize) -> Result<()> {
-        self.ensure_idle_mode()?;
+        // An init queued behind the close of its connection must not bring
+        // the connection back.
+        if source_id != LOCAL_CONN_ID && self.network.context(source_id).is_none() {
+            return Err(Error::ConnectionFailed(format!(
+                "connection {source_id} is closed"
+            )));
+        }
+        let capabilities = init.handshake.negotiate()?;
         let peer = init.peer;
+        peer.validate()?;
+
+        // A device that reconnects is restored in place, so it may rejoin while live.
+        let previous = self
+            .active_state
+            .controllers
+            .values()
+            .find(|controller| controller.is_same_device(&peer))
+            .map(|controller| controller.name.clone());
+        if previous.is_none() {
+            self.ensure_idle_mode()?;
+        }
+
+        if let Some(existing) = self.active_state.controllers.get(&peer.name) {
+            if existing.status.is_connected()
+                && existing.device_id.is_some()
+                && !existing.is_same_device(&peer)
+            {
+                return Err(Error::DuplicateController(format!(
+                    "{} is already connected from another device",
+                    peer.name
+                )));
+            }
+        }
+
+        if let Some(previous) = previous {
+            tracing::info!("controller {} reconnected as {}", previous, peer.name);
+            self.fading.remove(&previous);
+            self.owners.remove(&previous);
+            if previous != peer.name {
+                self.active_state.controllers.remove(&previous);
+                rebind_controller(&mut self.active_state, &previous, &peer.name);
+            }
+        }
+
         let context = self.network.context_mut(source_id);
         context.name = Some(peer.name.clone());
+        context.protocol_version = Some(init.handshake.protocol_version);
+        context.capabilities = capabilities;
+        self.owners.insert(peer.name.clone(), source_id);
         self.active_state
             .controllers
             .insert(peer.name.clone(), peer);
         Ok(())
     }
 
+    /// Mark the controller of the connection as disconnected and apply the offline policy.
+    ///
+    /// Returns the name of the disconnected controller, if any.
+    fn disconnect_controller(&mut self, conn_id: usize) -> Option<Name> {
+        let name = self
+            .network
+            .context(conn_id)
+            .and_then(|context| context.name.clone())?;
+        // The device may have reconnected before its old connection timed out.
+        if !self.owns_controller(&name, conn_id) {
+            return None;
+        }
+        self.owners.remove(&name);
+        let controller = self.active_state.controllers.get_mut(&name)?;
+        tracing::info!("controller {} disconnected", name);
+        controller.status = data::ControllerStatus::Disconnected;
+        match self.offline_policy {
+            data::OfflinePolicy::Hold => {}
+            data::OfflinePolicy::Reset => {
+                for property in controller.properties.values_mut() {
+                    if let Err(err) = property.reset() {
+                        tracing::error!("error resetting property: {}", err);
+                    }
+                }
+            }
+            data::OfflinePolicy::Fade(_) => {
+                let from = controller
+                    .properties
+                    .iter()
+                    .map(|(name, property)| (name.clone(), property.value.clone()))
+                    .collect();
+                self.fading.insert(
+                    name.clone(),
+                    FadeOut {
+                        since: Instant::now(),
+                        from,
+                    },
+                );
+            }
+        }
+        Some(name)
+    }
+
+    /// Whether the connection drives the controller, it does not once the
+    /// device reconnected on another connection.
+    fn owns_controller(&self, name: &Name, conn_id: usize) -> bool {
+        self.owners.get(name).is_none_or(|owner| *owner == conn_id)
+    }
+
+    /// Advance the fade of the disconnected controllers towards their default values.
+    fn fade_offline_controllers(&mut self) {
+        let data::OfflinePolicy::Fade(duration) = self.offline_policy else {
+            return;
+        };
+        let now = Instant::now();
+        let controllers = &mut self.active_state.controllers;
+        self.fading.retain(|name, fade| {
+            let Some(controller) = controllers.get_mut(name) else {
+                return false;
+            };
+            let t = match duration.is_zero() {
+                true => 1.0,
+                false => ((now - fade.since).as_secs_f64() / duration.as_secs_f64()).min(1.0),
+            };
+            for (property_name, from) in fade.from.iter() {
+                let Some(property) = controller.properties.get_mut(property_name) else {
+                    continue;
+                };
+                match from.lerp(&property.default_value, t) {
+                    Ok(value) => property.value = value,
+                    Err(err) => tracing::error!(
+                        "error fading property: {}.{}: {}",
+                        name,
+                        property_name,
+                        err
+                    ),
+                }
+            }
+            t < 1.0
+        });
+    }
+
     async fn handle_echo(&mut self, source_id: usize, message: messages::Echo) -> Result<()> {
         tracing::info!("echo: {message}");
         let event = Event {
@@ -269,16 +845,150 @@ impl Engine {
                 Ok(())
             }
             messages::SystemCommand::OpenConnection(_) => {
+                if self.network.context(source_id).is_some() {
+                    self.network.context_mut(source_id).opened = true;
+                }
                 self.send(Event {
                     target: Some(source_id),
                     body: events::ConnectionOpenedEvent().into(),
                 })
+                .await?;
+                self.send(Event {
+                    target: Some(source_id),
+                    body: events::TimeSyncEvent(clock::now_micros()).into(),
+                })
+                .await
+            }
+            messages::SystemCommand::CloseConnection(_) => self.close_connection(source_id).await,
+            messages::SystemCommand::Query(query) => {
+                let mut connections: Vec<messages::ConnectionInfo> = self
+                    .network
+                    .contexts()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect();
+                connections.sort_by_key(|connection| connection.id);
+                let scenes = self.scenes.list().unwrap_or_else(|err| {
+                    tracing::error!("error listing the saved scenes: {}", err);
+                    vec![]
+                });
+                let snapshot = messages::Snapshot {
+                    state: self.active_state.clone(),
+                    connections,
+                    scenes,
+                    takes: self.recorder.list(),
+                };
+                if query.ack_pipe.send(snapshot).is_err() {
+                    tracing::debug!("query ack pipe dropped before the snapshot was sent");
+                }
+                Ok(())
+            }
+            messages::SystemCommand::QueryTake(query) => {
+                let take = self.recorder.get(&query.name).cloned();
+                if query.ack_pipe.send(take).is_err() {
+                    tracing::debug!("query ack pipe dropped before the take was sent");
+                }
+                Ok(())
+            }
+            messages::SystemCommand::Submit(submit) => {
+                let result = self.handle_submit(submit.command, submit.role).await;
+                if submit.ack_pipe.send(result).is_err() {
+                    tracing::debug!("submit ack pipe dropped before the result was sent");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply a command submitted by a service on behalf of a caller with the given role.
+    async fn handle_submit(&mut self, command: messages::ClientCommand, role: Role) -> Result<()> {
+        if !role.permits(&command) {
+            return Err(Error::PermissionDenied(format!(
+                "a caller with the {} role cannot issue {}",
+                role,
+                command.name()
+            )));
+        }
+        self.handle_client_command(LOCAL_CONN_ID, command).await
+    }
+
+    /// Close the connection and let the remaining connections know about it.
+    async fn close_connection(&mut self, conn_id: usize) -> Result<()> {
+        if self.network.context(conn_id).is_none() {
+            tracing::debug!("connection {} is already closed", conn_id);
+            return Ok(());
+        }
+        let controller = self.disconnect_controller(conn_id);
+        self.network.close_connection(conn_id).await?;
+        self.send(Event {
+            target: None,
+            body: events::ConnectionClosedEvent(conn_id).into(),
+        })
+        .await?;
+        match controller {
+            Some(name) => {
+                self.send(Event {
+                    target: None,
+                    body: events::ControllerLeftEvent(name).into(),
+                })
                 .await
             }
-            messages::SystemCommand::CloseConnection(_) => {
-                self.network.close_connection(source_id).await
+            None => Ok(()),
+        }
+    }
+
+    /// Send heartbeats and close the connections that never opened or went silent.
+    async fn check_liveness(&mut self) -> Result<()> {
+        let Some(options) = self.heartbeat else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if self
+            .last_ping
+            .is_none_or(|last_ping| now - last_ping >= options.interval)
+        {
+            let timestamp = clock::now_micros();
+            self.last_ping = Some(now);
+            let connections: Vec<usize> = self
+                .network
+                .contexts()
+                .into_iter()
+                .map(|context| context.uid)
+                .collect();
+            for conn_id in connections {
+                self.network.context_mut(conn_id).ping_timestamp = Some(timestamp);
             }
+            self.send(Event {
+                target: None,
+                body: events::PingEvent(timestamp).into(),
+            })
+            .await?;
+            self.send(Event {
+                target: None,
+                body: events::TimeSyncEvent(clock::now_micros()).into(),
+            })
+            .await?;
+        }
+
+        let expired: Vec<usize> = self
+            .network
+            .contexts()
+            .into_iter()
+            .filter(|context| match context.opened {
+                false => context
+                    .connected_at
+                    .is_some_and(|at| now - at > options.open_timeout),
+                true => context
+                    .last_seen
+                    .is_some_and(|at| now - at > options.idle_timeout),
+            })
+            .map(|context| context.uid)
+            .collect();
+        for conn_id in expired {
+            tracing::warn!("closing connection {} after it timed out", conn_id);
+            self.close_connection(conn_id).await?;
         }
+        Ok(())
     }
 
     async fn send(&mut self, event: Event) -> Result<()> {
@@ -336,10 +1046,26 @@ impl Engine {
         Ok(())
     }
 
+    /// Validate the bound properties of the scene object against the controllers.
+    ///
+    /// Bindings to controllers that have not been initialized yet are allowed so
+    /// scenes can be prepared before the controllers join.
+    fn validate_bindings(&self, object: &SceneObject) -> Result<()> {
+        for link in object.properties().values() {
+            let data::PropertyLink::Bound { value, binding } = link else {
+                continue;
+            };
+            if let Some(controller) = self.active_state.controllers.get(&binding.namespace) {
+                controller.validate_binding(&binding.property, value)?;
+            }
+        }
+        Ok(())
+    }
+
     fn ensure_idle_mode(&self) -> Result<()> {
-        if self.active_state.mode.is_live() {
+        if !self.active_state.mode.is_idle() {
             Err(crate::Error::InvalidMode(
-                "cannot perform command while in live mode".into(),
+                "cannot perform command while live or recording".into(),
             ))
         } else {
             Ok(())
@@ -357,3 +1083,16 @@ pub fn reset_controller_properties(state: &mut State) {
         }
     }
 }
+
+// Point every scene object property bound to the old controller at the new controller.
+fn rebind_controller(state: &mut State, old: &Name, new: &Name) {
+    for object in state.scene.objects_mut().values_mut() {
+        for link in object.properties_mut().values_mut() {
+            if let data::PropertyLink::Bound { binding, .. } = link {
+                if &binding.namespace == old {
+                    binding.namespace = new.clone();
+                }
+            }
+        }
+    }
+}
@@ -37,6 +37,10 @@ impl super::network::NetworkComponent for NetworkSpy {
     fn context(&self, _: usize) -> Option<&connection::Context> {
         Some(&self.context)
     }
+    /// Get the contexts of all the managed connections.
+    fn contexts(&self) -> Vec<&connection::Context> {
+        vec![&self.context]
+    }
     /// Create and add a connection to manage
     async fn add_connection(&mut self, _: messages::AddConnection) -> Result<()> {
         Ok(())
@@ -71,7 +75,11 @@ async fn test_init_errors_when_not_idle() {
         peer: data::Controller {
             name: name!("controllerA"),
             properties: HashMap::new(),
+            device_id: None,
+            status: data::ControllerStatus::Connected,
+            sampled_at: None,
         },
+        handshake: Default::default(),
     };
     assert!(matches!(
         engine.handle_init(command, 1),
@@ -149,3 +157,1183 @@ async fn test_delete_scene_obj_errors_when_not_idle() {
         Err(Error::InvalidMode(_))
     ));
 }
+
+fn make_phone(name: &str, device_id: &str) -> data::Controller {
+    let mut controller = data::Controller::new(
+        name.into(),
+        HashMap::from([(
+            name!("position"),
+            data::Property::with_default_value(name!("position"), (0.0, 0.0, 0.0).into()),
+        )]),
+    );
+    controller.device_id = Some(device_id.into());
+    controller
+}
+
+fn make_engine(state: State, policy: data::OfflinePolicy) -> Engine {
+    let values = NetworkSpyValues::new();
+    let network = Box::new(NetworkSpy::new(values));
+    Engine::builder()
+        .with_inital_state(state)
+        .with_network_component(network)
+        .with_offline_policy(policy)
+        .build()
+        .expect("failed to build engine")
+}
+
+#[tokio::test]
+async fn test_init_restores_reconnecting_device_while_live() {
+    let mut phone = make_phone("phone", "device-1");
+    phone.status = data::ControllerStatus::Disconnected;
+    let mut state = State {
+        mode: crate::data::Mode::Live,
+        controllers: HashMap::from([(name!("phone"), phone)]),
+        ..Default::default()
+    };
+    state.scene.objects_mut().insert(
+        name!("camera"),
+        SceneObject::new(
+            name!("camera"),
+            HashMap::from([(
+                name!("position"),
+                data::PropertyLink::bind(name!("phone"), name!("position"), data::Value::vec3()),
+            )]),
+        ),
+    );
+    let mut engine = make_engine(state, data::OfflinePolicy::Hold);
+
+    let command = crate::messages::Init {
+        peer: make_phone("phone-2", "device-1"),
+        handshake: Default::default(),
+    };
+    engine
+        .handle_init(command, 1)
+        .expect("reconnecting device should be accepted while live");
+
+    let controllers = &engine.active_state.controllers;
+    assert!(!controllers.contains_key(&name!("phone")));
+    let controller = controllers
+        .get(&name!("phone-2"))
+        .expect("controller should be restored under its new name");
+    assert!(controller.status.is_connected());
+
+    let object = engine
+        .active_state
+        .scene
+        .object(&name!("camera"))
+        .expect("object should exist");
+    let data::PropertyLink::Bound { binding, .. } = object
+        .property(&name!("position"))
+        .expect("property should exist")
+    else {
+        panic!("property should still be bound");
+    };
+    assert_eq!(binding.namespace, name!("phone-2"));
+}
+
+#[tokio::test]
+async fn test_init_from_closed_connection_is_rejected() {
+    let (sender, _receiver) = messages::message_pipe();
+    let mut engine = Engine::builder()
+        .with_network_component(crate::engine::network::NetworkComponentImpl::boxed(sender))
+        .build()
+        .expect("failed to build engine");
+
+    let command = messages::Init {
+        peer: make_phone("phone", "device-1"),
+        handshake: Default::default(),
+    };
+    assert!(matches!(
+        engine
+            .handle_client_command(7, messages::ClientCommand::Init(command))
+            .await,
+        Err(Error::ConnectionFailed(_))
+    ));
+    assert!(engine.network.context(7).is_none());
+    assert!(engine.active_state.controllers.is_empty());
+    assert!(engine.owners.is_empty());
+}
+
+#[tokio::test]
+async fn test_stale_connection_closing_keeps_reconnected_controller() {
+    let mut engine = make_engine(State::default(), data::OfflinePolicy::Reset);
+    let init = || crate::messages::Init {
+        peer: make_phone("phone", "device-1"),
+        handshake: Default::default(),
+    };
+    engine
+        .handle_init(init(), 1)
+        .expect("first init should pass");
+    engine.active_state.mode = data::Mode::Live;
+    // The device reconnects while its first connection is still half open.
+    engine
+        .handle_init(init(), 2)
+        .expect("reconnect should pass");
+
+    let sample = || {
+        messages::SampleMotion(data::Sample::new(HashMap::from([(
+            name!("position"),
+            (0.0, 1.0, 0.0).into(),
+        )])))
+    };
+    assert!(matches!(
+        engine.handle_sample(sample(), 1),
+        Err(Error::UnknownController(_))
+    ));
+    engine
+        .handle_sample(sample(), 2)
+        .expect("the new connection should drive the controller");
+
+    assert_eq!(engine.disconnect_controller(1), None);
+    let controller = &engine.active_state.controllers[&name!("phone")];
+    assert!(controller.status.is_connected());
+    assert_eq!(
+        controller.properties[&name!("position")].value,
+        (0.0, 1.0, 0.0).into()
+    );
+
+    assert_eq!(engine.disconnect_controller(2), Some(name!("phone")));
+    assert_eq!(
+        engine.active_state.controllers[&name!("phone")].status,
+        data::ControllerStatus::Disconnected
+    );
+}
+
+#[tokio::test]
+async fn test_init_rejects_duplicate_controller() {
+    let state = State {
+        controllers: HashMap::from([(name!("phone"), make_phone("phone", "device-1"))]),
+        ..Default::default()
+    };
+    let mut engine = make_engine(state, data::OfflinePolicy::Hold);
+
+    let command = crate::messages::Init {
+        peer: make_phone("phone", "device-2"),
+        handshake: Default::default(),
+    };
+    assert!(matches!(
+        engine.handle_init(command, 2),
+        Err(Error::DuplicateController(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_close_connection_resets_controller_with_reset_policy() {
+    let mut phone = make_phone("phone", "device-1");
+    phone.properties.get_mut(&name!("position")).unwrap().value = (0.0, 5.0, 0.0).into();
+    let state = State {
+        controllers: HashMap::from([(name!("phone"), phone)]),
+        ..Default::default()
+    };
+    let mut engine = make_engine(state, data::OfflinePolicy::Reset);
+    engine.network.context_mut(1).name = Some(name!("phone"));
+
+    engine
+        .apply(messages::Message::with_command(
+            1,
+            messages::CloseConnection {},
+        ))
+        .await
+        .expect("close should be applied");
+
+    let controller = engine
+        .active_state
+        .controllers
+        .get(&name!("phone"))
+        .expect("controller should be kept while disconnected");
+    assert_eq!(controller.status, data::ControllerStatus::Disconnected);
+    assert_eq!(
+        controller.properties[&name!("position")].value,
+        data::Value::vec3()
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_disconnected_controller_fades_to_default() {
+    let mut phone = make_phone("phone", "device-1");
+    phone.properties.get_mut(&name!("position")).unwrap().value = (0.0, 10.0, 0.0).into();
+    let state = State {
+        controllers: HashMap::from([(name!("phone"), phone)]),
+        ..Default::default()
+    };
+    let mut engine = make_engine(
+        state,
+        data::OfflinePolicy::Fade(std::time::Duration::from_secs(1)),
+    );
+    engine.network.context_mut(1).name = Some(name!("phone"));
+    engine.disconnect_controller(1);
+
+    tokio::time::advance(std::time::Duration::from_millis(500)).await;
+    engine.tick().await.expect("tick should pass");
+    let value =
+        &engine.active_state.controllers[&name!("phone")].properties[&name!("position")].value;
+    assert_eq!(value, &data::Value::from((0.0, 5.0, 0.0)));
+
+    tokio::time::advance(std::time::Duration::from_secs(1)).await;
+    engine.tick().await.expect("tick should pass");
+    let value =
+        &engine.active_state.controllers[&name!("phone")].properties[&name!("position")].value;
+    assert_eq!(value, &data::Value::vec3());
+    assert!(engine.fading.is_empty());
+}
+
+#[tokio::test]
+async fn test_pong_records_latency() {
+    let mut engine = make_engine(State::default(), data::OfflinePolicy::Hold);
+    let sent = crate::clock::now_micros() - 20_000;
+    engine.network.context_mut(1).ping_timestamp = Some(sent);
+
+    engine
+        .apply(messages::Message::with_command(1, messages::Pong(sent)))
+        .await
+        .expect("pong should be applied");
+
+    let latency = &engine.network.context(1).unwrap().latency;
+    assert_eq!(latency.samples, 1);
+    assert!(latency.last.unwrap() >= std::time::Duration::from_millis(20));
+    assert_eq!(latency.last, latency.average);
+
+    // The ping was answered, a repeated pong does not count again.
+    engine.handle_pong(messages::Pong(sent), 1);
+    assert_eq!(engine.network.context(1).unwrap().latency.samples, 1);
+}
+
+#[tokio::test]
+async fn test_pong_ignores_implausible_timestamps() {
+    let mut engine = make_engine(State::default(), data::OfflinePolicy::Hold);
+    let now = crate::clock::now_micros();
+
+    // Nothing was pinged yet.
+    engine.handle_pong(messages::Pong(now), 1);
+    // Only the timestamp of the last ping is accepted, not an older ping or
+    // one picked to report a shorter round trip.
+    engine.network.context_mut(1).ping_timestamp = Some(now - 20_000);
+    for timestamp in [
+        i64::MIN,
+        i64::MAX,
+        now - 40_000,
+        now - 10_000,
+        now,
+        now + 60_000_000,
+    ] {
+        engine.handle_pong(messages::Pong(timestamp), 1);
+    }
+
+    assert_eq!(engine.network.context(1).unwrap().latency.samples, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_heartbeat_pings_and_closes_idle_connections() {
+    let values = NetworkSpyValues::new();
+    let mut network = Box::new(NetworkSpy::new(values.clone()));
+    network.context = connection::Context {
+        uid: 1,
+        opened: true,
+        connected_at: Some(tokio::time::Instant::now()),
+        last_seen: Some(tokio::time::Instant::now()),
+        ..Default::default()
+    };
+    let mut engine = Engine::builder()
+        .with_network_component(network)
+        .with_heartbeat(connection::HeartbeatOptions {
+            interval: std::time::Duration::from_secs(1),
+            idle_timeout: std::time::Duration::from_secs(5),
+            open_timeout: std::time::Duration::from_secs(5),
+        })
+        .build()
+        .expect("failed to build engine");
+
+    engine.tick().await.expect("tick should pass");
+    let events = std::mem::take(&mut values.lock().await.events);
+    let ping = events.iter().find_map(|event| match event.body {
+        EventBody::Ping(events::PingEvent(timestamp)) => Some(timestamp),
+        _ => None,
+    });
+    assert!(ping.is_some());
+    assert_eq!(engine.network.context(1).unwrap().ping_timestamp, ping);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event.body, EventBody::ConnectionClosed(_))));
+
+    tokio::time::advance(std::time::Duration::from_secs(6)).await;
+    engine.tick().await.expect("tick should pass");
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert!(events.contains(&Event {
+        target: None,
+        body: events::ConnectionClosedEvent(1).into(),
+    }));
+}
+
+#[tokio::test]
+async fn test_time_sync_maps_sample_timestamps_to_server_time() {
+    let state = State {
+        mode: crate::data::Mode::Live,
+        controllers: HashMap::from([(name!("phone"), make_phone("phone", "device-1"))]),
+        ..Default::default()
+    };
+    let mut engine = make_engine(state, data::OfflinePolicy::Hold);
+    engine.network.context_mut(1).name = Some(name!("phone"));
+
+    // The device clock runs five seconds ahead of the server clock.
+    let offset = 5_000_000;
+    let t0 = crate::clock::now_micros();
+    engine
+        .apply(messages::Message::with_command(
+            1,
+            messages::TimeSync {
+                t0,
+                t1: t0 + offset,
+                t2: t0 + offset,
+            },
+        ))
+        .await
+        .expect("time sync should be applied");
+    let estimate = engine.network.context(1).unwrap().clock.offset().unwrap();
+    assert!((estimate - offset).abs() < 1_000_000);
+
+    let device_time = t0 + offset + 10_000;
+    engine
+        .apply(messages::Message::with_command(
+            1,
+            messages::SampleMotion(
+                data::Sample::new(HashMap::from([(name!("position"), (0.0, 1.0, 0.0).into())]))
+                    .with_timestamp(device_time),
+            ),
+        ))
+        .await
+        .expect("sample should be applied");
+    let sampled_at = engine.active_state.controllers[&name!("phone")]
+        .sampled_at
+        .expect("the sample time should be recorded");
+    assert_eq!(sampled_at, device_time - estimate);
+}
+
+#[tokio::test]
+async fn test_roles_restrict_client_commands() {
+    let object = SceneObject::new(name!("camera"), HashMap::new());
+    let mut state = State::default();
+    state
+        .scene
+        .objects_mut()
+        .insert(object.name().clone(), object);
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_inital_state(state)
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+
+    for role in [connection::Role::Controller, connection::Role::Viewer] {
+        engine.network.context_mut(1).role = role;
+        engine
+            .apply(messages::Message::with_command(
+                1,
+                messages::DeleteSceneObject(name!("camera")),
+            ))
+            .await
+            .expect("the error should be reported to the sender");
+        let events = std::mem::take(&mut values.lock().await.events);
+        assert!(events.iter().any(|event| event.target == Some(1)
+            && matches!(&event.body, EventBody::Error(error) if matches!(error.error, Error::PermissionDenied(_))
+                && error.command == Some("DeleteSceneObject"))));
+        assert!(engine.active_state.scene.object(&name!("camera")).is_some());
+    }
+
+    engine.network.context_mut(1).role = connection::Role::Viewer;
+    let sent = crate::clock::now_micros();
+    engine.network.context_mut(1).ping_timestamp = Some(sent);
+    engine
+        .apply(messages::Message::with_command(1, messages::Pong(sent)))
+        .await
+        .expect("pong should be applied");
+    assert_eq!(engine.network.context(1).unwrap().latency.samples, 1);
+
+    // A batch is only permitted when all of its commands are.
+    engine.network.context_mut(1).role = connection::Role::Controller;
+    let batch = messages::Batch(vec![messages::ClientCommand::DeleteSceneObject(
+        messages::DeleteSceneObject(name!("camera")),
+    )]);
+    assert!(matches!(
+        engine
+            .handle_client_command(1, messages::ClientCommand::Batch(batch))
+            .await,
+        Err(Error::PermissionDenied(_))
+    ));
+
+    engine.network.context_mut(1).role = connection::Role::Operator;
+    engine
+        .apply(messages::Message::with_command(
+            1,
+            messages::DeleteSceneObject(name!("camera")),
+        ))
+        .await
+        .expect("delete should be applied");
+    assert!(engine.active_state.scene.object(&name!("camera")).is_none());
+}
+
+#[tokio::test]
+async fn test_batch_rejects_mode_changes() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+
+    // The failing edit would roll the mode back while the take kept recording.
+    let batch = messages::ClientCommand::Batch(messages::Batch(vec![
+        messages::ClientCommand::ChangeMode(messages::ChangeMode(crate::data::Mode::Recording)),
+        messages::ClientCommand::DeleteSceneObject(messages::DeleteSceneObject(name!("missing"))),
+    ]));
+    assert!(matches!(
+        engine.handle_client_command(1, batch).await,
+        Err(Error::BadCommand(_))
+    ));
+    assert!(engine.active_state.mode.is_idle());
+    assert!(!engine.recorder.is_recording());
+    assert!(engine.recorder.list().is_empty());
+}
+
+#[tokio::test]
+async fn test_request_ids_are_acknowledged() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+
+    let object = SceneObject::new(name!("camera"), HashMap::new());
+    engine
+        .apply(
+            messages::Message::with_command(1, messages::AddSceneObject(object.clone()))
+                .with_request_id(Some(7)),
+        )
+        .await
+        .unwrap();
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(
+        events.last(),
+        Some(&Event::new(1, events::AckEvent(7).into()))
+    );
+
+    // A failed command is answered with a nack in place of an error.
+    engine
+        .apply(
+            messages::Message::with_command(1, messages::AddSceneObject(object))
+                .with_request_id(Some(8)),
+        )
+        .await
+        .unwrap();
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0].body,
+        EventBody::Nack(events::NackEvent { request_id: 8, error })
+            if error.command == Some("AddSceneObject")
+    ));
+
+    // Without a request id nothing is acknowledged.
+    engine
+        .apply(messages::Message::with_command(
+            1,
+            messages::DeleteSceneObject(name!("camera")),
+        ))
+        .await
+        .unwrap();
+    assert!(values
+        .lock()
+        .await
+        .events
+        .iter()
+        .all(|event| event.target.is_none()));
+}
+
+#[tokio::test]
+async fn test_init_rejects_incompatible_clients() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+
+    let command = crate::messages::Init {
+        peer: data::Controller::new(name!("phone"), HashMap::new()),
+        handshake: crate::protocol::Handshake {
+            protocol_version: 99,
+            capabilities: vec![],
+        },
+    };
+    assert!(matches!(
+        engine.handle_init(command, 1),
+        Err(Error::IncompatibleClient(_))
+    ));
+    assert!(engine.active_state.controllers.is_empty());
+
+    let command = crate::messages::Init {
+        peer: data::Controller::new(name!("phone"), HashMap::new()),
+        handshake: crate::protocol::Handshake {
+            protocol_version: 1,
+            capabilities: vec!["value:float".into(), "from-the-future".into()],
+        },
+    };
+    engine.handle_init(command, 1).unwrap();
+    let context = engine.network.context(1).unwrap();
+    assert_eq!(context.protocol_version, Some(1));
+    assert_eq!(context.capabilities, vec!["value:float"]);
+}
+
+#[tokio::test]
+async fn test_batch_is_applied_atomically() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+    let camera = SceneObject::new(name!("camera"), HashMap::new());
+    let light = SceneObject::new(name!("light"), HashMap::new());
+
+    // The second camera fails, so the light added before it is rolled back.
+    let batch = messages::Batch(vec![
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(camera.clone())),
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(light.clone())),
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(camera.clone())),
+    ]);
+    engine
+        .apply(messages::Message::with_command(1, batch).with_request_id(Some(3)))
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.object(&name!("camera")).is_none());
+    assert!(engine.active_state.scene.object(&name!("light")).is_none());
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0].body,
+        EventBody::Nack(events::NackEvent { request_id: 3, error })
+            if matches!(error.error, Error::InvalidSceneObject(_))
+                && error.command == Some("Batch")
+    ));
+
+    let batch = messages::Batch(vec![
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(camera)),
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(light)),
+    ]);
+    engine
+        .apply(messages::Message::with_command(1, batch).with_request_id(Some(4)))
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.object(&name!("camera")).is_some());
+    assert!(engine.active_state.scene.object(&name!("light")).is_some());
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(
+        events,
+        vec![
+            Event {
+                target: None,
+                body: events::HistoryChangedEvent {
+                    undo: vec!["Batch".into()],
+                    redo: vec![],
+                }
+                .into(),
+            },
+            Event::new(1, events::AckEvent(4).into())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_batch_rejects_connection_commands() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+
+    let batch = messages::ClientCommand::Batch(messages::Batch(vec![
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(SceneObject::new(
+            name!("camera"),
+            HashMap::new(),
+        ))),
+        messages::ClientCommand::Batch(messages::Batch(vec![])),
+    ]));
+    assert!(matches!(
+        engine.handle_client_command(1, batch).await,
+        Err(Error::BadCommand(_))
+    ));
+    assert!(engine.active_state.scene.object(&name!("camera")).is_none());
+}
+
+#[tokio::test]
+async fn test_undo_and_redo_scene_edits() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+    let history = |undo: &[&str], redo: &[&str]| Event {
+        target: None,
+        body: events::HistoryChangedEvent {
+            undo: undo.iter().map(|label| label.to_string()).collect(),
+            redo: redo.iter().map(|label| label.to_string()).collect(),
+        }
+        .into(),
+    };
+
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::AddSceneObject(messages::AddSceneObject(SceneObject::new(
+                name!("camera"),
+                HashMap::new(),
+            ))),
+        )
+        .await
+        .unwrap();
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::ClearScene(messages::ClearScene {}),
+        )
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.objects().is_empty());
+
+    engine
+        .handle_client_command(1, messages::ClientCommand::Undo(messages::Undo {}))
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.object(&name!("camera")).is_some());
+    engine
+        .handle_client_command(1, messages::ClientCommand::Redo(messages::Redo {}))
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.objects().is_empty());
+
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(
+        events,
+        vec![
+            history(&["AddSceneObject"], &[]),
+            history(&["AddSceneObject", "ClearScene"], &[]),
+            history(&["AddSceneObject"], &["ClearScene"]),
+            history(&["AddSceneObject", "ClearScene"], &[]),
+        ]
+    );
+
+    // Edits that change nothing are not recorded.
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::ClearScene(messages::ClearScene {}),
+        )
+        .await
+        .unwrap();
+    assert!(values.lock().await.events.is_empty());
+    assert!(matches!(
+        engine
+            .handle_client_command(1, messages::ClientCommand::Redo(messages::Redo {}))
+            .await,
+        Err(Error::BadCommand(_))
+    ));
+
+    engine.active_state.mode = data::Mode::Live;
+    assert!(matches!(
+        engine
+            .handle_client_command(1, messages::ClientCommand::Undo(messages::Undo {}))
+            .await,
+        Err(Error::InvalidMode(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_property_commands() {
+    let mut state = State::default();
+    state
+        .controllers
+        .insert(name!("phone"), make_phone("phone", "device-1"));
+    let object = SceneObject::new(
+        name!("camera"),
+        HashMap::from([
+            (
+                name!("position"),
+                data::PropertyLink::unbound(data::Value::vec3()),
+            ),
+            (
+                name!("focal"),
+                data::PropertyLink::unbound(data::Value::Float(35.0)),
+            ),
+        ]),
+    );
+    state
+        .scene
+        .objects_mut()
+        .insert(object.name().clone(), object);
+    let mut engine = make_engine(state, data::OfflinePolicy::default());
+    let property = |engine: &Engine, name: &str| {
+        engine
+            .active_state
+            .scene
+            .object(&name!("camera"))
+            .unwrap()
+            .properties()[&Name::from(name)]
+            .clone()
+    };
+
+    let binding = data::PropertyReference {
+        namespace: name!("phone"),
+        property: name!("position"),
+    };
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::BindProperty(messages::BindProperty {
+                object: name!("camera"),
+                property: name!("position"),
+                binding: binding.clone(),
+            }),
+        )
+        .await
+        .unwrap();
+    assert!(property(&engine, "position").has_binding());
+
+    // The value type of the controller property must match.
+    assert!(matches!(
+        engine
+            .handle_client_command(
+                1,
+                messages::ClientCommand::BindProperty(messages::BindProperty {
+                    object: name!("camera"),
+                    property: name!("focal"),
+                    binding: binding.clone(),
+                }),
+            )
+            .await,
+        Err(Error::InvalidBinding(_))
+    ));
+
+    // Bound values are driven by the controller.
+    let set_position = |value: data::Value| {
+        messages::ClientCommand::SetPropertyValue(messages::SetPropertyValue {
+            object: name!("camera"),
+            property: name!("position"),
+            value,
+        })
+    };
+    assert!(matches!(
+        engine
+            .handle_client_command(1, set_position(data::Value::vec3()))
+            .await,
+        Err(Error::InvalidBinding(_))
+    ));
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::UnbindProperty(messages::UnbindProperty {
+                object: name!("camera"),
+                property: name!("position"),
+            }),
+        )
+        .await
+        .unwrap();
+    engine
+        .handle_client_command(1, set_position(data::Value::Vec3((1.0, 2.0, 3.0).into())))
+        .await
+        .unwrap();
+    assert_eq!(
+        property(&engine, "position"),
+        data::PropertyLink::unbound(data::Value::Vec3((1.0, 2.0, 3.0).into()))
+    );
+    assert!(matches!(
+        engine
+            .handle_client_command(1, set_position(data::Value::Float(1.0)))
+            .await,
+        Err(Error::InvalidValue(_))
+    ));
+
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::AddProperty(messages::AddProperty {
+                object: name!("camera"),
+                property: name!("zoom"),
+                link: data::PropertyLink::unbound(data::Value::Float(1.0)),
+            }),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        engine
+            .handle_client_command(
+                1,
+                messages::ClientCommand::AddProperty(messages::AddProperty {
+                    object: name!("camera"),
+                    property: name!("zoom"),
+                    link: data::PropertyLink::unbound(data::Value::Float(1.0)),
+                }),
+            )
+            .await,
+        Err(Error::InvalidSceneObject(_))
+    ));
+    let remove_focal = || {
+        messages::ClientCommand::RemoveProperty(messages::RemoveProperty {
+            object: name!("camera"),
+            property: name!("focal"),
+        })
+    };
+    engine
+        .handle_client_command(1, remove_focal())
+        .await
+        .unwrap();
+    assert!(matches!(
+        engine.handle_client_command(1, remove_focal()).await,
+        Err(Error::UnknownProperty(_))
+    ));
+
+    let rename = |name: &str, new_name: &str| {
+        messages::ClientCommand::RenameSceneObject(messages::RenameSceneObject {
+            name: name.into(),
+            new_name: new_name.into(),
+        })
+    };
+    assert!(matches!(
+        engine
+            .handle_client_command(1, rename("camera", "default"))
+            .await,
+        Err(Error::InvalidSceneObject(_))
+    ));
+    engine
+        .handle_client_command(1, rename("camera", "crane"))
+        .await
+        .unwrap();
+    let crane = engine.active_state.scene.object(&name!("crane")).unwrap();
+    assert_eq!(crane.name(), &name!("crane"));
+    assert!(crane.property(&name!("zoom")).is_some());
+    assert!(engine.active_state.scene.object(&name!("camera")).is_none());
+}
+
+#[tokio::test]
+async fn test_adjust_property_while_not_idle() {
+    let mut state = State::default();
+    state
+        .controllers
+        .insert(name!("phone"), make_phone("phone", "device-1"));
+    state.scene.objects_mut().insert(
+        name!("lens"),
+        SceneObject::new(
+            name!("lens"),
+            HashMap::from([
+                (
+                    name!("focal"),
+                    data::PropertyLink::unbound(data::Value::Float(35.0)),
+                ),
+                (
+                    name!("offset"),
+                    data::PropertyLink::unbound(data::Value::vec3()),
+                ),
+            ]),
+        ),
+    );
+    let mut engine = make_engine(state, data::OfflinePolicy::default());
+    let adjust = |property: &str, adjustment: messages::Adjustment| {
+        messages::ClientCommand::AdjustProperty(messages::AdjustProperty {
+            object: name!("lens"),
+            property: property.into(),
+            adjustment,
+        })
+    };
+    let property = |engine: &Engine, name: &str| {
+        engine
+            .active_state
+            .scene
+            .object(&name!("lens"))
+            .unwrap()
+            .properties()[&Name::from(name)]
+            .clone()
+    };
+
+    for mode in [data::Mode::Live, data::Mode::Recording] {
+        engine.active_state.mode = mode;
+
+        // Structural edits and the idle-only property commands stay blocked.
+        assert!(matches!(
+            engine
+                .handle_client_command(
+                    1,
+                    messages::ClientCommand::DeleteSceneObject(messages::DeleteSceneObject(name!(
+                        "lens"
+                    ))),
+                )
+                .await,
+            Err(Error::InvalidMode(_))
+        ));
+        assert!(matches!(
+            engine
+                .handle_client_command(
+                    1,
+                    messages::ClientCommand::SetPropertyValue(messages::SetPropertyValue {
+                        object: name!("lens"),
+                        property: name!("focal"),
+                        value: data::Value::Float(50.0),
+                    }),
+                )
+                .await,
+            Err(Error::InvalidMode(_))
+        ));
+
+        engine
+            .handle_client_command(
+                1,
+                adjust(
+                    "focal",
+                    messages::Adjustment::SetValue(data::Value::Float(50.0)),
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            property(&engine, "focal").value(),
+            &data::Value::Float(50.0)
+        );
+
+        engine
+            .handle_client_command(
+                1,
+                adjust(
+                    "offset",
+                    messages::Adjustment::Bind(data::PropertyReference {
+                        namespace: name!("phone"),
+                        property: name!("position"),
+                    }),
+                ),
+            )
+            .await
+            .unwrap();
+        assert!(property(&engine, "offset").has_binding());
+        engine
+            .handle_client_command(1, adjust("offset", messages::Adjustment::Unbind))
+            .await
+            .unwrap();
+        assert!(!property(&engine, "offset").has_binding());
+    }
+
+    // The adjusted values are part of the rendered state.
+    engine.tick().await.unwrap();
+    assert_eq!(
+        engine
+            .current_state
+            .scene
+            .object(&name!("lens"))
+            .unwrap()
+            .properties()[&name!("focal")]
+            .value(),
+        &data::Value::Float(50.0)
+    );
+}
+
+#[tokio::test]
+async fn test_save_load_and_list_scenes() {
+    let values = NetworkSpyValues::new();
+    let mut engine = Engine::builder()
+        .with_network_component(Box::new(NetworkSpy::new(values.clone())))
+        .build()
+        .expect("failed to build engine");
+    let add = |name: &str| {
+        messages::ClientCommand::AddSceneObject(messages::AddSceneObject(SceneObject::new(
+            name.into(),
+            HashMap::new(),
+        )))
+    };
+
+    engine.handle_client_command(1, add("jib")).await.unwrap();
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::SaveScene(messages::SaveScene(name!("crane"))),
+        )
+        .await
+        .unwrap();
+    assert_eq!(engine.active_state.scene.name, name!("crane"));
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(
+        events.last(),
+        Some(&Event {
+            target: None,
+            body: events::SceneListEvent(vec![name!("crane")]).into(),
+        })
+    );
+
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::ClearScene(messages::ClearScene {}),
+        )
+        .await
+        .unwrap();
+    engine
+        .handle_client_command(
+            1,
+            messages::ClientCommand::LoadScene(messages::LoadScene(name!("crane"))),
+        )
+        .await
+        .unwrap();
+    assert!(engine.active_state.scene.object(&name!("jib")).is_some());
+    assert!(matches!(
+        engine
+            .handle_client_command(
+                1,
+                messages::ClientCommand::LoadScene(messages::LoadScene(name!("handheld"))),
+            )
+            .await,
+        Err(Error::UnknownScene(_))
+    ));
+
+    // Scenes are only switched while idle.
+    engine.active_state.mode = data::Mode::Live;
+    assert!(matches!(
+        engine
+            .handle_client_command(
+                1,
+                messages::ClientCommand::LoadScene(messages::LoadScene(name!("crane"))),
+            )
+            .await,
+        Err(Error::InvalidMode(_))
+    ));
+
+    // Viewers may list the scenes, the answer only goes to them.
+    engine.network.context_mut(1).role = connection::Role::Viewer;
+    std::mem::take(&mut values.lock().await.events);
+    engine
+        .apply(messages::Message::with_command(1, messages::ListScenes {}))
+        .await
+        .unwrap();
+    let events = std::mem::take(&mut values.lock().await.events);
+    assert_eq!(
+        events,
+        vec![Event::new(
+            1,
+            events::SceneListEvent(vec![name!("crane")]).into()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn test_recording_captures_takes() {
+    let mut state = State::default();
+    state.scene.objects_mut().insert(
+        name!("camera"),
+        SceneObject::new(
+            name!("camera"),
+            HashMap::from([(
+                name!("focal_length"),
+                data::PropertyLink::unbound(35.0.into()),
+            )]),
+        ),
+    );
+    let mut engine = make_engine(state, data::OfflinePolicy::Hold);
+    let change_mode = |mode| messages::ClientCommand::ChangeMode(messages::ChangeMode(mode));
+
+    // Nothing is captured outside of the recording mode.
+    engine.tick().await.unwrap();
+    engine
+        .handle_client_command(1, change_mode(data::Mode::Live))
+        .await
+        .unwrap();
+    engine.tick().await.unwrap();
+    assert!(engine.recorder.list().is_empty());
+
+    engine
+        .handle_client_command(1, change_mode(data::Mode::Recording))
+        .await
+        .unwrap();
+    engine.tick().await.unwrap();
+    engine.tick().await.unwrap();
+    engine
+        .handle_client_command(1, change_mode(data::Mode::Idle))
+        .await
+        .unwrap();
+    engine.tick().await.unwrap();
+
+    let takes = engine.recorder.list();
+    assert_eq!(takes.len(), 1);
+    assert_eq!(takes[0].frames, 2);
+    let take = engine.recorder.get(&name!("take-1")).unwrap();
+    assert_eq!(
+        take.frames[1].objects[&name!("camera")][&name!("focal_length")],
+        35.0.into()
+    );
+}
+
+#[tokio::test]
+async fn test_live_adjustments_are_merged_and_recorded() {
+    let mut state = State::default();
+    state.scene.objects_mut().insert(
+        name!("lens"),
+        SceneObject::new(
+            name!("lens"),
+            HashMap::from([
+                (name!("focal"), data::PropertyLink::unbound(35.0.into())),
+                (name!("iris"), data::PropertyLink::unbound(2.8.into())),
+            ]),
+        ),
+    );
+    let mut engine = make_engine(state, data::OfflinePolicy::Hold);
+    let change_mode = |mode| messages::ClientCommand::ChangeMode(messages::ChangeMode(mode));
+    let adjust = |property: &str, value: f64| {
+        messages::ClientCommand::AdjustProperty(messages::AdjustProperty {
+            object: name!("lens"),
+            property: property.into(),
+            adjustment: messages::Adjustment::SetValue(value.into()),
+        })
+    };
+    let focal = |engine: &Engine| {
+        engine
+            .active_state
+            .scene
+            .object(&name!("lens"))
+            .unwrap()
+            .properties()[&name!("focal")]
+            .value()
+            .clone()
+    };
+
+    engine
+        .handle_client_command(1, change_mode(data::Mode::Recording))
+        .await
+        .unwrap();
+    for value in [40.0, 45.0, 50.0] {
+        engine
+            .handle_client_command(1, adjust("focal", value))
+            .await
+            .unwrap();
+        engine.tick().await.unwrap();
+    }
+    // A run of adjustments to one property is a single edit.
+    assert_eq!(engine.history.undo_labels(), vec!["AdjustProperty"]);
+    engine
+        .handle_client_command(1, adjust("iris", 4.0))
+        .await
+        .unwrap();
+    engine
+        .handle_client_command(1, adjust("focal", 85.0))
+        .await
+        .unwrap();
+    assert_eq!(engine.history.undo_labels().len(), 3);
+    engine
+        .handle_client_command(1, change_mode(data::Mode::Idle))
+        .await
+        .unwrap();
+
+    // The adjustments are captured in the take.
+    let take = engine.recorder.get(&name!("take-1")).unwrap();
+    let values: Vec<_> = take
+        .frames
+        .iter()
+        .map(|frame| frame.objects[&name!("lens")][&name!("focal")].clone())
+        .collect();
+    assert_eq!(values, vec![40.0.into(), 45.0.into(), 50.0.into()]);
+
+    let undo = || messages::ClientCommand::Undo(messages::Undo {});
+    engine.handle_client_command(1, undo()).await.unwrap();
+    assert_eq!(focal(&engine), 50.0.into());
+    engine.handle_client_command(1, undo()).await.unwrap();
+    engine.handle_client_command(1, undo()).await.unwrap();
+    assert_eq!(focal(&engine), 35.0.into());
+}
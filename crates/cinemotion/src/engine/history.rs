@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+use crate::scene::Scene;
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod history_test;
+
+/// How many scene edits can be undone when no depth is configured.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// The scenes from before each edit, so edits can be undone and redone.
+#[derive(Debug, Clone)]
+pub struct History {
+    depth: usize,
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+}
+
+/// A scene along with the name of the command that replaced it.
+#[derive(Debug, Clone)]
+struct Entry {
+    label: &'static str,
+    /// Identifies edits that are merged into this one when they follow it.
+    key: Option<String>,
+    scene: Scene,
+}
+
+impl History {
+    /// A history keeping at most `depth` edits, the oldest are dropped first.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Remember the scene from before an edit. The edits that were undone can
+    /// no longer be redone.
+    pub fn record(&mut self, label: &'static str, scene: Scene) {
+        self.push(label, None, scene);
+    }
+
+    /// Remember the scene from before an edit unless the last edit has the same
+    /// key, like a run of adjustments to one property, which are undone as one.
+    ///
+    /// Returns whether the history changed.
+    pub fn record_merged(&mut self, label: &'static str, key: String, scene: Scene) -> bool {
+        let continues_last = self.redo.is_empty()
+            && self
+                .undo
+                .back()
+                .is_some_and(|entry| entry.key.as_ref() == Some(&key));
+        if continues_last {
+            return false;
+        }
+        self.push(label, Some(key), scene);
+        true
+    }
+
+    fn push(&mut self, label: &'static str, key: Option<String>, scene: Scene) {
+        self.redo.clear();
+        if self.depth == 0 {
+            return;
+        }
+        if self.undo.len() == self.depth {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(Entry { label, key, scene });
+    }
+
+    /// Swap the current scene for the one from before the last edit.
+    pub fn undo(&mut self, current: Scene) -> Option<Scene> {
+        let entry = self.undo.pop_back()?;
+        self.redo.push(Entry {
+            label: entry.label,
+            key: entry.key,
+            scene: current,
+        });
+        Some(entry.scene)
+    }
+
+    /// Swap the current scene for the one from after the last undone edit.
+    pub fn redo(&mut self, current: Scene) -> Option<Scene> {
+        let entry = self.redo.pop()?;
+        self.undo.push_back(Entry {
+            label: entry.label,
+            key: entry.key,
+            scene: current,
+        });
+        Some(entry.scene)
+    }
+
+    /// The names of the edits that can be undone, the most recent last.
+    pub fn undo_labels(&self) -> Vec<String> {
+        self.undo
+            .iter()
+            .map(|entry| entry.label.to_string())
+            .collect()
+    }
+
+    /// The names of the edits that can be redone, the next one last.
+    pub fn redo_labels(&self) -> Vec<String> {
+        self.redo
+            .iter()
+            .map(|entry| entry.label.to_string())
+            .collect()
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
@@ -0,0 +1,84 @@
+use super::*;
+use crate::name;
+use crate::scene::SceneObject;
+
+fn scene_with(names: &[&str]) -> Scene {
+    let mut scene = Scene::default();
+    for name in names {
+        scene.objects_mut().insert(
+            (*name).into(),
+            SceneObject::new((*name).into(), Default::default()),
+        );
+    }
+    scene
+}
+
+#[test]
+fn test_undo_and_redo() {
+    let mut history = History::default();
+    assert!(history.undo(scene_with(&[])).is_none());
+
+    history.record("AddSceneObject", scene_with(&[]));
+    history.record("AddSceneObject", scene_with(&["camera"]));
+    assert_eq!(
+        history.undo_labels(),
+        vec!["AddSceneObject", "AddSceneObject"]
+    );
+
+    let scene = history.undo(scene_with(&["camera", "light"])).unwrap();
+    assert_eq!(scene, scene_with(&["camera"]));
+    assert_eq!(history.redo_labels(), vec!["AddSceneObject"]);
+
+    let scene = history.redo(scene).unwrap();
+    assert_eq!(scene, scene_with(&["camera", "light"]));
+    assert!(history.redo_labels().is_empty());
+    assert!(history.redo(scene).is_none());
+}
+
+#[test]
+fn test_record_clears_redo() {
+    let mut history = History::default();
+    history.record("AddSceneObject", scene_with(&[]));
+    history.undo(scene_with(&["camera"])).unwrap();
+    history.record("ClearScene", scene_with(&[]));
+    assert!(history.redo_labels().is_empty());
+    assert_eq!(history.undo_labels(), vec!["ClearScene"]);
+}
+
+#[test]
+fn test_depth_drops_oldest_edits() {
+    let mut history = History::new(2);
+    history.record("AddSceneObject", scene_with(&[]));
+    history.record("UpdateSceneObject", scene_with(&["camera"]));
+    history.record("DeleteSceneObject", scene_with(&["camera"]));
+    assert_eq!(
+        history.undo_labels(),
+        vec!["UpdateSceneObject", "DeleteSceneObject"]
+    );
+    assert!(history
+        .undo(scene_with(&[]))
+        .is_some_and(|scene| scene.object(&name!("camera")).is_some()));
+
+    let mut disabled = History::new(0);
+    disabled.record("ClearScene", scene_with(&[]));
+    assert!(disabled.undo_labels().is_empty());
+}
+
+#[test]
+fn test_record_merged() {
+    let mut history = History::default();
+    assert!(history.record_merged("AdjustProperty", "lens.focal".into(), scene_with(&[])));
+    assert!(!history.record_merged("AdjustProperty", "lens.focal".into(), scene_with(&["a"])));
+    assert!(history.record_merged("AdjustProperty", "lens.offset".into(), scene_with(&["b"])));
+    assert!(history.record_merged("AdjustProperty", "lens.focal".into(), scene_with(&["c"])));
+    assert_eq!(history.undo_labels().len(), 3);
+
+    // The run keeps the scene from before its first edit.
+    history.undo(scene_with(&["d"]));
+    history.undo(scene_with(&["c"]));
+    assert_eq!(history.undo(scene_with(&["b"])), Some(scene_with(&[])));
+
+    // An edit that was redone is not merged into, it can be undone again.
+    history.redo(scene_with(&[]));
+    assert!(history.record_merged("AdjustProperty", "lens.focal".into(), scene_with(&["b"])));
+}
@@ -1,8 +1,10 @@
 #![allow(clippy::module_inception)]
 pub mod components;
 pub mod engine;
+pub mod history;
 pub mod network;
 pub mod observer;
 
 pub use engine::{Builder, Engine};
+pub use history::{History, DEFAULT_HISTORY_DEPTH};
 pub use observer::Observer;
@@ -3,15 +3,20 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 
 use crate::{
-    connection::{Connection, Context},
+    connection::{Connection, Context, LOCAL_CONN_ID},
     events::{event_pipe, EventPipeTx},
     messages::{AddConnection, MessagePipeTx},
-    Error, Event, Result,
+    Event, Result,
 };
 
 use super::components::network::NetworkComponent;
 
+#[cfg(test)]
+#[path = "network_test.rs"]
+mod network_test;
+
 pub struct NetworkComponentImpl {
+    next_id: usize,
     connections: HashMap<usize, Box<Connection>>,
     contexts: HashMap<usize, Context>,
     message_pipe: MessagePipeTx,
@@ -20,8 +25,17 @@ pub struct NetworkComponentImpl {
 
 impl NetworkComponentImpl {
     pub fn boxed(message_pipe: MessagePipeTx) -> Box<dyn NetworkComponent> {
-        let event_pipe = event_pipe();
+        Self::boxed_with_event_pipe(message_pipe, event_pipe())
+    }
+
+    /// Create the component broadcasting events on the given pipe, so other
+    /// services can subscribe to them too.
+    pub fn boxed_with_event_pipe(
+        message_pipe: MessagePipeTx,
+        event_pipe: EventPipeTx,
+    ) -> Box<dyn NetworkComponent> {
         Box::new(Self {
+            next_id: LOCAL_CONN_ID + 1,
             connections: Default::default(),
             contexts: Default::default(),
             message_pipe,
@@ -40,6 +54,10 @@ impl NetworkComponent for NetworkComponentImpl {
         }
     }
 
+    fn contexts(&self) -> Vec<&Context> {
+        self.contexts.values().collect()
+    }
+
     /// Get a mutable reference to the context for a connection id.
     /// If the context does not exist then create it.
     fn context_mut(&mut self, conn_id: usize) -> &mut Context {
@@ -48,14 +66,13 @@ impl NetworkComponent for NetworkComponentImpl {
     }
 
     async fn add_connection(&mut self, options: AddConnection) -> Result<()> {
-        // TODO: add a connection open timeout that will close the connection if it is not opened
-        // Do this on the engine side that tracks if the connection is open or not and each tick
-        // check timeout.
         let agent = options.agent;
         let ack_pipe = options.ack_pipe;
-        // FIXME: change this to use a atomic incrementor, if connection disconnect and the
-        // reconnect then the id migh be reused.
-        let active_id = self.connections.len() + 1;
+        let role = options.role;
+        // Ids are never reused so events targeted at a closed connection can not
+        // be routed to a newer one.
+        let active_id = self.next_id;
+        self.next_id += 1;
         let conn = Box::new(Connection::new(
             active_id,
             self.message_pipe.clone(),
@@ -63,27 +80,40 @@ impl NetworkComponent for NetworkComponentImpl {
             agent,
         ));
         self.connections.insert(active_id, conn);
+        self.contexts.insert(
+            active_id,
+            Context {
+                uid: active_id,
+                role,
+                connected_at: Some(tokio::time::Instant::now()),
+                last_seen: Some(tokio::time::Instant::now()),
+                ..Default::default()
+            },
+        );
         if ack_pipe.send(Ok(active_id)).is_err() {
             tracing::error!(
                 "create ack pipe dropped while creating connection, dropping connection"
             );
-            let _ = self.connections.remove(&active_id);
+            if let Some(conn) = self.connections.remove(&active_id) {
+                conn.close().await;
+            }
+            let _ = self.contexts.remove(&active_id);
         }
         Ok(())
     }
 
     async fn close_connection(&mut self, conn_id: usize) -> Result<()> {
-        self.connections.remove(&conn_id);
+        if let Some(conn) = self.connections.remove(&conn_id) {
+            conn.close().await;
+        }
         self.contexts.remove(&conn_id);
         Ok(())
     }
 
     async fn send(&mut self, event: Event) -> Result<()> {
-        if self.event_pipe.send(event).is_err() {
-            return Err(Error::EngineFailed(
-                "event pipe closed unexpectedly.".to_string(),
-            ));
-        }
+        // Sending only fails when nothing is subscribed, which is the normal
+        // state of a server without connections.
+        let _ = self.event_pipe.send(event);
         Ok(())
     }
 }
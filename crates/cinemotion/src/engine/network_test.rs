@@ -0,0 +1,60 @@
+use super::*;
+use crate::connection::{ConnectionAgent, SendHandlerFn};
+use crate::messages::message_pipe;
+
+struct NullAgent;
+
+#[async_trait]
+impl ConnectionAgent for NullAgent {
+    async fn initialize(&mut self, _: SendHandlerFn) {}
+    async fn receive(&mut self, _: Event) {}
+    async fn close(&mut self) {}
+}
+
+async fn add_connection(network: &mut Box<dyn NetworkComponent>) -> usize {
+    let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
+    network
+        .add_connection(AddConnection {
+            agent: Box::new(NullAgent),
+            role: Default::default(),
+            ack_pipe,
+        })
+        .await
+        .expect("connection should be added");
+    ack_pipe_rx
+        .await
+        .expect("ack should be sent")
+        .expect("connection should be acknowledged")
+}
+
+#[tokio::test]
+async fn test_connection_ids_are_not_reused() {
+    let (sender, _receiver) = message_pipe();
+    let mut network = NetworkComponentImpl::boxed(sender);
+
+    let first = add_connection(&mut network).await;
+    let second = add_connection(&mut network).await;
+    network
+        .close_connection(first)
+        .await
+        .expect("connection should close");
+    let third = add_connection(&mut network).await;
+
+    assert_eq!((first, second, third), (1, 2, 3));
+    assert!(network.context(first).is_none());
+    assert_eq!(network.context(third).map(|context| context.uid), Some(3));
+}
+
+#[tokio::test]
+async fn test_send_without_connections() {
+    let (sender, _receiver) = message_pipe();
+    let mut network = NetworkComponentImpl::boxed(sender);
+
+    network
+        .send(crate::Event {
+            target: None,
+            body: crate::events::ConnectionClosedEvent(1).into(),
+        })
+        .await
+        .expect("broadcasting without subscribers should not fail");
+}
@@ -29,9 +29,54 @@ pub enum Error {
     #[error("invalid property value: {0}")]
     InvalidValue(String),
 
+    #[error("property value out of range: {0}")]
+    ValueOutOfRange(String),
+
+    #[error("unknown controller: {0}")]
+    UnknownController(String),
+
+    #[error("duplicate controller: {0}")]
+    DuplicateController(String),
+
+    #[error("unknown property: {0}")]
+    UnknownProperty(String),
+
+    #[error("invalid property binding: {0}")]
+    InvalidBinding(String),
+
+    #[error("discovery failed: {0}")]
+    DiscoveryFailed(String),
+
+    #[error("failed to load the server identity: {0}")]
+    IdentityFailed(String),
+
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("invalid mode: {0}")]
     InvalidMode(String),
 
+    #[error("incompatible client: {0}")]
+    IncompatibleClient(String),
+
+    #[error("unknown signaling session: {0}")]
+    UnknownSession(String),
+
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("unknown scene: {0}")]
+    UnknownScene(String),
+
+    #[error("unknown take: {0}")]
+    UnknownTake(String),
+
+    #[error("failed to store scenes: {0}")]
+    StorageFailed(String),
+
     #[error("cannot modify closed take")]
     TakeClosed,
 }
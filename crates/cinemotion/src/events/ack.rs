@@ -0,0 +1,54 @@
+use super::{ErrorEvent, EventBody};
+use cinemotion_proto as proto;
+
+/// Sent to a connection once a command it sent with a request id was applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AckEvent(pub u64);
+
+impl From<AckEvent> for EventBody {
+    fn from(value: AckEvent) -> Self {
+        Self::Ack(value)
+    }
+}
+
+impl From<AckEvent> for proto::AckEvent {
+    fn from(value: AckEvent) -> Self {
+        Self {
+            request_id: value.0,
+        }
+    }
+}
+
+/// Sent to a connection in place of an error event when a command it sent
+/// with a request id failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NackEvent {
+    pub request_id: u64,
+    pub error: ErrorEvent,
+}
+
+impl EventBody {
+    /// The answer to a command that failed, a nack when it was sent with a
+    /// request id and a bare error otherwise.
+    pub fn rejection(error: ErrorEvent, request_id: Option<u64>) -> Self {
+        match request_id {
+            Some(request_id) => NackEvent { request_id, error }.into(),
+            None => error.into(),
+        }
+    }
+}
+
+impl From<NackEvent> for EventBody {
+    fn from(value: NackEvent) -> Self {
+        Self::Nack(value)
+    }
+}
+
+impl From<NackEvent> for proto::NackEvent {
+    fn from(value: NackEvent) -> Self {
+        Self {
+            request_id: value.request_id,
+            error: Some(value.error.into()),
+        }
+    }
+}
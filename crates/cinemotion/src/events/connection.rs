@@ -1,4 +1,5 @@
 use super::EventBody;
+use crate::{protocol, Name};
 use cinemotion_proto as proto;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,8 +11,102 @@ impl From<ConnectionOpenedEvent> for EventBody {
     }
 }
 
+/// The hello advertises what the server supports, see `crate::protocol`.
 impl From<ConnectionOpenedEvent> for proto::ConnectionOpenedEvent {
     fn from(_: ConnectionOpenedEvent) -> Self {
-        Self {}
+        Self {
+            server_version: crate::VERSION.to_string(),
+            protocol_versions: protocol::PROTOCOL_VERSIONS.to_vec(),
+            capabilities: protocol::server_capabilities(),
+        }
+    }
+}
+
+/// Broadcast when a connection is closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionClosedEvent(pub usize);
+
+impl From<ConnectionClosedEvent> for EventBody {
+    fn from(value: ConnectionClosedEvent) -> Self {
+        Self::ConnectionClosed(value)
+    }
+}
+
+impl From<ConnectionClosedEvent> for proto::ConnectionClosedEvent {
+    fn from(value: ConnectionClosedEvent) -> Self {
+        Self {
+            connection_id: value.0 as u64,
+        }
+    }
+}
+
+/// Broadcast when a controller is initialized on a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerJoinedEvent(pub Name);
+
+impl From<ControllerJoinedEvent> for EventBody {
+    fn from(value: ControllerJoinedEvent) -> Self {
+        Self::ControllerJoined(value)
+    }
+}
+
+impl From<ControllerJoinedEvent> for proto::ControllerJoinedEvent {
+    fn from(value: ControllerJoinedEvent) -> Self {
+        Self {
+            name: value.0.to_string(),
+        }
+    }
+}
+
+/// Broadcast when the connection of a controller is closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerLeftEvent(pub Name);
+
+impl From<ControllerLeftEvent> for EventBody {
+    fn from(value: ControllerLeftEvent) -> Self {
+        Self::ControllerLeft(value)
+    }
+}
+
+impl From<ControllerLeftEvent> for proto::ControllerLeftEvent {
+    fn from(value: ControllerLeftEvent) -> Self {
+        Self {
+            name: value.0.to_string(),
+        }
+    }
+}
+
+/// A heartbeat sent to connections with the server time in microseconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingEvent(pub i64);
+
+impl From<PingEvent> for EventBody {
+    fn from(value: PingEvent) -> Self {
+        Self::Ping(value)
+    }
+}
+
+impl From<PingEvent> for proto::PingEvent {
+    fn from(value: PingEvent) -> Self {
+        Self { timestamp: value.0 }
+    }
+}
+
+/// A clock synchronization request with the server time in microseconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSyncEvent(pub i64);
+
+impl From<TimeSyncEvent> for EventBody {
+    fn from(value: TimeSyncEvent) -> Self {
+        Self::TimeSync(value)
+    }
+}
+
+impl From<TimeSyncEvent> for proto::TimeSync {
+    fn from(value: TimeSyncEvent) -> Self {
+        Self {
+            t0: value.0,
+            ..Default::default()
+        }
     }
 }
@@ -1,25 +1,78 @@
 use crate::Error;
 use cinemotion_proto as proto;
 
+use proto::error_event::ErrorType;
+
+#[cfg(test)]
+#[path = "error_test.rs"]
+mod error_test;
+
 #[derive(Debug, Clone, PartialEq)]
-pub struct ErrorEvent(pub Error);
+pub struct ErrorEvent {
+    pub error: Error,
+    /// The name of the command that failed, if the error answers one.
+    pub command: Option<&'static str>,
+}
+
+impl ErrorEvent {
+    /// An error answering the named command.
+    pub fn with_command(error: Error, command: &'static str) -> Self {
+        Self {
+            error,
+            command: Some(command),
+        }
+    }
+}
 
 impl From<Error> for ErrorEvent {
     fn from(error: Error) -> Self {
-        Self(error)
+        Self {
+            error,
+            command: None,
+        }
     }
 }
 
 impl From<ErrorEvent> for proto::ErrorEvent {
     fn from(value: ErrorEvent) -> Self {
-        let description = value.0.to_string();
-        let error_type: proto::error_event::ErrorType = match value.0 {
-            Error::InvalidSceneObject(_) => proto::error_event::ErrorType::InvalidSceneObject,
-            _ => proto::error_event::ErrorType::Unknown,
-        };
         proto::ErrorEvent {
-            description,
-            r#type: error_type.into(),
+            description: value.error.to_string(),
+            r#type: error_type(&value.error).into(),
+            command: value.command.unwrap_or_default().to_string(),
         }
     }
 }
+
+/// The type of an error, shared by error events and HTTP problem details.
+pub fn error_type(error: &Error) -> ErrorType {
+    match error {
+        Error::BadCommand(_) | Error::BadRTCDescriptor(_) => ErrorType::BadCommand,
+        Error::InvalidMode(_) | Error::TakeClosed => ErrorType::InvalidMode,
+        Error::IncompatibleClient(_) => ErrorType::IncompatibleClient,
+        Error::InvalidSceneObject(_) => ErrorType::InvalidSceneObject,
+        Error::InvalidBinding(_) => ErrorType::InvalidBinding,
+        Error::InvalidValue(_) => ErrorType::InvalidValue,
+        Error::ValueOutOfRange(_) => ErrorType::ValueOutOfRange,
+        Error::UnknownController(_) => ErrorType::UnknownController,
+        Error::UnknownProperty(_) => ErrorType::UnknownProperty,
+        Error::DuplicateController(_) => ErrorType::DuplicateController,
+        Error::UnknownSession(_) => ErrorType::UnknownSession,
+        Error::UnknownScene(_) => ErrorType::UnknownScene,
+        Error::UnknownTake(_) => ErrorType::UnknownTake,
+        Error::PermissionDenied(_) => ErrorType::PermissionDenied,
+        Error::Unauthorized(_) => ErrorType::Unauthorized,
+        Error::RateLimited(_) => ErrorType::RateLimited,
+        Error::SignalingFailed(_) | Error::WebRTCError(_) | Error::ConnectionFailed(_) => {
+            ErrorType::SignalingFailed
+        }
+        Error::ChannelClosed(_) | Error::EngineFailed(_) => ErrorType::Unavailable,
+        Error::DiscoveryFailed(_) | Error::IdentityFailed(_) | Error::StorageFailed(_) => {
+            ErrorType::Unknown
+        }
+    }
+}
+
+/// The snake case code of an error type, like `invalid_mode`.
+pub fn error_code(error: &Error) -> String {
+    error_type(error).as_str_name().to_lowercase()
+}
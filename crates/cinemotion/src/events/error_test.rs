@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn test_error_event_to_proto() {
+    let event = ErrorEvent::with_command(Error::InvalidMode("not idle".into()), "ChangeMode");
+    let proto: proto::ErrorEvent = event.into();
+    assert_eq!(proto.r#type(), ErrorType::InvalidMode);
+    assert_eq!(proto.description, "invalid mode: not idle");
+    assert_eq!(proto.command, "ChangeMode");
+
+    let proto: proto::ErrorEvent = ErrorEvent::from(Error::BadCommand("empty".into())).into();
+    assert_eq!(proto.r#type(), ErrorType::BadCommand);
+    assert_eq!(proto.command, "");
+}
+
+#[test]
+fn test_error_types() {
+    let cases = [
+        (Error::BadCommand(String::new()), ErrorType::BadCommand),
+        (Error::InvalidMode(String::new()), ErrorType::InvalidMode),
+        (Error::InvalidValue(String::new()), ErrorType::InvalidValue),
+        (
+            Error::UnknownController(String::new()),
+            ErrorType::UnknownController,
+        ),
+        (
+            Error::UnknownProperty(String::new()),
+            ErrorType::UnknownProperty,
+        ),
+        (
+            Error::PermissionDenied(String::new()),
+            ErrorType::PermissionDenied,
+        ),
+        (Error::RateLimited(String::new()), ErrorType::RateLimited),
+        (Error::UnknownScene(String::new()), ErrorType::UnknownScene),
+        (Error::UnknownTake(String::new()), ErrorType::UnknownTake),
+        (Error::StorageFailed(String::new()), ErrorType::Unknown),
+        (Error::ChannelClosed("closed"), ErrorType::Unavailable),
+    ];
+    for (error, expected) in cases {
+        assert_eq!(error_type(&error), expected, "{error}");
+    }
+    assert_eq!(
+        error_code(&Error::ValueOutOfRange(String::new())),
+        "value_out_of_range"
+    );
+}
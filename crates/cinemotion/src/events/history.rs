@@ -0,0 +1,27 @@
+use super::EventBody;
+use cinemotion_proto as proto;
+
+/// Sent to every connection when the scene edits that can be undone or
+/// redone changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryChangedEvent {
+    /// The names of the edits that can be undone, the most recent last.
+    pub undo: Vec<String>,
+    /// The names of the edits that can be redone, the next one last.
+    pub redo: Vec<String>,
+}
+
+impl From<HistoryChangedEvent> for EventBody {
+    fn from(value: HistoryChangedEvent) -> Self {
+        Self::HistoryChanged(value)
+    }
+}
+
+impl From<HistoryChangedEvent> for proto::HistoryChangedEvent {
+    fn from(value: HistoryChangedEvent) -> Self {
+        Self {
+            undo: value.undo,
+            redo: value.redo,
+        }
+    }
+}
@@ -1,11 +1,17 @@
+mod ack;
 mod connection;
 mod error;
+mod history;
+mod scene;
 mod state;
 
 use crate::messages::Echo;
 
+pub use ack::*;
 pub use connection::*;
 pub use error::*;
+pub use history::*;
+pub use scene::*;
 pub use state::*;
 
 use cinemotion_proto as proto;
@@ -33,10 +39,27 @@ impl From<Event> for proto::Event {
                 EventBody::ConnectionOpened(opened) => {
                     proto::event::Payload::ConnectionOpened(opened.into())
                 }
+                EventBody::ConnectionClosed(closed) => {
+                    proto::event::Payload::ConnectionClosed(closed.into())
+                }
+                EventBody::ControllerJoined(joined) => {
+                    proto::event::Payload::ControllerJoined(joined.into())
+                }
+                EventBody::ControllerLeft(left) => {
+                    proto::event::Payload::ControllerLeft(left.into())
+                }
+                EventBody::Ping(ping) => proto::event::Payload::Ping(ping.into()),
+                EventBody::TimeSync(sync) => proto::event::Payload::TimeSync(sync.into()),
                 EventBody::StateChanged(change) => {
                     proto::event::Payload::StateChange(change.into())
                 }
                 EventBody::Error(err) => proto::event::Payload::Error(err.into()),
+                EventBody::Ack(ack) => proto::event::Payload::Ack(ack.into()),
+                EventBody::Nack(nack) => proto::event::Payload::Nack(nack.into()),
+                EventBody::HistoryChanged(history) => {
+                    proto::event::Payload::HistoryChanged(history.into())
+                }
+                EventBody::SceneList(list) => proto::event::Payload::SceneList(list.into()),
             }),
         }
     }
@@ -46,8 +69,17 @@ impl From<Event> for proto::Event {
 pub enum EventBody {
     Echo(Echo),
     ConnectionOpened(ConnectionOpenedEvent),
+    ConnectionClosed(ConnectionClosedEvent),
+    ControllerJoined(ControllerJoinedEvent),
+    ControllerLeft(ControllerLeftEvent),
+    Ping(PingEvent),
+    TimeSync(TimeSyncEvent),
     StateChanged(StateChangeEvent),
     Error(ErrorEvent),
+    Ack(AckEvent),
+    Nack(NackEvent),
+    HistoryChanged(HistoryChangedEvent),
+    SceneList(SceneListEvent),
 }
 
 impl From<StateChangeEvent> for EventBody {
@@ -65,7 +97,14 @@ impl From<ErrorEvent> for EventBody {
 pub type EventPipeTx = tokio::sync::broadcast::Sender<Event>;
 pub type EventPipeRx = tokio::sync::broadcast::Receiver<Event>;
 
+/// The number of events a slow connection may lag behind before it misses events.
+pub const DEFAULT_EVENT_PIPE_CAPACITY: usize = 1024;
+
 pub fn event_pipe() -> EventPipeTx {
-    let (sender, _) = tokio::sync::broadcast::channel(1024);
+    event_pipe_with_capacity(DEFAULT_EVENT_PIPE_CAPACITY)
+}
+
+pub fn event_pipe_with_capacity(capacity: usize) -> EventPipeTx {
+    let (sender, _) = tokio::sync::broadcast::channel(capacity);
     sender
 }
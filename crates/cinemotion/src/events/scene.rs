@@ -0,0 +1,22 @@
+use super::EventBody;
+use crate::Name;
+use cinemotion_proto as proto;
+
+/// The names of the saved scenes, sent to the connection that listed them and
+/// to everyone when a scene was saved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneListEvent(pub Vec<Name>);
+
+impl From<SceneListEvent> for EventBody {
+    fn from(value: SceneListEvent) -> Self {
+        Self::SceneList(value)
+    }
+}
+
+impl From<SceneListEvent> for proto::SceneListEvent {
+    fn from(value: SceneListEvent) -> Self {
+        Self {
+            scenes: value.0.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
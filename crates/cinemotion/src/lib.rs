@@ -1,14 +1,21 @@
+pub mod auth;
+pub mod clock;
 pub mod connection;
 pub mod data;
+pub mod discovery;
 pub mod engine;
 pub mod error;
 pub mod events;
 pub mod messages;
 pub mod name;
+pub mod presets;
+pub mod protocol;
 pub mod quic;
 pub mod scene;
 pub mod services;
 pub mod state;
+pub mod takes;
+pub mod tls;
 pub mod webrtc;
 
 // TODO: Add support for animation capture and saving
@@ -17,7 +24,10 @@ pub mod webrtc;
 // TODO: Add support for triggers
 // TODO: Document the API
 pub static VERSION: &str = "0.1.0";
+/// The version of the wire protocol, bumped on breaking changes.
+pub static PROTOCOL_VERSION: u32 = 1;
 pub static DEFAULT_WEB_PORT: u16 = 7272;
+pub static DEFAULT_QUIC_PORT: u16 = 4567;
 
 pub use error::{Error, Result};
 
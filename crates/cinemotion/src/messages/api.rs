@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+use super::{ClientCommand, Payload, SystemCommand};
+use crate::connection::{Context, Role};
+use crate::takes::{Take, TakeSummary};
+use crate::{Name, Result, State};
+
+/// Request a snapshot of the engine for a service outside of a connection,
+/// like the REST API.
+#[derive(Debug)]
+pub struct Query {
+    pub ack_pipe: tokio::sync::oneshot::Sender<Snapshot>,
+}
+
+impl From<Query> for Payload {
+    fn from(value: Query) -> Self {
+        Self::System(SystemCommand::Query(value))
+    }
+}
+
+/// Request a recorded take by name for a service outside of a connection.
+#[derive(Debug)]
+pub struct QueryTake {
+    pub name: Name,
+    pub ack_pipe: tokio::sync::oneshot::Sender<Result<Take>>,
+}
+
+impl From<QueryTake> for Payload {
+    fn from(value: QueryTake) -> Self {
+        Self::System(SystemCommand::QueryTake(value))
+    }
+}
+
+/// Apply a client command on behalf of a service outside of a connection,
+/// like the REST API, and report the result on the ack pipe.
+#[derive(Debug)]
+pub struct Submit {
+    pub command: ClientCommand,
+    /// The role the caller was granted when it authenticated.
+    pub role: Role,
+    pub ack_pipe: tokio::sync::oneshot::Sender<Result<()>>,
+}
+
+impl From<Submit> for Payload {
+    fn from(value: Submit) -> Self {
+        Self::System(SystemCommand::Submit(value))
+    }
+}
+
+/// The state of the engine and its connections at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub state: State,
+    pub connections: Vec<ConnectionInfo>,
+    /// The names of the saved scenes.
+    pub scenes: Vec<Name>,
+    /// The recorded takes, without their frames.
+    pub takes: Vec<TakeSummary>,
+}
+
+/// What is known about a connection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionInfo {
+    pub id: usize,
+    /// The controller the connection initialized, if any.
+    pub name: Option<Name>,
+    pub role: Role,
+    pub opened: bool,
+    /// The average round trip time in milliseconds.
+    pub latency_ms: Option<f64>,
+    /// The estimated offset of the device clock in microseconds.
+    pub clock_offset_us: Option<i64>,
+    /// The protocol version the client initialized with.
+    pub protocol_version: Option<u32>,
+    /// The capabilities both the client and the server support.
+    pub capabilities: Vec<String>,
+}
+
+impl From<&Context> for ConnectionInfo {
+    fn from(value: &Context) -> Self {
+        Self {
+            id: value.uid,
+            name: value.name.clone(),
+            role: value.role,
+            opened: value.opened,
+            latency_ms: value
+                .latency
+                .average
+                .map(|average| average.as_secs_f64() * 1000.0),
+            clock_offset_us: value.clock.offset(),
+            protocol_version: value.protocol_version,
+            capabilities: value.capabilities.clone(),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::{ClientCommand, Payload};
+use crate::{Error, Result};
+use cinemotion_proto as proto;
+
+/// Commands applied together, either all of them or none.
+#[derive(Debug)]
+pub struct Batch(pub Vec<ClientCommand>);
+
+impl From<Batch> for Payload {
+    fn from(value: Batch) -> Self {
+        Self::Client(ClientCommand::Batch(value))
+    }
+}
+
+impl TryFrom<proto::Batch> for Batch {
+    type Error = Error;
+
+    fn try_from(value: proto::Batch) -> Result<Self> {
+        value
+            .commands
+            .into_iter()
+            .map(|command| match command.payload {
+                Some(payload) => ClientCommand::from_protobuf(payload),
+                None => Err(Error::BadCommand(
+                    "failed to decode batched command, no payload found".to_string(),
+                )),
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
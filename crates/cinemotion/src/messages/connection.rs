@@ -1,6 +1,7 @@
 use super::{ClientCommand, Payload, SystemCommand};
-use crate::connection::ConnectionAgent;
+use crate::connection::{ConnectionAgent, Role};
 use crate::data::controllers;
+use crate::protocol::{self, Handshake};
 use crate::Result;
 use cinemotion_proto as proto;
 use std::fmt::Debug;
@@ -8,6 +9,8 @@ use std::fmt::Debug;
 #[derive(Debug)]
 pub struct Init {
     pub peer: controllers::Controller,
+    /// The protocol version and capabilities the client declared.
+    pub handshake: Handshake,
 }
 
 impl From<Init> for Payload {
@@ -18,8 +21,59 @@ impl From<Init> for Payload {
 
 impl From<proto::InitCommand> for Init {
     fn from(value: proto::InitCommand) -> Self {
+        let protocol_version = match value.protocol_version {
+            0 => protocol::LEGACY_PROTOCOL_VERSION,
+            version => version,
+        };
         Self {
             peer: value.controller.unwrap().into(),
+            handshake: Handshake {
+                protocol_version,
+                capabilities: value.capabilities,
+            },
+        }
+    }
+}
+
+/// Reply to a heartbeat ping with the timestamp of the ping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pong(pub i64);
+
+impl From<Pong> for Payload {
+    fn from(value: Pong) -> Self {
+        Self::Client(ClientCommand::Pong(value))
+    }
+}
+
+impl From<proto::Pong> for Pong {
+    fn from(value: proto::Pong) -> Self {
+        Self(value.timestamp)
+    }
+}
+
+/// The reply of a client to a clock synchronization request.
+///
+/// `t0` is the server time of the request, `t1` and `t2` are the client
+/// times it was received and answered, all in microseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSync {
+    pub t0: i64,
+    pub t1: i64,
+    pub t2: i64,
+}
+
+impl From<TimeSync> for Payload {
+    fn from(value: TimeSync) -> Self {
+        Self::Client(ClientCommand::TimeSync(value))
+    }
+}
+
+impl From<proto::TimeSync> for TimeSync {
+    fn from(value: proto::TimeSync) -> Self {
+        Self {
+            t0: value.t0,
+            t1: value.t1,
+            t2: value.t2,
         }
     }
 }
@@ -35,6 +89,8 @@ impl From<OpenConnection> for Payload {
 
 pub struct AddConnection {
     pub agent: Box<dyn ConnectionAgent + Send + Sync>,
+    /// The role the peer was granted when it authenticated.
+    pub role: Role,
     pub ack_pipe: tokio::sync::oneshot::Sender<Result<usize>>,
 }
 
@@ -42,6 +98,7 @@ impl Debug for AddConnection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AddConnection")
             .field("agent", &"Box<dyn ConnectionAgent + Send + Sync>")
+            .field("role", &self.role)
             .field("ack_pipe", &"tokio::sync::oneshot::Sender<Result<usize>>")
             .finish()
     }
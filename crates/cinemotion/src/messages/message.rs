@@ -4,6 +4,8 @@ use super::Payload;
 pub struct Message {
     pub source_id: usize,
     pub command: Payload,
+    /// The id the client asked the command to be acknowledged with.
+    pub request_id: Option<u64>,
 }
 
 impl Message {
@@ -11,6 +13,13 @@ impl Message {
         Self {
             source_id: conn_id,
             command: command.into(),
+            request_id: None,
         }
     }
+
+    /// Acknowledge the command to its source with the request id.
+    pub fn with_request_id(mut self, request_id: Option<u64>) -> Message {
+        self.request_id = request_id;
+        self
+    }
 }
@@ -1,14 +1,20 @@
+mod api;
+mod batch;
 mod connection;
 mod echo;
 mod message;
 mod motion;
 mod payload;
+mod property;
 mod scene;
 
+pub use api::*;
+pub use batch::*;
 pub use connection::*;
 pub use echo::*;
 pub use motion::*;
 pub use payload::*;
+pub use property::*;
 pub use scene::*;
 
 pub use message::Message;
@@ -49,6 +49,9 @@ pub enum SystemCommand {
     AddConnection(AddConnection),
     OpenConnection(OpenConnection),
     CloseConnection(CloseConnection),
+    Query(Query),
+    QueryTake(QueryTake),
+    Submit(Submit),
 }
 
 impl From<AddConnection> for SystemCommand {
@@ -63,38 +66,106 @@ impl From<AddConnection> for SystemCommand {
 pub enum ClientCommand {
     Echo(Echo),
     Init(Init),
+    Pong(Pong),
+    TimeSync(TimeSync),
     ChangeMode(ChangeMode),
     AddSceneObject(AddSceneObject),
     ClearScene(ClearScene),
     DeleteSceneObject(DeleteSceneObject),
     UpdateSceneObject(UpdateSceneObject),
+    RenameSceneObject(RenameSceneObject),
+    BindProperty(BindProperty),
+    UnbindProperty(UnbindProperty),
+    SetPropertyValue(SetPropertyValue),
+    AddProperty(AddProperty),
+    RemoveProperty(RemoveProperty),
+    AdjustProperty(AdjustProperty),
+    SaveScene(SaveScene),
+    LoadScene(LoadScene),
+    ListScenes(ListScenes),
+    Undo(Undo),
+    Redo(Redo),
     SampleMotion(SampleMotion),
+    Batch(Batch),
 }
 
 impl ClientCommand {
     /// Decode a command from a byte buffer.
     pub fn from_protobuf(payload: cinemotion_proto::command::Payload) -> Result<Self> {
-        Ok(payload.into())
+        use cinemotion_proto::command::Payload;
+        Ok(match payload {
+            Payload::Echo(p) => Self::Echo(p.into()),
+            Payload::Init(p) => Self::Init(p.into()),
+            Payload::Pong(p) => Self::Pong(p.into()),
+            Payload::TimeSync(p) => Self::TimeSync(p.into()),
+            Payload::AddSceneObject(p) => Self::AddSceneObject(p.into()),
+            Payload::ClearScene(p) => Self::ClearScene(p.into()),
+            Payload::DeleteSceneObject(p) => Self::DeleteSceneObject(p.into()),
+            Payload::UpdateSceneObject(p) => Self::UpdateSceneObject(p.into()),
+            Payload::RenameSceneObject(p) => Self::RenameSceneObject(p.into()),
+            Payload::BindProperty(p) => Self::BindProperty(p.into()),
+            Payload::UnbindProperty(p) => Self::UnbindProperty(p.into()),
+            Payload::SetPropertyValue(p) => Self::SetPropertyValue(p.try_into()?),
+            Payload::AddProperty(p) => Self::AddProperty(p.try_into()?),
+            Payload::RemoveProperty(p) => Self::RemoveProperty(p.into()),
+            Payload::AdjustProperty(p) => Self::AdjustProperty(p.try_into()?),
+            Payload::SaveScene(p) => Self::SaveScene(p.into()),
+            Payload::LoadScene(p) => Self::LoadScene(p.into()),
+            Payload::ListScenes(p) => Self::ListScenes(p.into()),
+            Payload::Undo(p) => Self::Undo(p.into()),
+            Payload::Redo(p) => Self::Redo(p.into()),
+            Payload::ChangeMode(mode) => Self::ChangeMode(mode.into()),
+            Payload::SendSample(sample) => Self::SampleMotion(sample.into()),
+            Payload::Batch(batch) => Self::Batch(batch.try_into()?),
+        })
+    }
+
+    /// The name of the command for logs and error messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Echo(_) => "Echo",
+            Self::Init(_) => "Init",
+            Self::Pong(_) => "Pong",
+            Self::TimeSync(_) => "TimeSync",
+            Self::ChangeMode(_) => "ChangeMode",
+            Self::AddSceneObject(_) => "AddSceneObject",
+            Self::ClearScene(_) => "ClearScene",
+            Self::DeleteSceneObject(_) => "DeleteSceneObject",
+            Self::UpdateSceneObject(_) => "UpdateSceneObject",
+            Self::RenameSceneObject(_) => "RenameSceneObject",
+            Self::BindProperty(_) => "BindProperty",
+            Self::UnbindProperty(_) => "UnbindProperty",
+            Self::SetPropertyValue(_) => "SetPropertyValue",
+            Self::AddProperty(_) => "AddProperty",
+            Self::RemoveProperty(_) => "RemoveProperty",
+            Self::AdjustProperty(_) => "AdjustProperty",
+            Self::SaveScene(_) => "SaveScene",
+            Self::LoadScene(_) => "LoadScene",
+            Self::ListScenes(_) => "ListScenes",
+            Self::Undo(_) => "Undo",
+            Self::Redo(_) => "Redo",
+            Self::SampleMotion(_) => "SampleMotion",
+            Self::Batch(_) => "Batch",
+        }
     }
-}
 
-impl From<cinemotion_proto::command::Payload> for ClientCommand {
-    fn from(value: cinemotion_proto::command::Payload) -> Self {
-        match value {
-            cinemotion_proto::command::Payload::Echo(p) => Self::Echo(p.into()),
-            cinemotion_proto::command::Payload::Init(p) => Self::Init(p.into()),
-            cinemotion_proto::command::Payload::AddSceneObject(p) => Self::AddSceneObject(p.into()),
-            cinemotion_proto::command::Payload::ClearScene(p) => Self::ClearScene(p.into()),
-            cinemotion_proto::command::Payload::DeleteSceneObject(p) => {
-                Self::DeleteSceneObject(p.into())
-            }
-            cinemotion_proto::command::Payload::UpdateSceneObject(p) => {
-                Self::UpdateSceneObject(p.into())
-            }
-            cinemotion_proto::command::Payload::ChangeMode(mode) => Self::ChangeMode(mode.into()),
-            cinemotion_proto::command::Payload::SendSample(sample) => {
-                Self::SampleMotion(sample.into())
-            }
+    /// Whether the command edits the scene and can be undone.
+    pub fn is_scene_edit(&self) -> bool {
+        match self {
+            Self::AddSceneObject(_)
+            | Self::ClearScene(_)
+            | Self::DeleteSceneObject(_)
+            | Self::UpdateSceneObject(_)
+            | Self::RenameSceneObject(_)
+            | Self::BindProperty(_)
+            | Self::UnbindProperty(_)
+            | Self::SetPropertyValue(_)
+            | Self::AddProperty(_)
+            | Self::RemoveProperty(_)
+            | Self::AdjustProperty(_)
+            | Self::LoadScene(_) => true,
+            Self::Batch(batch) => batch.0.iter().any(Self::is_scene_edit),
+            _ => false,
         }
     }
 }
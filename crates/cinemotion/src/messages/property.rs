@@ -0,0 +1,193 @@
+use super::{ClientCommand, Payload};
+use crate::data::{PropertyLink, PropertyReference, Value};
+use crate::{Error, Name, Result};
+use cinemotion_proto as proto;
+
+#[cfg(test)]
+#[path = "property_test.rs"]
+mod property_test;
+
+/// Bind a property of a scene object to a controller property.
+#[derive(Debug)]
+pub struct BindProperty {
+    pub object: Name,
+    pub property: Name,
+    pub binding: PropertyReference,
+}
+
+impl From<BindProperty> for Payload {
+    fn from(value: BindProperty) -> Self {
+        Self::Client(ClientCommand::BindProperty(value))
+    }
+}
+
+impl From<proto::BindProperty> for BindProperty {
+    fn from(value: proto::BindProperty) -> Self {
+        Self {
+            object: value.object.into(),
+            property: value.property.into(),
+            binding: PropertyReference {
+                namespace: value.namespace.into(),
+                property: value.controller_property.into(),
+            },
+        }
+    }
+}
+
+/// Detach a property of a scene object from its controller, keeping its value.
+#[derive(Debug)]
+pub struct UnbindProperty {
+    pub object: Name,
+    pub property: Name,
+}
+
+impl From<UnbindProperty> for Payload {
+    fn from(value: UnbindProperty) -> Self {
+        Self::Client(ClientCommand::UnbindProperty(value))
+    }
+}
+
+impl From<proto::UnbindProperty> for UnbindProperty {
+    fn from(value: proto::UnbindProperty) -> Self {
+        Self {
+            object: value.object.into(),
+            property: value.property.into(),
+        }
+    }
+}
+
+/// Set the value of an unbound property of a scene object.
+#[derive(Debug)]
+pub struct SetPropertyValue {
+    pub object: Name,
+    pub property: Name,
+    pub value: Value,
+}
+
+impl From<SetPropertyValue> for Payload {
+    fn from(value: SetPropertyValue) -> Self {
+        Self::Client(ClientCommand::SetPropertyValue(value))
+    }
+}
+
+impl TryFrom<proto::SetPropertyValue> for SetPropertyValue {
+    type Error = Error;
+
+    fn try_from(value: proto::SetPropertyValue) -> Result<Self> {
+        let Some(property_value) = value.value else {
+            return Err(Error::BadCommand(
+                "failed to decode SetPropertyValue, no value found".to_string(),
+            ));
+        };
+        Ok(Self {
+            object: value.object.into(),
+            property: value.property.into(),
+            value: property_value.into(),
+        })
+    }
+}
+
+/// Add a property to a scene object.
+#[derive(Debug)]
+pub struct AddProperty {
+    pub object: Name,
+    pub property: Name,
+    pub link: PropertyLink,
+}
+
+impl From<AddProperty> for Payload {
+    fn from(value: AddProperty) -> Self {
+        Self::Client(ClientCommand::AddProperty(value))
+    }
+}
+
+impl TryFrom<proto::AddProperty> for AddProperty {
+    type Error = Error;
+
+    fn try_from(value: proto::AddProperty) -> Result<Self> {
+        let Some(link) = value.link else {
+            return Err(Error::BadCommand(
+                "failed to decode AddProperty, no link found".to_string(),
+            ));
+        };
+        Ok(Self {
+            object: value.object.into(),
+            property: value.property.into(),
+            link: link.into(),
+        })
+    }
+}
+
+/// Remove a property from a scene object.
+#[derive(Debug)]
+pub struct RemoveProperty {
+    pub object: Name,
+    pub property: Name,
+}
+
+impl From<RemoveProperty> for Payload {
+    fn from(value: RemoveProperty) -> Self {
+        Self::Client(ClientCommand::RemoveProperty(value))
+    }
+}
+
+impl From<proto::RemoveProperty> for RemoveProperty {
+    fn from(value: proto::RemoveProperty) -> Self {
+        Self {
+            object: value.object.into(),
+            property: value.property.into(),
+        }
+    }
+}
+
+/// Change the value or binding of a scene object property while live or recording.
+#[derive(Debug)]
+pub struct AdjustProperty {
+    pub object: Name,
+    pub property: Name,
+    pub adjustment: Adjustment,
+}
+
+/// How a property is adjusted.
+#[derive(Debug)]
+pub enum Adjustment {
+    /// Set the value of an unbound property.
+    SetValue(Value),
+    /// Bind the property to a controller property.
+    Bind(PropertyReference),
+    /// Detach the property from its controller, keeping its value.
+    Unbind,
+}
+
+impl From<AdjustProperty> for Payload {
+    fn from(value: AdjustProperty) -> Self {
+        Self::Client(ClientCommand::AdjustProperty(value))
+    }
+}
+
+impl TryFrom<proto::AdjustProperty> for AdjustProperty {
+    type Error = Error;
+
+    fn try_from(value: proto::AdjustProperty) -> Result<Self> {
+        let Some(adjustment) = value.adjustment else {
+            return Err(Error::BadCommand(
+                "failed to decode AdjustProperty, no adjustment found".to_string(),
+            ));
+        };
+        let adjustment = match adjustment {
+            proto::adjust_property::Adjustment::Value(value) => Adjustment::SetValue(value.into()),
+            proto::adjust_property::Adjustment::Bind(binding) => {
+                Adjustment::Bind(PropertyReference {
+                    namespace: binding.namespace.into(),
+                    property: binding.property.into(),
+                })
+            }
+            proto::adjust_property::Adjustment::Unbind(_) => Adjustment::Unbind,
+        };
+        Ok(Self {
+            object: value.object.into(),
+            property: value.property.into(),
+            adjustment,
+        })
+    }
+}
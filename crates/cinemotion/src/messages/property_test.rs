@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn test_decode_set_property_value_without_value() {
+    let command = proto::command::Payload::SetPropertyValue(proto::SetPropertyValue {
+        object: "camera".into(),
+        property: "focal".into(),
+        value: None,
+    });
+    assert!(matches!(
+        ClientCommand::from_protobuf(command),
+        Err(Error::BadCommand(_))
+    ));
+}
+
+#[test]
+fn test_decode_add_property_without_link() {
+    let command = proto::command::Payload::AddProperty(proto::AddProperty {
+        object: "camera".into(),
+        property: "focal".into(),
+        link: None,
+    });
+    assert!(matches!(
+        ClientCommand::from_protobuf(command),
+        Err(Error::BadCommand(_))
+    ));
+}
+
+#[test]
+fn test_decode_adjust_property_without_adjustment() {
+    let command = proto::command::Payload::AdjustProperty(proto::AdjustProperty {
+        object: "camera".into(),
+        property: "focal".into(),
+        adjustment: None,
+    });
+    assert!(matches!(
+        ClientCommand::from_protobuf(command),
+        Err(Error::BadCommand(_))
+    ));
+}
@@ -26,6 +26,108 @@ impl From<proto::ClearScene> for ClearScene {
     }
 }
 
+/// Give a scene object a new name.
+#[derive(Debug)]
+pub struct RenameSceneObject {
+    pub name: crate::Name,
+    pub new_name: crate::Name,
+}
+
+impl From<RenameSceneObject> for Payload {
+    fn from(value: RenameSceneObject) -> Self {
+        Self::Client(ClientCommand::RenameSceneObject(value))
+    }
+}
+
+impl From<proto::RenameSceneObject> for RenameSceneObject {
+    fn from(value: proto::RenameSceneObject) -> Self {
+        Self {
+            name: value.name.into(),
+            new_name: value.new_name.into(),
+        }
+    }
+}
+
+/// Save the current scene under a name.
+#[derive(Debug)]
+pub struct SaveScene(pub crate::Name);
+
+impl From<SaveScene> for Payload {
+    fn from(value: SaveScene) -> Self {
+        Self::Client(ClientCommand::SaveScene(value))
+    }
+}
+
+impl From<proto::SaveScene> for SaveScene {
+    fn from(value: proto::SaveScene) -> Self {
+        Self(value.name.into())
+    }
+}
+
+/// Replace the current scene with a saved one.
+#[derive(Debug)]
+pub struct LoadScene(pub crate::Name);
+
+impl From<LoadScene> for Payload {
+    fn from(value: LoadScene) -> Self {
+        Self::Client(ClientCommand::LoadScene(value))
+    }
+}
+
+impl From<proto::LoadScene> for LoadScene {
+    fn from(value: proto::LoadScene) -> Self {
+        Self(value.name.into())
+    }
+}
+
+/// Ask for the names of the saved scenes.
+#[derive(Debug)]
+pub struct ListScenes {}
+
+impl From<ListScenes> for Payload {
+    fn from(value: ListScenes) -> Self {
+        Self::Client(ClientCommand::ListScenes(value))
+    }
+}
+
+impl From<proto::ListScenes> for ListScenes {
+    fn from(_: proto::ListScenes) -> Self {
+        Self {}
+    }
+}
+
+/// Revert the last scene edit.
+#[derive(Debug)]
+pub struct Undo {}
+
+impl From<Undo> for Payload {
+    fn from(value: Undo) -> Self {
+        Self::Client(ClientCommand::Undo(value))
+    }
+}
+
+impl From<proto::Undo> for Undo {
+    fn from(_: proto::Undo) -> Self {
+        Self {}
+    }
+}
+
+/// Apply the last reverted scene edit again.
+#[derive(Debug)]
+pub struct Redo {}
+
+impl From<Redo> for Payload {
+    fn from(value: Redo) -> Self {
+        Self::Client(ClientCommand::Redo(value))
+    }
+}
+
+impl From<proto::Redo> for Redo {
+    fn from(_: proto::Redo) -> Self {
+        Self {}
+    }
+}
+
 #[derive(Debug)]
 pub struct DeleteSceneObject(pub crate::Name);
 
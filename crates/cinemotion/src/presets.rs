@@ -0,0 +1,190 @@
+//! Named scenes saved to switch between setups, like a wide shot rig or a crane.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Name, Result, Scene};
+
+#[cfg(test)]
+#[path = "presets_test.rs"]
+mod presets_test;
+
+/// The directory in the config directory the saved scenes are kept in.
+pub const SCENES_DIR: &str = "scenes";
+
+const MAX_NAME_LEN: usize = 64;
+
+/// Where saved scenes are kept.
+#[derive(Debug, Clone)]
+pub struct SceneStore {
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Memory(HashMap<Name, Scene>),
+    /// One `<name>.json` file per scene.
+    Directory(PathBuf),
+}
+
+impl SceneStore {
+    /// A store that forgets the scenes when the server stops.
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Backend::Memory(HashMap::new()),
+        }
+    }
+
+    /// A store that keeps each scene as a JSON file in the directory, which
+    /// is created when the first scene is saved.
+    pub fn in_directory(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            backend: Backend::Directory(dir.into()),
+        }
+    }
+
+    /// Save the scene under its name, replacing a saved scene of that name.
+    pub fn save(&mut self, scene: &Scene) -> Result<()> {
+        validate_name(&scene.name)?;
+        check_object_names(scene).map_err(Error::StorageFailed)?;
+        match &mut self.backend {
+            Backend::Memory(scenes) => {
+                scenes.insert(scene.name.clone(), scene.clone());
+                Ok(())
+            }
+            Backend::Directory(dir) => {
+                fs::create_dir_all(&*dir).map_err(|err| io_error(dir, err))?;
+                let contents = serde_json::to_vec_pretty(scene)
+                    .map_err(|err| Error::StorageFailed(err.to_string()))?;
+                // Write next to the scene and move it in place so a failed
+                // write never leaves a truncated scene behind.
+                let path = scene_path(dir, &scene.name);
+                let partial = path.with_extension("json.partial");
+                fs::write(&partial, contents).map_err(|err| io_error(&partial, err))?;
+                fs::rename(&partial, &path).map_err(|err| io_error(&path, err))
+            }
+        }
+    }
+
+    /// Load the scene saved under the name.
+    pub fn load(&self, name: &Name) -> Result<Scene> {
+        validate_name(name)?;
+        let unknown = || Error::UnknownScene(format!("no scene is saved as {name}"));
+        match &self.backend {
+            Backend::Memory(scenes) => scenes.get(name).cloned().ok_or_else(unknown),
+            Backend::Directory(dir) => {
+                let path = scene_path(dir, name);
+                let contents = match fs::read(&path) {
+                    Ok(contents) => contents,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err(unknown()),
+                    Err(err) => return Err(io_error(&path, err)),
+                };
+                let mut scene: Scene = serde_json::from_slice(&contents)
+                    .map_err(|err| Error::StorageFailed(format!("{}: {err}", path.display())))?;
+                check_object_names(&scene)
+                    .map_err(|err| Error::StorageFailed(format!("{}: {err}", path.display())))?;
+                // The file name wins over a name edited in the file.
+                scene.name = name.clone();
+                Ok(scene)
+            }
+        }
+    }
+
+    /// The names of the saved scenes, sorted.
+    pub fn list(&self) -> Result<Vec<Name>> {
+        let mut names: Vec<Name> = match &self.backend {
+            Backend::Memory(scenes) => scenes.keys().cloned().collect(),
+            Backend::Directory(dir) => {
+                let entries = match fs::read_dir(dir) {
+                    Ok(entries) => entries,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+                    Err(err) => return Err(io_error(dir, err)),
+                };
+                entries
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        if path.extension()? != "json" {
+                            return None;
+                        }
+                        let name = path.file_stem()?.to_str()?;
+                        validate_name(name).ok()?;
+                        Some(Name::from(name))
+                    })
+                    .collect()
+            }
+        };
+        names.sort_by(|a, b| str::cmp(a, b));
+        Ok(names)
+    }
+
+    /// Delete the scene saved under the name.
+    pub fn delete(&mut self, name: &Name) -> Result<()> {
+        validate_name(name)?;
+        let unknown = || Error::UnknownScene(format!("no scene is saved as {name}"));
+        match &mut self.backend {
+            Backend::Memory(scenes) => scenes.remove(name).map(|_| ()).ok_or_else(unknown),
+            Backend::Directory(dir) => {
+                let path = scene_path(dir, name);
+                match fs::remove_file(&path) {
+                    Ok(()) => Ok(()),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(unknown()),
+                    Err(err) => Err(io_error(&path, err)),
+                }
+            }
+        }
+    }
+}
+
+impl Default for SceneStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+/// Check that every object is keyed by its own name, which an edited file
+/// may break and which the lookups by name rely on.
+fn check_object_names(scene: &Scene) -> std::result::Result<(), String> {
+    match scene
+        .objects()
+        .iter()
+        .find(|(key, object)| *key != object.name())
+    {
+        Some((key, object)) => Err(format!(
+            "the object {} is stored under the name {key}",
+            object.name()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Check that the name can be used as a file name on every platform.
+///
+/// ```
+/// use cinemotion::presets::validate_name;
+///
+/// assert!(validate_name("wide-shot_2").is_ok());
+/// assert!(validate_name("../crane").is_err());
+/// ```
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    match valid {
+        true => Ok(()),
+        false => Err(Error::BadCommand(format!(
+            "invalid scene name {name:?}, use up to {MAX_NAME_LEN} letters, digits, '-', '_' or '.'"
+        ))),
+    }
+}
+
+fn scene_path(dir: &Path, name: &Name) -> PathBuf {
+    dir.join(format!("{name}.json"))
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::StorageFailed(format!("{}: {err}", path.display()))
+}
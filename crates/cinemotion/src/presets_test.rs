@@ -0,0 +1,108 @@
+use super::*;
+use crate::{name, SceneObject};
+
+fn scene(name: &str, objects: &[&str]) -> Scene {
+    let mut scene = Scene::default();
+    scene.name = name.into();
+    for object in objects {
+        scene.objects_mut().insert(
+            (*object).into(),
+            SceneObject::new((*object).into(), Default::default()),
+        );
+    }
+    scene
+}
+
+fn check_store(mut store: SceneStore) {
+    assert!(store.list().unwrap().is_empty());
+    assert!(matches!(
+        store.load(&name!("crane")),
+        Err(Error::UnknownScene(_))
+    ));
+
+    store.save(&scene("crane", &["jib"])).unwrap();
+    store.save(&scene("handheld", &["camera"])).unwrap();
+    assert_eq!(
+        store.list().unwrap(),
+        vec![name!("crane"), name!("handheld")]
+    );
+    assert_eq!(
+        store.load(&name!("crane")).unwrap(),
+        scene("crane", &["jib"])
+    );
+
+    // Saving again replaces the scene.
+    store.save(&scene("crane", &["jib", "light"])).unwrap();
+    assert_eq!(
+        store.load(&name!("crane")).unwrap(),
+        scene("crane", &["jib", "light"])
+    );
+
+    store.delete(&name!("crane")).unwrap();
+    assert_eq!(store.list().unwrap(), vec![name!("handheld")]);
+    assert!(matches!(
+        store.delete(&name!("crane")),
+        Err(Error::UnknownScene(_))
+    ));
+    assert!(matches!(
+        store.save(&scene("../escape", &[])),
+        Err(Error::BadCommand(_))
+    ));
+}
+
+#[test]
+fn test_memory_store() {
+    check_store(SceneStore::in_memory());
+}
+
+#[test]
+fn test_directory_store() {
+    // The store creates its directory, the temporary one is removed on drop.
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().join("scenes");
+    check_store(SceneStore::in_directory(&dir));
+
+    // Scenes survive the store and files that are no scenes are skipped.
+    fs::write(dir.join("notes.txt"), "").unwrap();
+    let store = SceneStore::in_directory(&dir);
+    assert_eq!(store.list().unwrap(), vec![name!("handheld")]);
+    assert_eq!(
+        store.load(&name!("handheld")).unwrap(),
+        scene("handheld", &["camera"])
+    );
+}
+
+#[test]
+fn test_mismatched_object_names_are_rejected() {
+    let mut mismatched = scene("handheld", &["camera"]);
+    let camera = mismatched.objects_mut().remove(&name!("camera")).unwrap();
+    mismatched.objects_mut().insert(name!("crane"), camera);
+    let mut store = SceneStore::in_memory();
+    assert!(matches!(
+        store.save(&mismatched),
+        Err(Error::StorageFailed(_))
+    ));
+
+    // A file edited by hand is checked when it is loaded.
+    let temp = tempfile::tempdir().unwrap();
+    let store = SceneStore::in_directory(temp.path());
+    fs::write(
+        temp.path().join("handheld.json"),
+        serde_json::to_vec(&mismatched).unwrap(),
+    )
+    .unwrap();
+    assert!(matches!(
+        store.load(&name!("handheld")),
+        Err(Error::StorageFailed(_))
+    ));
+}
+
+#[test]
+fn test_validate_name() {
+    for name in ["wide", "wide-shot_2", "crane.v2"] {
+        assert!(validate_name(name).is_ok(), "{name}");
+    }
+    for name in ["", ".hidden", "a/b", "a b", &"x".repeat(65)] {
+        assert!(validate_name(name).is_err(), "{name}");
+    }
+}
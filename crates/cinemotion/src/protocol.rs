@@ -0,0 +1,73 @@
+//! The protocol versions and capabilities negotiated when a client initializes.
+//!
+//! The server advertises what it supports when a connection opens and the
+//! client declares what it speaks in its init command. A client that speaks
+//! no version the server supports is rejected, capabilities only one side
+//! knows are ignored so either side may add new ones.
+
+use crate::data::ValueType;
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "protocol_test.rs"]
+mod protocol_test;
+
+/// The protocol versions the server speaks.
+pub const PROTOCOL_VERSIONS: &[u32] = &[1];
+
+/// The version assumed for clients that predate the handshake.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// The capability of a property value type, like `value:vec3`.
+pub fn value_capability(value_type: ValueType) -> String {
+    format!("value:{value_type}")
+}
+
+/// The capabilities the server supports.
+pub fn server_capabilities() -> Vec<String> {
+    [
+        ValueType::Float,
+        ValueType::Vec3,
+        ValueType::Vec4,
+        ValueType::Matrix44,
+    ]
+    .into_iter()
+    .map(value_capability)
+    .collect()
+}
+
+/// What a client declared about itself when it initialized.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl Default for Handshake {
+    fn default() -> Self {
+        Self {
+            protocol_version: crate::PROTOCOL_VERSION,
+            capabilities: Vec::new(),
+        }
+    }
+}
+
+impl Handshake {
+    /// Check that the server can talk to the client and return the
+    /// capabilities both of them support.
+    pub fn negotiate(&self) -> Result<Vec<String>> {
+        if !PROTOCOL_VERSIONS.contains(&self.protocol_version) {
+            return Err(Error::IncompatibleClient(format!(
+                "protocol version {} is not supported, the server speaks {:?}",
+                self.protocol_version, PROTOCOL_VERSIONS
+            )));
+        }
+        let supported = server_capabilities();
+        Ok(self
+            .capabilities
+            .iter()
+            .filter(|capability| supported.contains(capability))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,52 @@
+use super::*;
+
+#[test]
+fn test_server_capabilities() {
+    let capabilities = server_capabilities();
+    assert!(capabilities.contains(&"value:vec3".to_string()));
+    assert!(capabilities.contains(&"value:matrix44".to_string()));
+    assert!(!capabilities.contains(&"delta_state".to_string()));
+}
+
+#[test]
+fn test_negotiate() {
+    let handshake = Handshake {
+        protocol_version: 1,
+        capabilities: vec![
+            "value:vec3".into(),
+            "datagrams".into(),
+            "from-the-future".into(),
+        ],
+    };
+    assert_eq!(handshake.negotiate().unwrap(), vec!["value:vec3"]);
+    assert_eq!(
+        Handshake::default().negotiate().unwrap(),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn test_negotiate_rejects_unsupported_versions() {
+    let handshake = Handshake {
+        protocol_version: 99,
+        capabilities: vec![],
+    };
+    assert!(matches!(
+        handshake.negotiate(),
+        Err(Error::IncompatibleClient(_))
+    ));
+}
+
+#[test]
+fn test_clients_without_a_version_are_legacy() {
+    let init = crate::messages::Init::from(cinemotion_proto::InitCommand {
+        controller: Some(cinemotion_proto::ControllerDef {
+            name: "phone".into(),
+            ..Default::default()
+        }),
+        protocol_version: 0,
+        capabilities: vec![],
+    });
+    assert_eq!(init.handshake.protocol_version, LEGACY_PROTOCOL_VERSION);
+    assert!(init.handshake.negotiate().is_ok());
+}
@@ -1,7 +1,8 @@
 use super::stream;
 use crate::{
     connection::{ConnectionAgent, SendHandlerFn},
-    Event, Result,
+    events::EventBody,
+    messages, Event, Result,
 };
 use arc_swap::ArcSwapOption;
 use futures::lock::Mutex;
@@ -11,6 +12,8 @@ use tokio::sync::oneshot::Receiver;
 pub struct QuicAgent {
     conn: quinn::Connection,
     send_handler: Arc<ArcSwapOption<Mutex<SendHandlerFn>>>,
+    /// The stream events are written to, opened once the agent initializes.
+    send_stream: Arc<Mutex<Option<quinn::SendStream>>>,
 }
 
 impl QuicAgent {
@@ -40,6 +43,7 @@ impl QuicAgent {
         Self {
             conn,
             send_handler: Arc::new(ArcSwapOption::default()),
+            send_stream: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -50,6 +54,18 @@ impl ConnectionAgent for QuicAgent {
     async fn initialize(&mut self, send_fn: SendHandlerFn) {
         self.send_handler.store(Some(Arc::new(Mutex::new(send_fn))));
 
+        // Let the engine know once the connection is lost.
+        let shared_conn = self.conn.clone();
+        let shared_send_fn = Arc::clone(&self.send_handler);
+        tokio::spawn(async move {
+            let reason = shared_conn.closed().await;
+            tracing::debug!("quic connection closed: {}", reason);
+            if let Some(handler) = &*shared_send_fn.load() {
+                let mut f = handler.lock().await;
+                let _ = f(messages::CloseConnection {}.into(), None);
+            }
+        });
+
         // Open a new bidirectional data stream for the message pipe.
         let (send_stream, mut recv_stream) = match self.conn.open_bi().await {
             Ok(stream) => stream,
@@ -58,34 +74,42 @@ impl ConnectionAgent for QuicAgent {
                 return;
             }
         };
+        *self.send_stream.lock().await = Some(send_stream);
 
         // Start recv loop for the message pipe.
         let shared_send_fn = Arc::clone(&self.send_handler);
-        let (shutdown_tx, mut shutdown_rx): (
-            tokio::sync::mpsc::Sender<()>,
-            tokio::sync::mpsc::Receiver<()>,
-        ) = tokio::sync::mpsc::channel(1);
+        let shared_send_stream = Arc::clone(&self.send_stream);
         tokio::spawn(async move {
             loop {
-                tokio::select! {
-                    serialization = stream::recv_command(&mut recv_stream) => {
-                        match serialization {
-                            Ok(command) => {
-                                if let Some(handler) = &*shared_send_fn.load() {
-                                    let mut f = handler.lock().await;
-                                    let _ = f(command);
-                                }
-                            },
-                            Err(err) => {
-                                tracing::error!("failed to read message: {}", err);
-                                continue;
-                            }
+                let (command, request_id) = match stream::recv_command(&mut recv_stream).await {
+                    Ok(received) => received,
+                    // The stream is closed or out of sync, the connection closing
+                    // lets the engine know.
+                    Err(stream::RecvError::FrameError(err)) => {
+                        tracing::info!("shutting down data recv stream: {}", err);
+                        break;
+                    }
+                    // Answer commands that could not be decoded like the engine
+                    // answers failed ones.
+                    Err(stream::RecvError::InvalidCommand { error, request_id }) => {
+                        tracing::error!("failed to decode command. err={error}");
+                        let event = Event {
+                            target: None,
+                            body: EventBody::rejection(error.into(), request_id),
+                        };
+                        if let Some(send_stream) = &mut *shared_send_stream.lock().await {
+                            let _ = stream::send_event(send_stream, event).await;
                         }
+                        continue;
                     }
-                    _ = shutdown_rx.recv() => {
-                        tracing::info!("shutting down data recv stream.");
-                        break;
+                    Err(err) => {
+                        tracing::error!("failed to read message: {}", err);
+                        continue;
                     }
+                };
+                if let Some(handler) = &*shared_send_fn.load() {
+                    let mut f = handler.lock().await;
+                    let _ = f(command, request_id);
                 }
             }
         });
@@ -93,11 +117,17 @@ impl ConnectionAgent for QuicAgent {
 
     #[doc = r" Receives an event from the server"]
     async fn receive(&mut self, event: Event) {
-        todo!()
+        let mut send_stream = self.send_stream.lock().await;
+        let Some(send_stream) = &mut *send_stream else {
+            return;
+        };
+        if let Err(err) = stream::send_event(send_stream, event).await {
+            tracing::error!("failed to send event. err={err}");
+        }
     }
 
     #[doc = r" Closes the connection agent and its connection to the peer."]
     async fn close(&mut self) {
-        todo!()
+        self.conn.close(0u32.into(), b"connection closed");
     }
 }
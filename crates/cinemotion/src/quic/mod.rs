@@ -2,10 +2,10 @@ mod agent;
 mod serialize;
 mod stream;
 
-// TODO: Implement Stream Deserialization.
-// - implement command deserialization process
+// Command and event frames carry the protobuf messages of the WebRTC data channel.
+// TODO: Decide whether the binary encoding in `serialize` should replace them.
 // - test value deserialization
 // - test init command deserialization
 // - make list of commands to deserialize
-// TODO: Implement Stream Serialization.
 pub use agent::*;
+pub use stream::recv_auth;
@@ -39,7 +39,7 @@ impl TryFrom<Frame> for messages::Payload {
     type Error = DeserializeError;
 
     fn try_from(frame: Frame) -> Result<Self, Self::Error> {
-        if frame.api_version != 1 {
+        if !crate::protocol::PROTOCOL_VERSIONS.contains(&u32::from(frame.api_version)) {
             return Err(DeserializeError::BadFrame);
         }
 
@@ -80,10 +80,8 @@ impl TryFrom<&mut QuicBytes> for messages::Init {
             properties.insert(property.name.clone(), property);
         }
         Ok(Self {
-            peer: data::Controller {
-                name: name.into(),
-                properties,
-            },
+            peer: data::Controller::new(name.into(), properties),
+            handshake: Default::default(),
         })
     }
 }
@@ -155,6 +155,9 @@ fn test_init_deserialization() {
         .into_iter()
         .map(|item| (item.name.clone(), item))
         .collect(),
+        device_id: None,
+        status: data::ControllerStatus::Connected,
+        sampled_at: None,
     };
 
     let parsed: messages::Init = (&mut QuicBytes::new(bytes.freeze()))
@@ -1,5 +1,5 @@
 use crate::messages;
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes};
 use std::ops::{Deref, DerefMut};
 use thiserror::Error;
 
@@ -11,11 +11,16 @@ mod stream_test;
 pub enum FrameError {
     #[error("invalid frame: {0}")]
     InvalidFrame(String),
+
+    #[error("unsupported protocol version: {0}")]
+    UnsupportedVersion(u8),
 }
 
 pub enum FrameType {
     Command,
     Error,
+    Auth,
+    Event,
     Invalid(u8),
 }
 
@@ -24,11 +29,25 @@ impl From<u8> for FrameType {
         match value {
             0 => Self::Command,
             1 => Self::Error,
+            2 => Self::Auth,
+            3 => Self::Event,
             _ => Self::Invalid(value),
         }
     }
 }
 
+impl From<FrameType> for u8 {
+    fn from(value: FrameType) -> Self {
+        match value {
+            FrameType::Command => 0,
+            FrameType::Error => 1,
+            FrameType::Auth => 2,
+            FrameType::Event => 3,
+            FrameType::Invalid(kind) => kind,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct QuicBytes(Bytes);
 
@@ -71,6 +90,27 @@ pub struct Frame {
 }
 
 impl Frame {
+    /// Frame a payload for the protocol version of the server.
+    pub fn new(kind: FrameType, payload: Bytes) -> Self {
+        Self {
+            api_version: crate::PROTOCOL_VERSION as u8,
+            kind: kind.into(),
+            payload_length: payload.len() as u32,
+            payload: payload.into(),
+        }
+    }
+
+    /// Encode the frame header followed by the payload.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = bytes::BytesMut::with_capacity(8 + self.payload.len());
+        buf.put_u8(self.api_version);
+        buf.put_u8(self.kind);
+        buf.put_u32(self.payload_length);
+        buf.put_u16(0); // padding
+        buf.put_slice(&self.payload);
+        buf.freeze()
+    }
+
     pub async fn from_stream<T>(stream: &mut T) -> Result<Self, FrameError>
     where
         T: tokio::io::AsyncReadExt + Send + Unpin,
@@ -100,6 +140,11 @@ impl Frame {
             .await
             .map_err(|err| FrameError::InvalidFrame(err.to_string()))?;
 
+        // The payload is read first so the stream stays aligned on frames.
+        if !crate::protocol::PROTOCOL_VERSIONS.contains(&u32::from(api_version)) {
+            return Err(FrameError::UnsupportedVersion(api_version));
+        }
+
         Ok(Frame {
             api_version,
             kind: frame_type,
@@ -121,11 +166,19 @@ pub enum RecvError {
     #[error("invalid frame type was received: {0}")]
     InvalidFrameType(u8),
 
+    #[error("command could not be decoded: {error}")]
+    InvalidCommand {
+        error: crate::Error,
+        /// The request id of the command, if at least the envelope decoded.
+        request_id: Option<u64>,
+    },
+
     #[error("some invoked functionality is not implemented yet")]
     NotImplemented,
 }
 
-pub async fn recv_command<T>(stream: &mut T) -> Result<messages::Payload, RecvError>
+/// Receive the next command and the request id it was sent with.
+pub async fn recv_command<T>(stream: &mut T) -> Result<(messages::Payload, Option<u64>), RecvError>
 where
     T: tokio::io::AsyncReadExt + Send + Sync + Unpin,
 {
@@ -134,12 +187,51 @@ where
         .map_err(|err| RecvError::FrameError(err.to_string()))?;
 
     match frame.frame_type() {
+        // Commands are encoded like on the WebRTC data channel.
         FrameType::Command => {
-            todo!();
-            // let command: messages::Command = frame.into();
-            // Ok(command)
+            let command = cinemotion_proto::Command::try_from(Bytes::clone(&frame.payload))
+                .map_err(|err| RecvError::InvalidCommand {
+                    error: crate::Error::BadCommand(format!("failed to decode command: {err}")),
+                    request_id: None,
+                })?;
+            let request_id = command.request_id;
+            messages::Payload::from_protobuf(command)
+                .map(|payload| (payload, request_id))
+                .map_err(|error| RecvError::InvalidCommand { error, request_id })
         }
         FrameType::Error => Err(RecvError::NotImplemented),
+        // Events only flow from the server to the client.
+        FrameType::Event => Err(RecvError::InvalidFrameType(frame.kind)),
+        // Credentials are only accepted while the connection is being established.
+        FrameType::Auth => Err(RecvError::InvalidFrameType(frame.kind)),
         FrameType::Invalid(kind_id) => Err(RecvError::InvalidFrameType(kind_id)),
     }
 }
+
+/// Write an event to the stream as an event frame.
+pub async fn send_event(stream: &mut quinn::SendStream, event: crate::Event) -> crate::Result<()> {
+    let event: cinemotion_proto::Event = event.into();
+    let payload: Bytes = event
+        .try_into()
+        .map_err(|err: cinemotion_proto::Error| crate::Error::ConnectionFailed(err.to_string()))?;
+    stream
+        .write_all(&Frame::new(FrameType::Event, payload).to_bytes())
+        .await
+        .map_err(|err| crate::Error::ConnectionFailed(err.to_string()))
+}
+
+/// Receive the join token or PIN a client sends when it connects.
+pub async fn recv_auth<T>(stream: &mut T) -> Result<String, RecvError>
+where
+    T: tokio::io::AsyncReadExt + Send + Unpin,
+{
+    let frame = Frame::from_stream(stream)
+        .await
+        .map_err(|err| RecvError::FrameError(err.to_string()))?;
+
+    match frame.frame_type() {
+        FrameType::Auth => String::from_utf8(frame.payload.to_vec())
+            .map_err(|err| RecvError::FrameError(err.to_string())),
+        _ => Err(RecvError::InvalidFrameType(frame.kind)),
+    }
+}
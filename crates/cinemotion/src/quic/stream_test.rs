@@ -21,3 +21,113 @@ async fn test_frame_from_stream() {
     assert_eq!(frame.payload_length, 4);
     assert_eq!(frame.payload, Bytes::from_static(&[1, 2, 3, 4]).into());
 }
+
+#[tokio::test]
+async fn test_frame_rejects_unsupported_versions() {
+    let mut bytes = BytesMut::new();
+    bytes.put_u8(99);
+    bytes.put_u8(2);
+    bytes.put_u32(6);
+    bytes.put_u16(0);
+    bytes.put_slice(b"123456");
+    let mut cursor = std::io::Cursor::new(bytes);
+
+    assert_eq!(
+        Frame::from_stream(&mut cursor).await.err(),
+        Some(FrameError::UnsupportedVersion(99))
+    );
+}
+
+#[tokio::test]
+async fn test_recv_auth() {
+    let mut bytes = BytesMut::new();
+    bytes.put_u8(1);
+    bytes.put_u8(2);
+    bytes.put_u32(6);
+    bytes.put_u16(0);
+    bytes.put_slice(b"123456");
+    let mut cursor = std::io::Cursor::new(bytes);
+
+    assert_eq!(recv_auth(&mut cursor).await.unwrap(), "123456");
+}
+
+#[tokio::test]
+async fn test_recv_auth_rejects_other_frames() {
+    let mut bytes = BytesMut::new();
+    bytes.put_u8(1);
+    bytes.put_u8(0);
+    bytes.put_u32(1);
+    bytes.put_u16(0);
+    bytes.put_u8(1);
+    let mut cursor = std::io::Cursor::new(bytes);
+
+    assert_eq!(
+        recv_auth(&mut cursor).await,
+        Err(RecvError::InvalidFrameType(0))
+    );
+}
+
+#[tokio::test]
+async fn test_frame_round_trip() {
+    let frame = Frame::new(FrameType::Event, Bytes::from_static(&[1, 2, 3]));
+    let mut cursor = std::io::Cursor::new(frame.to_bytes());
+
+    let decoded = Frame::from_stream(&mut cursor).await.unwrap();
+
+    assert_eq!(decoded.api_version, crate::PROTOCOL_VERSION as u8);
+    assert_eq!(decoded.kind, 3);
+    assert_eq!(decoded.payload_length, 3);
+    assert_eq!(decoded.payload, Bytes::from_static(&[1, 2, 3]).into());
+}
+
+#[tokio::test]
+async fn test_recv_command() {
+    let command = cinemotion_proto::Command {
+        payload: Some(cinemotion_proto::command::Payload::Pong(
+            cinemotion_proto::Pong { timestamp: 42 },
+        )),
+        request_id: Some(7),
+    };
+    let payload: Bytes = command.try_into().unwrap();
+    let frame = Frame::new(FrameType::Command, payload);
+    let mut cursor = std::io::Cursor::new(frame.to_bytes());
+
+    assert!(matches!(
+        recv_command(&mut cursor).await,
+        Ok((
+            messages::Payload::Client(messages::ClientCommand::Pong(messages::Pong(42))),
+            Some(7)
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_recv_command_rejects_bad_payloads() {
+    let frame = Frame::new(FrameType::Command, Bytes::from_static(&[0xff, 0xff]));
+    let mut cursor = std::io::Cursor::new(frame.to_bytes());
+
+    assert!(matches!(
+        recv_command(&mut cursor).await,
+        Err(RecvError::InvalidCommand {
+            request_id: None,
+            ..
+        })
+    ));
+
+    // A command without a payload still answers with its request id.
+    let command = cinemotion_proto::Command {
+        payload: None,
+        request_id: Some(3),
+    };
+    let payload: Bytes = command.try_into().unwrap();
+    let frame = Frame::new(FrameType::Command, payload);
+    let mut cursor = std::io::Cursor::new(frame.to_bytes());
+
+    assert!(matches!(
+        recv_command(&mut cursor).await,
+        Err(RecvError::InvalidCommand {
+            error: crate::Error::BadCommand(_),
+            request_id: Some(3),
+        })
+    ));
+}
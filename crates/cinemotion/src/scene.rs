@@ -1,13 +1,14 @@
 use cinemotion_proto as proto;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::{
     data::{PropertyLink, Value},
-    name, Name, Result,
+    name, Error, Name, Result,
 };
 
 /// Represents the currently loaded scene in the system.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Scene {
     /// The name of the scene.
     pub name: Name,
@@ -48,6 +49,27 @@ impl Scene {
         self.objects.get(name)
     }
 
+    /// Get a mutable reference to the object with the given name.
+    pub fn object_mut(&mut self, name: &Name) -> Option<&mut SceneObject> {
+        self.objects.get_mut(name)
+    }
+
+    /// Get a mutable reference to a property of an object.
+    ///
+    /// This will error with `InvalidSceneObject` if the object does not exist and
+    /// with `UnknownProperty` if it has no such property.
+    pub fn property_mut(&mut self, object: &Name, property: &Name) -> Result<&mut PropertyLink> {
+        let Some(scene_object) = self.objects.get_mut(object) else {
+            return Err(Error::InvalidSceneObject(format!(
+                "object {object} does not exist"
+            )));
+        };
+        scene_object
+            .properties
+            .get_mut(property)
+            .ok_or_else(|| Error::UnknownProperty(format!("{object} has no property {property}")))
+    }
+
     /// Add a new object to the scene.
     ///
     /// This will error with `InvalidSceneObject` if an object with the same name already exists.
@@ -59,7 +81,7 @@ impl Scene {
 }
 
 /// Represents an object in the scene graph that can be animated but the controllers.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SceneObject {
     /// A unique name for the scene object.
     name: Name,
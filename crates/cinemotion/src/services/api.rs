@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use crate::auth::Authenticator;
+use crate::connection::{Role, LOCAL_CONN_ID};
+use crate::data::{Mode, PropertyLink, PropertyReference, Value};
+use crate::events::{error_code, error_type};
+use crate::messages::{self, ClientCommand, MessagePipeTx, Snapshot};
+use crate::takes::Take;
+use crate::{Error, Name, Result, SceneObject};
+use cinemotion_proto::error_event::ErrorType;
+
+#[cfg(test)]
+#[path = "api_test.rs"]
+mod api_test;
+
+/// The largest request body the API accepts.
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+/// The media type of error responses.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// The body of the mode endpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModeBody {
+    pub mode: Mode,
+}
+
+/// The body of the endpoints that take a name, renaming objects and saving scenes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameBody {
+    pub name: Name,
+}
+
+/// The body of the adjust endpoint, like `{"value": {"float": 50.0}}`,
+/// `{"bind": {"namespace": "phone", "property": "orientation"}}` or `"unbind"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentBody {
+    Value(Value),
+    Bind(PropertyReference),
+    Unbind,
+}
+
+impl From<AdjustmentBody> for messages::Adjustment {
+    fn from(value: AdjustmentBody) -> Self {
+        match value {
+            AdjustmentBody::Value(value) => Self::SetValue(value),
+            AdjustmentBody::Bind(binding) => Self::Bind(binding),
+            AdjustmentBody::Unbind => Self::Unbind,
+        }
+    }
+}
+
+/// The body of an error response, an RFC 7807 problem details object.
+///
+/// Engine errors have a `urn:cinemotion:error:<code>` type, where the code is
+/// the snake case name of the error type of error events, like `invalid_mode`.
+/// Requests the API cannot route have the `about:blank` type and no code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl Problem {
+    /// A problem described by its status alone.
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            code: None,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn into_response(self) -> warp::reply::Response {
+        let status = self.status();
+        let reply = warp::reply::with_status(warp::reply::json(&self), status);
+        warp::reply::with_header(reply, "content-type", PROBLEM_CONTENT_TYPE).into_response()
+    }
+}
+
+impl From<&Error> for Problem {
+    fn from(err: &Error) -> Self {
+        let code = error_code(err);
+        let mut title = code.replace('_', " ");
+        if let Some(first) = title.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        Self {
+            problem_type: format!("urn:cinemotion:error:{code}"),
+            title,
+            status: status_code(err).as_u16(),
+            detail: err.to_string(),
+            code: Some(code),
+        }
+    }
+}
+
+/// The REST API for reading and editing the engine state over plain HTTP.
+///
+/// Requests are routed into the engine over the message pipe and answered
+/// from its ack pipe. Callers authenticate with an `Authorization: Bearer`
+/// header carrying a join token or PIN and are limited by its role.
+#[derive(Clone)]
+pub struct RestApi {
+    sender: MessagePipeTx,
+    auth: Arc<Authenticator>,
+}
+
+impl RestApi {
+    pub fn new(sender: MessagePipeTx, auth: Arc<Authenticator>) -> Self {
+        Self { sender, auth }
+    }
+
+    /// All the routes of the API.
+    pub fn routes(&self) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
+        let get_state = warp::get()
+            .and(warp::path!("state"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| snapshot.state))
+            });
+        let list_objects = warp::get()
+            .and(warp::path!("scene" / "objects"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| {
+                    let mut objects: Vec<SceneObject> =
+                        snapshot.state.scene.objects().values().cloned().collect();
+                    objects.sort_by(|a, b| str::cmp(a.name(), b.name()));
+                    objects
+                }))
+            });
+        let get_object = warp::get()
+            .and(warp::path!("scene" / "objects" / String))
+            .and(self.with_role())
+            .and_then(|name: String, api: RestApi, _| async move {
+                let snapshot = match api.query().await {
+                    Ok(snapshot) => snapshot,
+                    Err(err) => return reply::<()>(Err(err)),
+                };
+                match snapshot.state.scene.object(&name.as_str().into()) {
+                    Some(object) => reply(Ok(object)),
+                    None => Ok(Problem::new(
+                        StatusCode::NOT_FOUND,
+                        format!("scene object {name} does not exist"),
+                    )
+                    .into_response()),
+                }
+            });
+        let add_object = warp::post()
+            .and(warp::path!("scene" / "objects"))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(|api: RestApi, role, object: SceneObject| async move {
+                let result = api
+                    .submit(
+                        role,
+                        ClientCommand::AddSceneObject(messages::AddSceneObject(object.clone())),
+                    )
+                    .await;
+                reply_with_status(result.map(|_| object), StatusCode::CREATED)
+            });
+        let update_object = warp::put()
+            .and(warp::path!("scene" / "objects" / String))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |name: String, api: RestApi, role, object: SceneObject| async move {
+                    if **object.name() != *name {
+                        return reply::<()>(Err(Error::BadCommand(format!(
+                            "the object name {} does not match the path {name}",
+                            object.name()
+                        ))));
+                    }
+                    let result = api
+                        .submit(
+                            role,
+                            ClientCommand::UpdateSceneObject(messages::UpdateSceneObject(
+                                object.clone(),
+                            )),
+                        )
+                        .await;
+                    reply(result.map(|_| object))
+                },
+            );
+        let delete_object = warp::delete()
+            .and(warp::path!("scene" / "objects" / String))
+            .and(self.with_role())
+            .and_then(|name: String, api: RestApi, role| async move {
+                let command =
+                    ClientCommand::DeleteSceneObject(messages::DeleteSceneObject(Name::from(name)));
+                no_content(api.submit(role, command).await)
+            });
+        let rename_object = warp::post()
+            .and(warp::path!("scene" / "objects" / String / "rename"))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |name: String, api: RestApi, role, body: NameBody| async move {
+                    let command = ClientCommand::RenameSceneObject(messages::RenameSceneObject {
+                        name: name.into(),
+                        new_name: body.name.clone(),
+                    });
+                    reply(api.submit(role, command).await.map(|_| body))
+                },
+            );
+        let add_property = warp::post()
+            .and(warp::path!("scene" / "objects" / String / "properties" / String))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |object: String, property: String, api: RestApi, role, link: PropertyLink| async move {
+                    let command = ClientCommand::AddProperty(messages::AddProperty {
+                        object: object.into(),
+                        property: property.into(),
+                        link: link.clone(),
+                    });
+                    let result = api.submit(role, command).await;
+                    reply_with_status(result.map(|_| link), StatusCode::CREATED)
+                },
+            );
+        let remove_property = warp::delete()
+            .and(warp::path!(
+                "scene" / "objects" / String / "properties" / String
+            ))
+            .and(self.with_role())
+            .and_then(
+                |object: String, property: String, api: RestApi, role| async move {
+                    let command = ClientCommand::RemoveProperty(messages::RemoveProperty {
+                        object: object.into(),
+                        property: property.into(),
+                    });
+                    no_content(api.submit(role, command).await)
+                },
+            );
+        let set_value = warp::put()
+            .and(warp::path!(
+                "scene" / "objects" / String / "properties" / String / "value"
+            ))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |object: String, property: String, api: RestApi, role, value: Value| async move {
+                    let command = ClientCommand::SetPropertyValue(messages::SetPropertyValue {
+                        object: object.into(),
+                        property: property.into(),
+                        value: value.clone(),
+                    });
+                    reply(api.submit(role, command).await.map(|_| value))
+                },
+            );
+        let bind_property = warp::put()
+            .and(warp::path!(
+                "scene" / "objects" / String / "properties" / String / "binding"
+            ))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |object: String,
+                 property: String,
+                 api: RestApi,
+                 role,
+                 binding: PropertyReference| async move {
+                    let command = ClientCommand::BindProperty(messages::BindProperty {
+                        object: object.into(),
+                        property: property.into(),
+                        binding: binding.clone(),
+                    });
+                    reply(api.submit(role, command).await.map(|_| binding))
+                },
+            );
+        let unbind_property = warp::delete()
+            .and(warp::path!(
+                "scene" / "objects" / String / "properties" / String / "binding"
+            ))
+            .and(self.with_role())
+            .and_then(
+                |object: String, property: String, api: RestApi, role| async move {
+                    let command = ClientCommand::UnbindProperty(messages::UnbindProperty {
+                        object: object.into(),
+                        property: property.into(),
+                    });
+                    no_content(api.submit(role, command).await)
+                },
+            );
+        let adjust_property = warp::post()
+            .and(warp::path!(
+                "scene" / "objects" / String / "properties" / String / "adjust"
+            ))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(
+                |object: String,
+                 property: String,
+                 api: RestApi,
+                 role,
+                 adjustment: AdjustmentBody| async move {
+                    let command = ClientCommand::AdjustProperty(messages::AdjustProperty {
+                        object: object.into(),
+                        property: property.into(),
+                        adjustment: adjustment.clone().into(),
+                    });
+                    reply(api.submit(role, command).await.map(|_| adjustment))
+                },
+            );
+        let undo = warp::post()
+            .and(warp::path!("scene" / "undo"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, role| async move {
+                no_content(
+                    api.submit(role, ClientCommand::Undo(messages::Undo {}))
+                        .await,
+                )
+            });
+        let redo = warp::post()
+            .and(warp::path!("scene" / "redo"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, role| async move {
+                no_content(
+                    api.submit(role, ClientCommand::Redo(messages::Redo {}))
+                        .await,
+                )
+            });
+        let list_scenes = warp::get()
+            .and(warp::path!("scenes"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| snapshot.scenes))
+            });
+        let save_scene = warp::post()
+            .and(warp::path!("scenes"))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(|api: RestApi, role, body: NameBody| async move {
+                let command = ClientCommand::SaveScene(messages::SaveScene(body.name.clone()));
+                let result = api.submit(role, command).await;
+                reply_with_status(result.map(|_| body), StatusCode::CREATED)
+            });
+        let load_scene = warp::post()
+            .and(warp::path!("scenes" / String / "load"))
+            .and(self.with_role())
+            .and_then(|name: String, api: RestApi, role| async move {
+                let command = ClientCommand::LoadScene(messages::LoadScene(Name::from(name)));
+                no_content(api.submit(role, command).await)
+            });
+        let list_controllers = warp::get()
+            .and(warp::path!("controllers"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| {
+                    let mut controllers: Vec<_> =
+                        snapshot.state.controllers.into_values().collect();
+                    controllers.sort_by(|a, b| str::cmp(&a.name, &b.name));
+                    controllers
+                }))
+            });
+        let list_connections = warp::get()
+            .and(warp::path!("connections"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| snapshot.connections))
+            });
+        let get_mode = warp::get()
+            .and(warp::path!("mode"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| ModeBody {
+                    mode: snapshot.state.mode,
+                }))
+            });
+        let put_mode = warp::put()
+            .and(warp::path!("mode"))
+            .and(self.with_role())
+            .and(json_body())
+            .and_then(|api: RestApi, role, body: ModeBody| async move {
+                let result = api
+                    .submit(
+                        role,
+                        ClientCommand::ChangeMode(messages::ChangeMode(body.mode)),
+                    )
+                    .await;
+                reply(result.map(|_| body))
+            });
+        let list_takes = warp::get()
+            .and(warp::path!("takes"))
+            .and(self.with_role())
+            .and_then(|api: RestApi, _| async move {
+                reply(api.query().await.map(|snapshot| snapshot.takes))
+            });
+        let get_take = warp::get()
+            .and(warp::path!("takes" / String))
+            .and(self.with_role())
+            .and_then(|name: String, api: RestApi, _| async move {
+                reply(api.query_take(Name::from(name)).await)
+            });
+
+        get_state
+            .or(list_objects)
+            .or(get_object)
+            .or(add_object)
+            .or(update_object)
+            .or(delete_object)
+            .or(rename_object)
+            .or(add_property)
+            .or(remove_property)
+            .or(set_value)
+            .or(bind_property)
+            .or(unbind_property)
+            .or(adjust_property)
+            .or(undo)
+            .or(redo)
+            .or(list_scenes)
+            .or(save_scene)
+            .or(load_scene)
+            .or(list_controllers)
+            .or(list_connections)
+            .or(get_mode)
+            .or(put_mode)
+            .or(list_takes)
+            .or(get_take)
+    }
+
+    /// Read a snapshot of the engine.
+    pub async fn query(&self) -> Result<Snapshot> {
+        let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
+        self.send(messages::Query { ack_pipe })?;
+        ack_pipe_rx
+            .await
+            .map_err(|_| Error::ChannelClosed("engine dropped the query"))
+    }
+
+    /// Read a recorded take with its frames.
+    pub async fn query_take(&self, name: Name) -> Result<Take> {
+        let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
+        self.send(messages::QueryTake { name, ack_pipe })?;
+        ack_pipe_rx
+            .await
+            .map_err(|_| Error::ChannelClosed("engine dropped the query"))?
+    }
+
+    /// Apply a command to the engine with the permissions of the role.
+    pub async fn submit(&self, role: Role, command: ClientCommand) -> Result<()> {
+        let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
+        self.send(messages::Submit {
+            command,
+            role,
+            ack_pipe,
+        })?;
+        ack_pipe_rx
+            .await
+            .map_err(|_| Error::ChannelClosed("engine dropped the command"))?
+    }
+
+    fn send(&self, command: impl Into<messages::Payload>) -> Result<()> {
+        self.sender
+            .send(messages::Message::with_command(LOCAL_CONN_ID, command))
+            .map_err(|_| Error::ChannelClosed("runtime message channel closed"))
+    }
+
+    /// Authenticate the caller and extract the API along with the caller role.
+    fn with_role(&self) -> impl Filter<Extract = (RestApi, Role), Error = warp::Rejection> + Clone {
+        let api = self.clone();
+        authorize(Arc::clone(&self.auth))
+            .map(move |role| (api.clone(), role))
+            .untuple_one()
+    }
+}
+
+/// Authenticate the caller and extract its role.
+///
+/// The credential is read from an `Authorization: Bearer` header, or from a
+/// `token` query parameter for clients like `EventSource` that cannot set headers.
+pub fn authorize(
+    auth: Arc<Authenticator>,
+) -> impl Filter<Extract = (Role,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::addr::remote())
+        .and_then(
+            move |header: Option<String>,
+                  query: HashMap<String, String>,
+                  remote: Option<SocketAddr>| {
+                let auth = Arc::clone(&auth);
+                async move {
+                    // Other schemes, like `Basic` from a proxy, are not our
+                    // credentials and must not count as failed attempts.
+                    let token = header
+                        .as_deref()
+                        .and_then(bearer_token)
+                        .or_else(|| query.get("token").map(String::as_str));
+                    auth.verify_peer(remote.map(|addr| addr.ip()), token)
+                        .map_err(|err| warp::reject::custom(Rejected(err)))
+                }
+            },
+        )
+}
+
+/// The token of a `Bearer` authorization header, the scheme is case insensitive.
+fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then_some(token.trim())
+}
+
+/// Turn the rejections of the API into problem responses.
+pub async fn recover(rejection: warp::Rejection) -> std::result::Result<impl Reply, Infallible> {
+    if let Some(Rejected(err)) = rejection.find::<Rejected>() {
+        return Ok(error_response(err));
+    }
+    if let Some(err) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
+        return Ok(error_response(&Error::BadCommand(err.to_string())));
+    }
+    let problem = if rejection.is_not_found() {
+        Problem::new(StatusCode::NOT_FOUND, "not found")
+    } else if rejection.find::<warp::reject::MethodNotAllowed>().is_some() {
+        Problem::new(StatusCode::METHOD_NOT_ALLOWED, "method not allowed")
+    } else if rejection.find::<warp::reject::PayloadTooLarge>().is_some() {
+        Problem::new(StatusCode::PAYLOAD_TOO_LARGE, "payload too large")
+    } else {
+        Problem::new(StatusCode::BAD_REQUEST, format!("{rejection:?}"))
+    };
+    Ok(problem.into_response())
+}
+
+/// The problem response of an engine error.
+pub fn error_response(err: &Error) -> warp::reply::Response {
+    Problem::from(err).into_response()
+}
+
+/// The HTTP status code of an engine error.
+pub fn status_code(err: &Error) -> StatusCode {
+    match error_type(err) {
+        ErrorType::BadCommand | ErrorType::IncompatibleClient => StatusCode::BAD_REQUEST,
+        ErrorType::Unauthorized => StatusCode::UNAUTHORIZED,
+        ErrorType::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorType::InvalidMode | ErrorType::DuplicateController => StatusCode::CONFLICT,
+        ErrorType::UnknownController
+        | ErrorType::UnknownProperty
+        | ErrorType::UnknownSession
+        | ErrorType::UnknownScene
+        | ErrorType::UnknownTake => StatusCode::NOT_FOUND,
+        ErrorType::InvalidSceneObject
+        | ErrorType::InvalidBinding
+        | ErrorType::InvalidValue
+        | ErrorType::ValueOutOfRange => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorType::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        ErrorType::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorType::SignalingFailed | ErrorType::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[derive(Debug)]
+struct Rejected(Error);
+
+impl warp::reject::Reject for Rejected {}
+
+fn json_body<T>() -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone
+where
+    T: serde::de::DeserializeOwned + Send,
+{
+    warp::body::content_length_limit(MAX_BODY_SIZE).and(warp::body::json())
+}
+
+fn reply<T: Serialize>(
+    result: Result<T>,
+) -> std::result::Result<warp::reply::Response, Infallible> {
+    reply_with_status(result, StatusCode::OK)
+}
+
+fn reply_with_status<T: Serialize>(
+    result: Result<T>,
+    status: StatusCode,
+) -> std::result::Result<warp::reply::Response, Infallible> {
+    Ok(match result {
+        Ok(value) => warp::reply::with_status(warp::reply::json(&value), status).into_response(),
+        Err(err) => error_response(&err),
+    })
+}
+
+fn no_content(result: Result<()>) -> std::result::Result<warp::reply::Response, Infallible> {
+    Ok(match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => error_response(&err),
+    })
+}
@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use warp::http::StatusCode;
+
+use super::*;
+use crate::data::{PropertyLink, Value};
+use crate::engine::{network::NetworkComponentImpl, Engine};
+use crate::takes::TakeSummary;
+use crate::{messages::message_pipe, name, State};
+
+fn spawn_api() -> RestApi {
+    let (sender, mut receiver) = message_pipe();
+    let mut engine = Engine::builder()
+        .with_network_component(NetworkComponentImpl::boxed(sender.clone()))
+        .build()
+        .unwrap();
+    tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            let _ = engine.apply(message).await;
+        }
+    });
+    let auth = Authenticator::with_tokens(vec![
+        ("op".to_string(), Role::Operator),
+        ("view".to_string(), Role::Viewer),
+    ]);
+    RestApi::new(sender, Arc::new(auth))
+}
+
+fn object(name: &str) -> SceneObject {
+    SceneObject::new(
+        name.into(),
+        HashMap::from([(name!("x"), PropertyLink::unbound(Value::Float(1.0)))]),
+    )
+}
+
+#[tokio::test]
+async fn test_requires_credentials() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+
+    let response = warp::test::request().path("/state").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = warp::test::request()
+        .path("/state")
+        .header("authorization", "Bearer view")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let state: State = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(state, State::default());
+}
+
+#[tokio::test]
+async fn test_only_bearer_credentials_are_verified() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let remote: SocketAddr = "192.0.2.7:5000".parse().unwrap();
+
+    // Other schemes are no attempt, so they never trip the rate limit.
+    for _ in 0..10 {
+        let response = warp::test::request()
+            .path("/state")
+            .remote_addr(remote)
+            .header("authorization", "Basic b3A6b3A=")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = warp::test::request()
+        .path("/state?token=view")
+        .remote_addr(remote)
+        .header("authorization", "Basic b3A6b3A=")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = warp::test::request()
+        .path("/state")
+        .remote_addr(remote)
+        .header("authorization", "bearer op")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_scene_objects() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/scene/objects")
+        .header("authorization", "Bearer op")
+        .json(&object("camera"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = warp::test::request()
+        .path("/scene/objects/camera")
+        .header("authorization", "Bearer view")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let found: SceneObject = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(found, object("camera"));
+
+    let response = warp::test::request()
+        .method("DELETE")
+        .path("/scene/objects/camera")
+        .header("authorization", "Bearer view")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response.headers()["content-type"], PROBLEM_CONTENT_TYPE);
+    let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(
+        problem.problem_type,
+        "urn:cinemotion:error:permission_denied"
+    );
+    assert_eq!(problem.title, "Permission denied");
+    assert_eq!(problem.status, 403);
+    assert_eq!(problem.code.as_deref(), Some("permission_denied"));
+    assert!(
+        problem.detail.contains("DeleteSceneObject"),
+        "{}",
+        problem.detail
+    );
+
+    let response = warp::test::request()
+        .method("DELETE")
+        .path("/scene/objects/camera")
+        .header("authorization", "Bearer op")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = warp::test::request()
+        .path("/scene/objects/camera")
+        .header("authorization", "Bearer op")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_undo_and_redo() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let post = |path: &'static str| {
+        warp::test::request()
+            .method("POST")
+            .path(path)
+            .header("authorization", "Bearer op")
+    };
+
+    let response = post("/scene/undo").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post("/scene/objects")
+        .json(&object("camera"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = post("/scene/undo").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let state = api.query().await.unwrap().state;
+    assert!(state.scene.object(&name!("camera")).is_none());
+
+    let response = post("/scene/redo").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let state = api.query().await.unwrap().state;
+    assert!(state.scene.object(&name!("camera")).is_some());
+}
+
+#[tokio::test]
+async fn test_mode() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+
+    let response = warp::test::request()
+        .method("PUT")
+        .path("/mode")
+        .header("authorization", "Bearer op")
+        .json(&ModeBody { mode: Mode::Live })
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = warp::test::request()
+        .path("/mode")
+        .header("authorization", "Bearer op")
+        .reply(&routes)
+        .await;
+    let body: ModeBody = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body.mode, Mode::Live);
+}
+
+#[tokio::test]
+async fn test_takes() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let request = |method: &str, path: &str| {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", "Bearer op")
+    };
+
+    for mode in [Mode::Recording, Mode::Idle] {
+        let response = request("PUT", "/mode")
+            .json(&ModeBody { mode })
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = request("GET", "/takes").reply(&routes).await;
+    let takes: Vec<TakeSummary> = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(takes.len(), 1);
+    assert_eq!(takes[0].name, name!("take-1"));
+
+    let response = request("GET", "/takes/take-1").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let take: Take = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(take.name, name!("take-1"));
+
+    let response = request("GET", "/takes/take-2").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_bad_bodies_are_problems() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+
+    let response = warp::test::request()
+        .method("PUT")
+        .path("/mode")
+        .header("authorization", "Bearer op")
+        .body(r#"{"mode": "dancing"}"#)
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(problem.code.as_deref(), Some("bad_command"));
+
+    let response = warp::test::request()
+        .method("PATCH")
+        .path("/state")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(problem.problem_type, "about:blank");
+    assert_eq!(problem.title, "Method Not Allowed");
+    assert_eq!(problem.code, None);
+}
+
+#[test]
+fn test_status_codes() {
+    let cases = [
+        (Error::BadCommand(String::new()), StatusCode::BAD_REQUEST),
+        (Error::InvalidMode(String::new()), StatusCode::CONFLICT),
+        (
+            Error::InvalidValue(String::new()),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (Error::UnknownProperty(String::new()), StatusCode::NOT_FOUND),
+        (Error::UnknownSession(String::new()), StatusCode::NOT_FOUND),
+        (Error::UnknownScene(String::new()), StatusCode::NOT_FOUND),
+        (Error::UnknownTake(String::new()), StatusCode::NOT_FOUND),
+        (Error::Unauthorized(String::new()), StatusCode::UNAUTHORIZED),
+        (
+            Error::RateLimited(String::new()),
+            StatusCode::TOO_MANY_REQUESTS,
+        ),
+        (
+            Error::EngineFailed(String::new()),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ),
+    ];
+    for (error, expected) in cases {
+        assert_eq!(status_code(&error), expected, "{error}");
+    }
+}
+
+#[tokio::test]
+async fn test_properties() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let request = |method: &str, path: &str| {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", "Bearer op")
+    };
+
+    let response = request("POST", "/scene/objects")
+        .json(&object("camera"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = request("POST", "/scene/objects/camera/properties/focal")
+        .json(&PropertyLink::unbound(Value::Float(35.0)))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = request("PUT", "/scene/objects/camera/properties/focal/value")
+        .json(&Value::Float(50.0))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request("PUT", "/scene/objects/camera/properties/focal/value")
+        .json(&Value::vec3())
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = request("DELETE", "/scene/objects/camera/properties/x")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = request("POST", "/scene/objects/camera/rename")
+        .json(&NameBody {
+            name: name!("crane"),
+        })
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let state = api.query().await.unwrap().state;
+    let crane = state.scene.object(&name!("crane")).unwrap();
+    assert_eq!(
+        crane.properties(),
+        &HashMap::from([(name!("focal"), PropertyLink::unbound(Value::Float(50.0)))])
+    );
+}
+
+#[tokio::test]
+async fn test_adjust_property() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let request = |method: &str, path: &str| {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", "Bearer op")
+    };
+
+    let response = request("POST", "/scene/objects")
+        .json(&object("camera"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = request("POST", "/scene/objects/camera/properties/focal")
+        .json(&PropertyLink::unbound(Value::Float(35.0)))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = request("PUT", "/mode")
+        .json(&ModeBody { mode: Mode::Live })
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The property routes stay idle-only, live edits go through adjust.
+    let response = request("PUT", "/scene/objects/camera/properties/focal/value")
+        .json(&Value::Float(50.0))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = request("POST", "/scene/objects/camera/properties/focal/adjust")
+        .body(r#"{"value": {"float": 50.0}}"#)
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: AdjustmentBody = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body, AdjustmentBody::Value(Value::Float(50.0)));
+
+    let response = request("POST", "/scene/objects/camera/properties/focal/adjust")
+        .body(r#""unbind""#)
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let state = api.query().await.unwrap().state;
+    let camera = state.scene.object(&name!("camera")).unwrap();
+    assert_eq!(
+        camera.properties().get(&name!("focal")),
+        Some(&PropertyLink::unbound(Value::Float(50.0)))
+    );
+}
+
+#[tokio::test]
+async fn test_scenes() {
+    let api = spawn_api();
+    let routes = api.routes().recover(recover);
+    let request = |method: &str, path: &str| {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", "Bearer op")
+    };
+
+    let response = request("POST", "/scene/objects")
+        .json(&object("camera"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = request("POST", "/scenes")
+        .json(&NameBody {
+            name: name!("handheld"),
+        })
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = request("GET", "/scenes").reply(&routes).await;
+    let scenes: Vec<Name> = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(scenes, vec![name!("handheld")]);
+
+    let response = request("DELETE", "/scene/objects/camera")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = request("POST", "/scenes/handheld/load")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let state = api.query().await.unwrap().state;
+    assert_eq!(state.scene.name, name!("handheld"));
+    assert!(state.scene.object(&name!("camera")).is_some());
+
+    let response = request("POST", "/scenes/crane/load").reply(&routes).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,23 @@
+use warp::{Filter, Reply};
+
+#[cfg(test)]
+#[path = "browser_controller_test.rs"]
+mod browser_controller_test;
+
+/// The page that joins a session as a controller from a phone browser.
+pub const INDEX: &str = include_str!("browser_controller/index.html");
+
+const SCRIPT: &str = include_str!("browser_controller/controller.js");
+
+/// Serve the browser controller on `/controller`.
+///
+/// The page signals a WebRTC session over `POST /sessions` like any other
+/// peer and streams the device orientation and motion sensors as samples.
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let index = warp::path!("controller").map(|| warp::reply::html(INDEX).into_response());
+    let script = warp::path!("controller" / "controller.js").map(|| {
+        warp::reply::with_header(SCRIPT, "content-type", "text/javascript; charset=utf-8")
+            .into_response()
+    });
+    warp::get().and(index.or(script).unify())
+}
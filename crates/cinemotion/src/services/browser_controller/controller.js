@@ -0,0 +1,406 @@
+// Turns a phone browser into a controller over WebRTC, streaming the
+// DeviceOrientation and DeviceMotion sensors as samples.
+"use strict";
+
+const SAMPLE_INTERVAL_MS = 33;
+const PROTOCOL_VERSION = 1;
+const CAPABILITIES = ["value:vec3"];
+const DEVICE_ID_KEY = "cinemotion.device_id";
+const NAME_KEY = "cinemotion.controller_name";
+
+// The properties the controller declares, every value is a vec3.
+const PROPERTIES = {
+  orientation: { unit: "deg", semantic: "rotation" },
+  acceleration: { unit: "m/s2", semantic: "acceleration" },
+};
+
+/* Protocol buffer encoding of the subset of `cinemotion.proto` used here. */
+
+class Writer {
+  constructor() {
+    this.bytes = [];
+  }
+
+  varint(n) {
+    // Numbers stay exact below 2^53, which covers microsecond timestamps.
+    while (n >= 128) {
+      this.bytes.push((n % 128) | 128);
+      n = Math.floor(n / 128);
+    }
+    this.bytes.push(n);
+    return this;
+  }
+
+  tag(field, wireType) {
+    return this.varint(field * 8 + wireType);
+  }
+
+  int64(field, n) {
+    return n ? this.tag(field, 0).varint(Math.max(0, Math.round(n))) : this;
+  }
+
+  double(field, n) {
+    const view = new DataView(new ArrayBuffer(8));
+    view.setFloat64(0, n, true);
+    this.tag(field, 1);
+    this.bytes.push(...new Uint8Array(view.buffer));
+    return this;
+  }
+
+  bytesField(field, bytes) {
+    this.tag(field, 2).varint(bytes.length);
+    this.bytes.push(...bytes);
+    return this;
+  }
+
+  string(field, s) {
+    return s ? this.bytesField(field, new TextEncoder().encode(s)) : this;
+  }
+
+  message(field, build) {
+    const writer = new Writer();
+    build(writer);
+    return this.bytesField(field, writer.bytes);
+  }
+
+  finish() {
+    return new Uint8Array(this.bytes);
+  }
+}
+
+function vec3Value(w, [x, y, z]) {
+  // PropertyValue.vec3_value
+  w.message(2, (v) => v.double(1, x).double(2, y).double(3, z));
+}
+
+function initCommand(name, deviceId) {
+  return new Writer()
+    .message(2, (init) => {
+      init.message(1, (def) => {
+        def.string(2, name);
+        for (const [property, schema] of Object.entries(PROPERTIES)) {
+          def.message(3, (entry) =>
+            entry.string(1, property).message(2, (value) => vec3Value(value, [0, 0, 0])),
+          );
+          def.message(4, (entry) =>
+            entry
+              .string(1, property)
+              .message(2, (s) => s.string(2, schema.unit).string(3, schema.semantic)),
+          );
+        }
+        def.string(5, deviceId);
+      });
+      init.int64(2, PROTOCOL_VERSION);
+      CAPABILITIES.forEach((capability) => init.string(3, capability));
+    })
+    .finish();
+}
+
+function sampleCommand(values, timestamp) {
+  return new Writer()
+    .message(50, (send) =>
+      send.message(1, (sample) => {
+        for (const [property, value] of Object.entries(values)) {
+          sample.message(1, (entry) =>
+            entry.string(1, property).message(2, (v) => vec3Value(v, value)),
+          );
+        }
+        sample.int64(2, timestamp);
+      }),
+    )
+    .finish();
+}
+
+function pongCommand(timestamp) {
+  return new Writer().message(3, (pong) => pong.int64(1, timestamp)).finish();
+}
+
+function timeSyncCommand(t0, t1, t2) {
+  return new Writer()
+    .message(4, (sync) => sync.int64(1, t0).int64(2, t1).int64(3, t2))
+    .finish();
+}
+
+/** Decode the fields of a message into a map of field number to values. */
+function decode(bytes) {
+  const fields = new Map();
+  let pos = 0;
+  const varint = () => {
+    let n = 0;
+    let scale = 1;
+    let byte;
+    do {
+      byte = bytes[pos++];
+      n += (byte & 127) * scale;
+      scale *= 128;
+    } while (byte & 128);
+    return n;
+  };
+  while (pos < bytes.length) {
+    const key = varint();
+    const field = Math.floor(key / 8);
+    let value;
+    switch (key % 8) {
+      case 0:
+        value = varint();
+        break;
+      case 1:
+        value = bytes.subarray(pos, pos + 8);
+        pos += 8;
+        break;
+      case 2: {
+        const length = varint();
+        value = bytes.subarray(pos, pos + length);
+        pos += length;
+        break;
+      }
+      case 5:
+        value = bytes.subarray(pos, pos + 4);
+        pos += 4;
+        break;
+      default:
+        throw new Error(`unsupported wire type in field ${field}`);
+    }
+    fields.set(field, value);
+  }
+  return fields;
+}
+
+/* The session. */
+
+const $ = (id) => document.getElementById(id);
+const nowMicros = () => Math.round((performance.timeOrigin + performance.now()) * 1000);
+
+const motion = {
+  orientation: null,
+  acceleration: null,
+};
+let channel = null;
+let peer = null;
+let sampleTimer = null;
+let sentAt = 0;
+let session = null;
+
+function setStatus(text) {
+  $("status").textContent = text;
+  $("status").classList.toggle("connected", text === "connected");
+}
+
+function showError(message) {
+  $("error").textContent = message || "";
+  $("error").hidden = !message;
+}
+
+function deviceId() {
+  let id = localStorage.getItem(DEVICE_ID_KEY);
+  if (!id) {
+    id = crypto.randomUUID ? crypto.randomUUID() : String(Math.random()).slice(2);
+    localStorage.setItem(DEVICE_ID_KEY, id);
+  }
+  return id;
+}
+
+function send(bytes) {
+  if (channel && channel.readyState === "open") channel.send(bytes);
+}
+
+function handleEvent(bytes, receivedAt) {
+  const event = decode(bytes);
+  if (event.has(2)) {
+    // ConnectionOpenedEvent, the server is ready for the controller.
+    send(initCommand($("name").value.trim(), deviceId()));
+    setStatus("connected");
+    startSampling();
+  } else if (event.has(8)) {
+    send(pongCommand(decode(event.get(8)).get(1) || 0));
+  } else if (event.has(9)) {
+    const t0 = decode(event.get(9)).get(1) || 0;
+    send(timeSyncCommand(t0, receivedAt, nowMicros()));
+  } else if (event.has(4)) {
+    const description = decode(event.get(4)).get(11);
+    showError(description ? new TextDecoder().decode(description) : "server error");
+  }
+}
+
+function startSampling() {
+  clearInterval(sampleTimer);
+  sampleTimer = setInterval(() => {
+    const values = {};
+    for (const property of Object.keys(PROPERTIES)) {
+      if (motion[property]) values[property] = motion[property];
+    }
+    if (!Object.keys(values).length || sentAt === motion.updatedAt) return;
+    sentAt = motion.updatedAt;
+    send(sampleCommand(values, motion.updatedAt));
+  }, SAMPLE_INTERVAL_MS);
+}
+
+function format([x, y, z]) {
+  return [x, y, z].map((n) => n.toFixed(2)).join(" ");
+}
+
+window.addEventListener("deviceorientation", (event) => {
+  if (event.alpha === null) return;
+  motion.orientation = [event.alpha, event.beta, event.gamma];
+  motion.updatedAt = nowMicros();
+  $("orientation").textContent = format(motion.orientation);
+});
+
+window.addEventListener("devicemotion", (event) => {
+  const a = event.acceleration || event.accelerationIncludingGravity;
+  if (!a || a.x === null) return;
+  motion.acceleration = [a.x, a.y, a.z];
+  motion.updatedAt = nowMicros();
+  $("acceleration").textContent = format(motion.acceleration);
+});
+
+async function requestSensors() {
+  // iOS only exposes the sensors after asking from a user gesture.
+  for (const type of [window.DeviceOrientationEvent, window.DeviceMotionEvent]) {
+    if (type && typeof type.requestPermission === "function") {
+      if ((await type.requestPermission()) !== "granted") {
+        throw new Error("motion sensor access was denied");
+      }
+    }
+  }
+}
+
+const TRICKLE_INTERVAL_MS = 250;
+const TRICKLE_TIMEOUT_MS = 15000;
+
+/** Write candidates as an `application/trickle-ice-sdpfrag` body. */
+function sdpFragment(candidates, end) {
+  let frag = "";
+  let mid = null;
+  for (const candidate of candidates) {
+    if (candidate.sdpMid !== null && candidate.sdpMid !== mid) {
+      mid = candidate.sdpMid;
+      frag += `m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\na=mid:${mid}\r\n`;
+    }
+    frag += `a=${candidate.candidate}\r\n`;
+  }
+  return end ? `${frag}a=end-of-candidates\r\n` : frag;
+}
+
+/** Read the candidates of a fragment the server answered with. */
+function parseFragment(frag) {
+  const candidates = [];
+  let mid = null;
+  let end = false;
+  for (const line of frag.split(/\r?\n/)) {
+    if (line.startsWith("a=mid:")) mid = line.slice(6);
+    else if (line.startsWith("a=candidate:")) {
+      candidates.push({ candidate: line.slice(2), sdpMid: mid, sdpMLineIndex: 0 });
+    } else if (line === "a=end-of-candidates") end = true;
+  }
+  return { candidates, end };
+}
+
+/**
+ * Exchange candidates over the session until both sides sent all of theirs or
+ * the connection is established. Every response only carries the server
+ * candidates that were not sent before.
+ */
+async function trickle(pc, location, local) {
+  let sent = 0;
+  let sentEnd = false;
+  let remoteEnd = false;
+  const deadline = Date.now() + TRICKLE_TIMEOUT_MS;
+  while (pc === peer && Date.now() < deadline) {
+    if (["connected", "failed", "closed"].includes(pc.connectionState)) return;
+    if (sentEnd && remoteEnd) return;
+    const candidates = local.candidates.slice(sent);
+    const end = local.end && !sentEnd;
+    sent += candidates.length;
+    sentEnd = sentEnd || end;
+    const response = await fetch(location, {
+      method: "PATCH",
+      headers: { "Content-Type": "application/trickle-ice-sdpfrag" },
+      body: sdpFragment(candidates, end),
+    });
+    if (!response.ok) throw new Error("signaling failed");
+    if (response.status === 200) {
+      const remote = parseFragment(await response.text());
+      for (const candidate of remote.candidates) await pc.addIceCandidate(candidate);
+      remoteEnd = remoteEnd || remote.end;
+    }
+    await new Promise((resolve) => setTimeout(resolve, TRICKLE_INTERVAL_MS));
+  }
+}
+
+async function join() {
+  showError(null);
+  const name = $("name").value.trim();
+  if (!name) return showError("a name is required");
+  localStorage.setItem(NAME_KEY, name);
+  await requestSensors();
+
+  leave();
+  setStatus("connecting");
+  peer = new RTCPeerConnection();
+  // The server opens the `main` channel, this one only negotiates SCTP.
+  peer.createDataChannel("bootstrap");
+  peer.addEventListener("datachannel", (event) => {
+    if (event.channel.label !== "main") return;
+    channel = event.channel;
+    channel.binaryType = "arraybuffer";
+    channel.addEventListener("message", (message) =>
+      handleEvent(new Uint8Array(message.data), nowMicros()),
+    );
+    channel.addEventListener("close", () => {
+      clearInterval(sampleTimer);
+      setStatus("disconnected");
+    });
+  });
+  peer.addEventListener("connectionstatechange", () => {
+    if (peer.connectionState === "failed") setStatus("failed");
+  });
+
+  // Offer right away, the candidates of both sides follow over the session.
+  const local = { candidates: [], end: false };
+  peer.addEventListener("icecandidate", (event) => {
+    if (event.candidate && event.candidate.candidate) local.candidates.push(event.candidate);
+    else if (!event.candidate) local.end = true;
+  });
+  await peer.setLocalDescription(await peer.createOffer());
+
+  const token = $("token").value.trim();
+  const response = await fetch("/sessions", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({
+      payload: btoa(peer.localDescription.sdp).replace(/=+$/, ""),
+      trickle: true,
+      ...(token ? { token } : {}),
+    }),
+  });
+  if (!response.ok) {
+    setStatus("disconnected");
+    throw new Error(response.status === 401 ? "wrong PIN or token" : "signaling failed");
+  }
+  session = response.headers.get("Location");
+  const answer = await response.json();
+  await peer.setRemoteDescription({ type: "answer", sdp: atob(answer.payload) });
+  await trickle(peer, session, local);
+}
+
+function leave() {
+  if (session) {
+    fetch(session, { method: "DELETE", keepalive: true }).catch(() => {});
+    session = null;
+  }
+  if (peer) peer.close();
+  peer = null;
+}
+
+$("name").value =
+  localStorage.getItem(NAME_KEY) || `phone-${deviceId().replace(/-/g, "").slice(0, 4)}`;
+$("token").value = new URLSearchParams(location.search).get("token") || "";
+window.addEventListener("pagehide", leave);
+$("join").addEventListener("click", () => join().catch((err) => showError(err.message)));
+
+if (!window.isSecureContext) {
+  // Browsers never fire the sensor events outside of a secure context.
+  showError("motion sensors need a secure page, open the controller over https or on localhost");
+  $("join").disabled = true;
+}
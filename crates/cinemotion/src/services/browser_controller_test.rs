@@ -0,0 +1,23 @@
+use warp::http::StatusCode;
+
+use super::*;
+
+#[tokio::test]
+async fn test_routes() {
+    let response = warp::test::request()
+        .path("/controller")
+        .reply(&routes())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(INDEX.contains("/controller/controller.js"));
+
+    let response = warp::test::request()
+        .path("/controller/controller.js")
+        .reply(&routes())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/javascript; charset=utf-8"
+    );
+}
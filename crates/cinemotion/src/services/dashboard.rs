@@ -0,0 +1,28 @@
+use warp::Filter;
+
+#[cfg(test)]
+#[path = "dashboard_test.rs"]
+mod dashboard_test;
+
+/// The dashboard page served on the http root.
+pub const INDEX: &str = include_str!("dashboard/index.html");
+
+const SCRIPT: &str = include_str!("dashboard/dashboard.js");
+const STYLE: &str = include_str!("dashboard/dashboard.css");
+
+/// The static assets of the dashboard under `/dashboard/`.
+///
+/// The page itself needs no credentials, it asks for a PIN or token and
+/// reads everything else from the REST API and the event stream.
+pub fn assets() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let script = warp::path!("dashboard" / "dashboard.js")
+        .map(|| asset(SCRIPT, "text/javascript; charset=utf-8"));
+    let style =
+        warp::path!("dashboard" / "dashboard.css").map(|| asset(STYLE, "text/css; charset=utf-8"));
+    warp::get().and(script.or(style).unify())
+}
+
+fn asset(body: &'static str, content_type: &'static str) -> warp::reply::Response {
+    use warp::Reply;
+    warp::reply::with_header(body, "content-type", content_type).into_response()
+}
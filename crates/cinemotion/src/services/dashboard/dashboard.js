@@ -0,0 +1,213 @@
+// The stage dashboard, built on the REST API and the `/events` stream.
+"use strict";
+
+const TOKEN_KEY = "cinemotion.token";
+
+let token = new URLSearchParams(location.search).get("token") || localStorage.getItem(TOKEN_KEY) || "";
+let events = null;
+
+const $ = (id) => document.getElementById(id);
+
+function el(tag, text, className) {
+  const node = document.createElement(tag);
+  if (text !== undefined) node.textContent = text;
+  if (className) node.className = className;
+  return node;
+}
+
+function table(headers, rows) {
+  const node = el("table");
+  const head = node.createTHead().insertRow();
+  headers.forEach((header) => head.appendChild(el("th", header)));
+  const body = node.createTBody();
+  rows.forEach((cells) => {
+    const row = body.insertRow();
+    cells.forEach((cell) => row.appendChild(cell instanceof Node ? cell : el("td", cell)));
+  });
+  return node;
+}
+
+function replace(id, content, empty) {
+  const node = $(id);
+  node.replaceChildren(content || el("p", empty, "empty"));
+}
+
+function showError(message) {
+  const node = $("error");
+  node.textContent = message || "";
+  node.hidden = !message;
+}
+
+async function api(method, path, body) {
+  const response = await fetch(path, {
+    method,
+    headers: {
+      Authorization: `Bearer ${token}`,
+      ...(body ? { "Content-Type": "application/json" } : {}),
+    },
+    body: body ? JSON.stringify(body) : undefined,
+  });
+  const text = await response.text();
+  const data = text ? JSON.parse(text) : null;
+  if (!response.ok) {
+    throw new Error((data && data.detail) || response.statusText);
+  }
+  return data;
+}
+
+const fixed = (n) => Number(n).toFixed(3);
+
+function formatValue(value) {
+  if (value.float !== undefined) return fixed(value.float);
+  if (value.vec3) return ["x", "y", "z"].map((k) => fixed(value.vec3[k])).join(" ");
+  if (value.vec4) return ["x", "y", "z", "w"].map((k) => fixed(value.vec4[k])).join(" ");
+  if (value.matrix44) {
+    const m = value.matrix44;
+    return [m.row0, m.row1, m.row2, m.row3]
+      .map((row) => ["x", "y", "z", "w"].map((k) => fixed(row[k])).join(" "))
+      .join(" | ");
+  }
+  return JSON.stringify(value);
+}
+
+const byName = (a, b) => (a.name < b.name ? -1 : a.name > b.name ? 1 : 0);
+
+function valueCell(value) {
+  return el("td", formatValue(value), "value");
+}
+
+function renderControllers(controllers) {
+  const list = Object.values(controllers).sort(byName);
+  if (!list.length) return replace("controllers", null, "No controllers.");
+  const fragment = document.createDocumentFragment();
+  list.forEach((controller) => {
+    const title = el("h3", controller.name);
+    if (controller.status !== "connected") {
+      title.appendChild(el("span", ` (${controller.status})`, "offline"));
+    }
+    fragment.appendChild(title);
+    const rows = Object.values(controller.properties)
+      .sort(byName)
+      .map((property) => [property.name, valueCell(property.value)]);
+    fragment.appendChild(table(["Property", "Value"], rows));
+  });
+  replace("controllers", fragment);
+}
+
+function renderObjects(scene) {
+  $("scene-name").textContent = scene.name;
+  const list = Object.values(scene.objects).sort(byName);
+  if (!list.length) return replace("objects", null, "No objects.");
+  const fragment = document.createDocumentFragment();
+  list.forEach((object) => {
+    fragment.appendChild(el("h3", object.name));
+    const rows = Object.entries(object.properties)
+      .sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0))
+      .map(([name, link]) => [
+        name,
+        link.binding ? `${link.binding.namespace}.${link.binding.property}` : "unbound",
+        valueCell(link.value),
+      ]);
+    fragment.appendChild(table(["Property", "Binding", "Value"], rows));
+  });
+  replace("objects", fragment);
+}
+
+function renderMode(mode) {
+  $("mode-name").textContent = mode;
+  document.querySelectorAll("#mode button").forEach((button) => {
+    button.classList.toggle("active", button.dataset.mode === mode);
+  });
+}
+
+function renderState(state) {
+  renderMode(state.mode);
+  renderControllers(state.controllers);
+  renderObjects(state.scene);
+}
+
+async function refreshConnections() {
+  const connections = await api("GET", "/connections");
+  if (!connections.length) return replace("connections", null, "No connections.");
+  const rows = connections.map((c) => [
+    String(c.id),
+    c.name || "",
+    c.role,
+    c.latency_ms === null || c.latency_ms === undefined ? "-" : `${c.latency_ms.toFixed(1)} ms`,
+  ]);
+  replace("connections", table(["Id", "Name", "Role", "Latency"], rows));
+}
+
+async function refreshTakes() {
+  try {
+    const takes = await api("GET", "/takes");
+    if (!takes.length) return replace("takes", null, "No takes.");
+    const rows = takes.map((take) => [
+      take.name,
+      String(take.frames),
+      `${(take.duration_us / 1e6).toFixed(1)} s`,
+    ]);
+    replace("takes", table(["Name", "Frames", "Duration"], rows));
+  } catch (err) {
+    replace("takes", null, err.message);
+  }
+}
+
+function setStatus(text, connected) {
+  const node = $("status");
+  node.textContent = text;
+  node.classList.toggle("connected", connected);
+}
+
+async function connect() {
+  if (events) events.close();
+  showError(null);
+  try {
+    renderState(await api("GET", "/state"));
+    await refreshConnections();
+    await refreshTakes();
+  } catch (err) {
+    setStatus("disconnected", false);
+    showError(err.message);
+    return;
+  }
+  localStorage.setItem(TOKEN_KEY, token);
+
+  events = new EventSource(`/events?token=${encodeURIComponent(token)}`);
+  events.onopen = () => setStatus("connected", true);
+  events.onerror = () => setStatus("reconnecting", false);
+  events.addEventListener("state", (event) => renderState(JSON.parse(event.data)));
+  events.addEventListener("mode", (event) => {
+    renderMode(JSON.parse(event.data).mode);
+    // A take is finished when the recording mode is left.
+    refreshTakes();
+  });
+  events.addEventListener("error", (event) => {
+    // Connection failures also fire `error`, without data.
+    if (event.data) showError(JSON.parse(event.data).error);
+  });
+  ["connection_opened", "connection_closed", "controller_joined", "controller_left"].forEach(
+    (name) => events.addEventListener(name, () => refreshConnections().catch(() => {})),
+  );
+}
+
+document.querySelectorAll("#mode button").forEach((button) => {
+  button.addEventListener("click", async () => {
+    try {
+      await api("PUT", "/mode", { mode: button.dataset.mode });
+      renderMode(button.dataset.mode);
+      showError(null);
+    } catch (err) {
+      showError(err.message);
+    }
+  });
+});
+
+$("login").addEventListener("submit", (event) => {
+  event.preventDefault();
+  token = $("token").value.trim();
+  connect();
+});
+
+$("token").value = token;
+connect();
@@ -0,0 +1,28 @@
+use warp::http::StatusCode;
+
+use super::*;
+
+#[tokio::test]
+async fn test_assets() {
+    let response = warp::test::request()
+        .path("/dashboard/dashboard.js")
+        .reply(&assets())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/javascript; charset=utf-8"
+    );
+
+    let response = warp::test::request()
+        .path("/dashboard/missing.js")
+        .reply(&assets())
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_index_links_the_assets() {
+    assert!(INDEX.contains("/dashboard/dashboard.js"));
+    assert!(INDEX.contains("/dashboard/dashboard.css"));
+}
@@ -1,13 +1,21 @@
-use std::{collections::HashMap, convert::Infallible, net::SocketAddr, pin::Pin, sync::Arc};
+use std::{convert::Infallible, net::SocketAddr, pin::Pin, sync::Arc};
 
 use async_trait::async_trait;
-use tokio::sync::Mutex;
-use warp::{self, Filter};
+use warp::{self, Filter, Reply};
 
 use crate::data::WebRTCSessionDescriptor;
-use crate::webrtc::SignalingRelay;
+use crate::webrtc::{sdpfrag, SignalingRelay};
 
+use super::api::{self as rest, RestApi};
+use super::sse::EventStream;
 use super::Service;
+use super::{browser_controller, dashboard};
+
+/// The response header of the HTTP root carrying the server certificate fingerprint.
+pub const FINGERPRINT_HEADER: &str = "x-cinemotion-fingerprint";
+
+/// The largest trickle ICE body accepted.
+const MAX_SDPFRAG_SIZE: u64 = 64 * 1024;
 
 pub struct HttpService {
     future: tokio::task::JoinHandle<std::result::Result<(), crate::Error>>,
@@ -15,15 +23,21 @@ pub struct HttpService {
 }
 
 impl HttpService {
-    pub fn new<I>(address: I, signaling_relay: SignalingRelay) -> Self
+    pub fn new<I>(
+        address: I,
+        signaling_relay: SignalingRelay,
+        rest_api: RestApi,
+        event_stream: EventStream,
+        fingerprint: Option<String>,
+    ) -> Self
     where
         I: Into<SocketAddr> + Send + 'static,
     {
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
 
-        let manager = Arc::new(Mutex::new(signaling_relay));
+        let manager = Arc::new(signaling_relay);
 
-        let api = api(manager);
+        let api = api(manager, rest_api, event_stream, fingerprint);
         let routes = api.with(warp::log("cinemotion"));
         let service = warp::serve(routes).run(address);
 
@@ -79,55 +93,134 @@ impl futures::Future for HttpService {
 }
 
 fn api(
-    manager: Arc<Mutex<SignalingRelay>>,
-) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    root().or(session_create(manager))
+    manager: Arc<SignalingRelay>,
+    rest_api: RestApi,
+    event_stream: EventStream,
+    fingerprint: Option<String>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = std::convert::Infallible> + Clone {
+    root(fingerprint)
+        .or(dashboard::assets())
+        .or(browser_controller::routes())
+        .or(session_create(Arc::clone(&manager)))
+        .or(session_trickle(Arc::clone(&manager)))
+        .or(session_delete(manager))
+        .or(rest_api.routes())
+        .or(event_stream.routes())
+        .recover(rest::recover)
 }
 
-fn root() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    warp::path::end().and_then(handle_root)
+fn root(
+    fingerprint: Option<String>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path::end()
+        .and(warp::any().map(move || fingerprint.clone()))
+        .and_then(handle_root)
 }
 
-async fn handle_root() -> Result<impl warp::Reply, Infallible> {
-    Ok(warp::reply::with_status(
-        warp::reply::html("CineMotion"),
+async fn handle_root(fingerprint: Option<String>) -> Result<impl warp::Reply, Infallible> {
+    let mut response = warp::reply::with_status(
+        warp::reply::html(dashboard::INDEX),
         warp::http::StatusCode::OK,
-    ))
+    )
+    .into_response();
+    if let Some(value) = fingerprint.and_then(|f| warp::http::HeaderValue::from_str(&f).ok()) {
+        response.headers_mut().insert(FINGERPRINT_HEADER, value);
+    }
+    Ok(response)
 }
 
 fn session_create(
-    manager: Arc<Mutex<SignalingRelay>>,
+    manager: Arc<SignalingRelay>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::post()
-        .and(warp::path("sessions"))
+        .and(warp::path!("sessions"))
         .and(warp::body::json())
+        .and(warp::addr::remote())
         .and(with_connection_manager(manager))
         .and_then(handle_post_sessions)
 }
 
+fn session_trickle(
+    manager: Arc<SignalingRelay>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::patch()
+        .and(warp::path!("sessions" / String))
+        .and(warp::body::content_length_limit(MAX_SDPFRAG_SIZE))
+        .and(warp::body::bytes())
+        .and(with_connection_manager(manager))
+        .and_then(handle_patch_session)
+}
+
+fn session_delete(
+    manager: Arc<SignalingRelay>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::delete()
+        .and(warp::path!("sessions" / String))
+        .and(with_connection_manager(manager))
+        .and_then(handle_delete_session)
+}
+
 async fn handle_post_sessions(
     session_desc: WebRTCSessionDescriptor,
-    manager: Arc<Mutex<SignalingRelay>>,
-) -> Result<impl warp::Reply, Infallible> {
-    let manager = manager.lock().await;
-    match manager.create(session_desc).await {
-        Ok(r) => Ok(warp::reply::with_status(
-            warp::reply::json(&r),
-            warp::http::StatusCode::CREATED,
-        )),
-        Err(err) => {
-            let empty: HashMap<String, String> = HashMap::new();
-            Ok(warp::reply::with_status(
-                warp::reply::json(&empty),
-                warp::http::StatusCode::BAD_REQUEST,
-            ))
+    remote: Option<SocketAddr>,
+    manager: Arc<SignalingRelay>,
+) -> Result<warp::reply::Response, Infallible> {
+    match manager
+        .create(session_desc, remote.map(|addr| addr.ip()))
+        .await
+    {
+        Ok(r) => {
+            let location = format!("/sessions/{}", r.session_id.as_deref().unwrap_or_default());
+            Ok(warp::reply::with_header(
+                warp::reply::with_status(warp::reply::json(&r), warp::http::StatusCode::CREATED),
+                "location",
+                location,
+            )
+            .into_response())
+        }
+        Err(err) => Ok(session_error(err)),
+    }
+}
+
+/// Add trickled candidates, answering with the server candidates like WHIP does.
+async fn handle_patch_session(
+    id: String,
+    body: bytes::Bytes,
+    manager: Arc<SignalingRelay>,
+) -> Result<warp::reply::Response, Infallible> {
+    let Ok(frag) = std::str::from_utf8(&body) else {
+        return Ok(session_error(crate::Error::BadRTCDescriptor(
+            "the sdp fragment is not utf8".to_string(),
+        )));
+    };
+    match manager.trickle(&id, frag).await {
+        Ok(Some(local)) => {
+            Ok(
+                warp::reply::with_header(local.to_sdp(), "content-type", sdpfrag::CONTENT_TYPE)
+                    .into_response(),
+            )
         }
+        Ok(None) => Ok(warp::http::StatusCode::NO_CONTENT.into_response()),
+        Err(err) => Ok(session_error(err)),
     }
 }
 
+async fn handle_delete_session(
+    id: String,
+    manager: Arc<SignalingRelay>,
+) -> Result<warp::reply::Response, Infallible> {
+    match manager.delete(&id).await {
+        Ok(()) => Ok(warp::http::StatusCode::OK.into_response()),
+        Err(err) => Ok(session_error(err)),
+    }
+}
+
+fn session_error(err: crate::Error) -> warp::reply::Response {
+    rest::error_response(&err)
+}
+
 fn with_connection_manager(
-    manager: Arc<Mutex<SignalingRelay>>,
-) -> impl Filter<Extract = (Arc<Mutex<SignalingRelay>>,), Error = std::convert::Infallible> + Clone
-{
+    manager: Arc<SignalingRelay>,
+) -> impl Filter<Extract = (Arc<SignalingRelay>,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || manager.clone())
 }
@@ -4,25 +4,108 @@ use async_trait::async_trait;
 
 use super::Service;
 
+#[cfg(test)]
+#[path = "mdns_test.rs"]
+mod mdns_test;
+
+/// The DNS-SD service type of the http (WebRTC signaling) transport.
+pub const SERVICE_TYPE_TCP: &str = "_cinemotion._tcp";
+/// The DNS-SD service type of the quic transport.
+pub const SERVICE_TYPE_UDP: &str = "_cinemotion._udp";
+
+/// What the server advertises about itself on the local network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advertisement {
+    /// The DNS-SD instance name that tells servers on the network apart.
+    pub instance_name: String,
+    /// The port of the http service when it is running.
+    pub http_port: Option<u16>,
+    /// The port of the quic service when it is running.
+    pub quic_port: Option<u16>,
+    /// The fingerprint of the server certificate.
+    pub fingerprint: Option<String>,
+}
+
+impl Advertisement {
+    /// The default instance name, unique per host.
+    pub fn default_instance_name() -> String {
+        match hostname::get()
+            .ok()
+            .and_then(|name| name.into_string().ok())
+        {
+            Some(host) => format!("cinemotion-{host}"),
+            None => "cinemotion".to_string(),
+        }
+    }
+
+    /// The TXT records shared by every advertised service type.
+    pub fn txt_records(&self) -> Vec<String> {
+        let mut transports = vec![];
+        if self.http_port.is_some() {
+            transports.push("webrtc");
+        }
+        if self.quic_port.is_some() {
+            transports.push("quic");
+        }
+        let mut txt = vec![
+            format!("proto={}", crate::PROTOCOL_VERSION),
+            format!("version={}", crate::VERSION),
+            format!("transports={}", transports.join(",")),
+        ];
+        if let Some(port) = self.http_port {
+            txt.push(format!("webrtc={port}"));
+            txt.push("path=/".to_string());
+        }
+        if let Some(port) = self.quic_port {
+            txt.push(format!("quic={port}"));
+        }
+        if let Some(fingerprint) = &self.fingerprint {
+            txt.push(format!("fingerprint={fingerprint}"));
+        }
+        txt
+    }
+}
+
 pub struct MdnsService {
     future: tokio::task::JoinHandle<std::result::Result<(), crate::Error>>,
     shutdown_tx: tokio::sync::mpsc::Sender<()>,
 }
 
 impl MdnsService {
-    pub fn new(port: u16) -> Self {
+    /// Advertise a service type for each running transport, the services
+    /// are unregistered when the service shuts down.
+    pub fn new(advertisement: Advertisement) -> Self {
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
         MdnsService {
             future: tokio::task::spawn(async move {
-                let responder = libmdns::Responder::new().unwrap();
-                let _svc = responder.register(
-                    "_http._tcp".to_owned(),
-                    "cinemotion".to_owned(),
-                    port,
-                    &["path=/"],
-                );
+                let responder = libmdns::Responder::new()
+                    .map_err(|err| crate::Error::ConnectionFailed(err.to_string()))?;
+                let txt = advertisement.txt_records();
+                let txt: Vec<&str> = txt.iter().map(String::as_str).collect();
+                let services: Vec<_> = [
+                    (SERVICE_TYPE_TCP, advertisement.http_port),
+                    (SERVICE_TYPE_UDP, advertisement.quic_port),
+                ]
+                .into_iter()
+                .filter_map(|(service_type, port)| {
+                    let port = port?;
+                    tracing::info!(
+                        "advertising {}.{} on port {}",
+                        advertisement.instance_name,
+                        service_type,
+                        port
+                    );
+                    Some(responder.register(
+                        service_type.to_owned(),
+                        advertisement.instance_name.clone(),
+                        port,
+                        &txt,
+                    ))
+                })
+                .collect();
 
                 shutdown_rx.recv().await;
+                drop(services);
                 Ok(())
             }),
             shutdown_tx,
@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn test_txt_records() {
+    let advertisement = Advertisement {
+        instance_name: "stage-a".into(),
+        http_port: Some(7272),
+        quic_port: Some(4567),
+        fingerprint: Some("AB:CD".into()),
+    };
+    assert_eq!(
+        advertisement.txt_records(),
+        vec![
+            format!("proto={}", crate::PROTOCOL_VERSION),
+            format!("version={}", crate::VERSION),
+            "transports=webrtc,quic".to_string(),
+            "webrtc=7272".to_string(),
+            "path=/".to_string(),
+            "quic=4567".to_string(),
+            "fingerprint=AB:CD".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_txt_records_only_list_running_transports() {
+    let advertisement = Advertisement {
+        instance_name: "stage-a".into(),
+        http_port: None,
+        quic_port: Some(4567),
+        fingerprint: None,
+    };
+    let txt = advertisement.txt_records();
+    assert!(txt.contains(&"transports=quic".to_string()));
+    assert!(!txt.iter().any(|record| record.starts_with("webrtc=")));
+    assert!(!txt.iter().any(|record| record.starts_with("fingerprint=")));
+}
@@ -1,10 +1,14 @@
 use async_trait::async_trait;
 use futures::Future;
 
+pub mod api;
+pub mod browser_controller;
+pub mod dashboard;
 pub mod http;
 pub mod mdns;
 pub mod quic;
 pub mod runtime;
+pub mod sse;
 
 /// Represents a component of the server
 #[async_trait]
@@ -1,15 +1,22 @@
 use super::Service;
-use hostname;
 use quinn::{Endpoint, ServerConfig};
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::str;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::connection::LOCAL_CONN_ID;
+use crate::auth::Authenticator;
+use crate::connection::{self, LOCAL_CONN_ID};
 use crate::messages::{AddConnection, Message as MessageFrame, MessagePipeTx};
 use crate::quic;
+use crate::tls::Identity;
 
 pub const ALPN_QUIC_HTTP: &[&[u8]] = &[b"cinemotionv1"];
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+const AUTH_FAILED_CODE: u32 = 2;
 
 pub struct QuicService {
     shutdown_tx: tokio::sync::mpsc::Sender<()>,
@@ -17,22 +24,25 @@ pub struct QuicService {
 }
 
 impl QuicService {
-    pub fn new(sender: MessagePipeTx) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        sender: MessagePipeTx,
+        auth: Arc<Authenticator>,
+        identity: Identity,
+    ) -> Self {
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
         QuicService {
             shutdown_tx,
             future: tokio::task::spawn(async move {
                 tokio::select! {
                     _ = shutdown_rx.recv() => {}
-                    _ = run_server(sender) => {}
+                    _ = run_server(address, sender, auth, identity) => {}
                 }
 
                 Ok(())
             }),
         }
     }
-
-    // Add your methods here
 }
 
 #[async_trait::async_trait]
@@ -73,31 +83,44 @@ impl futures::Future for QuicService {
     }
 }
 
-async fn run_server(sender: MessagePipeTx) {
-    let addr = "0.0.0.0:4567".parse().unwrap();
-    let cert = rcgen::generate_simple_self_signed(get_certificate_names().await).unwrap();
-    let cert_der = cert.serialize_der().unwrap();
-    let private_key = cert.serialize_private_key_der();
-    let private_key = rustls::PrivateKey(private_key);
-    let cert_chain = vec![rustls::Certificate(cert_der.clone())];
-
-    let mut server_crypto = rustls::ServerConfig::builder()
+async fn run_server(
+    addr: SocketAddr,
+    sender: MessagePipeTx,
+    auth: Arc<Authenticator>,
+    identity: Identity,
+) {
+    tracing::info!("quic certificate fingerprint: {}", identity.fingerprint());
+    let mut server_crypto = match rustls::ServerConfig::builder()
         .with_safe_defaults()
         .with_no_client_auth()
-        .with_single_cert(cert_chain, private_key)
-        .unwrap();
+        .with_single_cert(identity.cert_chain, identity.key)
+    {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!(%err, "invalid server certificate, closing service.");
+            return;
+        }
+    };
     server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
 
     let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
 
-    // Update the idle timeout on the tansport config to ensure the conneciton stays alive.
-    // NOTE: this can be a source of lock up issues but those conditions are not yet known.
+    // Keep the connection alive at the transport level and let dead connections time out,
+    // the engine heartbeats take care of detecting unresponsive clients.
     let mut transport = quinn::TransportConfig::default();
-    transport.max_idle_timeout(None);
+    transport.keep_alive_interval(Some(KEEP_ALIVE_INTERVAL));
+    transport.max_idle_timeout(Some(MAX_IDLE_TIMEOUT.try_into().unwrap()));
     server_config.transport_config(Arc::new(transport));
 
     // Start the sever endpoint and accept incoming conections.
-    let endpoint = Endpoint::server(server_config, addr).unwrap();
+    let endpoint = match Endpoint::server(server_config, addr) {
+        Ok(endpoint) => endpoint,
+        Err(err) => {
+            tracing::error!(%err, %addr, "failed to bind the quic endpoint, closing service.");
+            return;
+        }
+    };
+    tracing::info!("accepting quic connections on: {}", addr);
 
     loop {
         // Accept an incoming connection and notify the runtime of the new connection.
@@ -106,57 +129,61 @@ async fn run_server(sender: MessagePipeTx) {
             continue;
         };
 
-        match connecting.await {
-            Ok(conn) => {
-                let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
-                let agent = Box::new(quic::QuicAgent::new(conn, ack_pipe_rx));
-                if let Err(err) = sender.send(MessageFrame::with_command(
-                    LOCAL_CONN_ID,
-                    AddConnection { agent, ack_pipe },
-                )) {
-                    tracing::error!(%err, "failed to send connection to runtime, closing service.");
+        // Establish and authenticate the connection off the accept loop so a slow
+        // client cannot hold up everyone else.
+        let sender = sender.clone();
+        let auth = Arc::clone(&auth);
+        tokio::spawn(async move {
+            let conn = match connecting.await {
+                Ok(conn) => conn,
+                Err(err) => {
+                    tracing::error!(%err, "failed to establish quic connection");
                     return;
                 }
+            };
+            let role = match authenticate(&conn, &auth).await {
+                Ok(role) => role,
+                Err(err) => {
+                    tracing::warn!(%err, remote = %conn.remote_address(), "rejecting quic connection");
+                    conn.close(AUTH_FAILED_CODE.into(), err.to_string().as_bytes());
+                    return;
+                }
+            };
+
+            let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
+            let agent = Box::new(quic::QuicAgent::new(conn, ack_pipe_rx));
+            if let Err(err) = sender.send(MessageFrame::with_command(
+                LOCAL_CONN_ID,
+                AddConnection {
+                    agent,
+                    role,
+                    ack_pipe,
+                },
+            )) {
+                tracing::error!(%err, "failed to send connection to runtime.");
             }
-            Err(err) => {
-                tracing::error!(%err, "failed to establish quic connection");
-            }
-        }
-    }
-}
-
-async fn get_certificate_names() -> Vec<String> {
-    let mut names = vec!["0.0.0.0".into(), "127.0.0.1".into(), "localhost".into()];
-    if let Ok(name) = hostname::get() {
-        if let Ok(name) = name.into_string() {
-            names.push(name);
-        }
-    }
-    // Get all network interfaces
-    let ifaces = get_if_addrs::get_if_addrs().expect("Failed to get network interfaces");
-
-    for iface in ifaces {
-        // Check if the IP is v4 and the interface is not a loopback
-        if !iface.is_loopback() && iface.ip().is_ipv4() {
-            names.push(iface.ip().to_string());
-        }
+        });
     }
-    names
 }
 
-// Implementation of `ServerCertVerifier` that verifies everything as trustworthy.
-struct SkipServerVerification;
-
-impl rustls::client::ServerCertVerifier for SkipServerVerification {
-    fn verify_server_cert(
-        &self,
-        _end_entity: &rustls::Certificate,
-        _intermediates: &[rustls::Certificate],
-        _server_name: &rustls::ServerName,
-        _scts: &mut dyn Iterator<Item = &[u8]>,
-        _ocsp_response: &[u8],
-        _now: std::time::SystemTime,
-    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
-        Ok(rustls::client::ServerCertVerified::assertion())
+/// Check the credential the client sends on its first unidirectional stream.
+async fn authenticate(
+    conn: &quinn::Connection,
+    auth: &Authenticator,
+) -> crate::Result<connection::Role> {
+    if !auth.is_enabled() {
+        return auth.verify(None);
     }
+    let credential = tokio::time::timeout(AUTH_TIMEOUT, async {
+        let mut stream = conn
+            .accept_uni()
+            .await
+            .map_err(|err| crate::Error::Unauthorized(err.to_string()))?;
+        quic::recv_auth(&mut stream)
+            .await
+            .map_err(|err| crate::Error::Unauthorized(err.to_string()))
+    })
+    .await
+    .map_err(|_| crate::Error::Unauthorized("timed out waiting for credentials".into()))??;
+    auth.verify_peer(Some(conn.remote_address().ip()), Some(&credential))
 }
@@ -3,16 +3,33 @@ use std::{pin::Pin, time::Duration};
 use async_trait::async_trait;
 
 use crate::{
+    connection::HeartbeatOptions,
+    data::OfflinePolicy,
     engine::network::NetworkComponentImpl,
     engine::Engine,
+    events::EventPipeTx,
     messages::{Message, MessagePipeRx, MessagePipeTx},
-    Error, Result,
+    presets::SceneStore,
+    Error, Result, Scene, State,
 };
 
 use super::Service;
 
+/// How often the engine ticks when no tick rate is configured.
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_millis(16);
+
 pub struct RuntimeOptions {
     pub message_pipe: (MessagePipeTx, MessagePipeRx),
+    pub offline_policy: OfflinePolicy,
+    pub heartbeat: HeartbeatOptions,
+    /// The time between engine ticks.
+    pub tick_interval: Duration,
+    /// The pipe the engine broadcasts its events on.
+    pub event_pipe: EventPipeTx,
+    /// Where scenes are saved.
+    pub scene_store: SceneStore,
+    /// The scene the engine starts with, the default scene when not set.
+    pub initial_scene: Option<Scene>,
 }
 
 pub struct RuntimeService {
@@ -23,9 +40,20 @@ pub struct RuntimeService {
 impl RuntimeService {
     pub fn new(options: RuntimeOptions) -> Self {
         let mut message_pipe = options.message_pipe.1;
-        let network = NetworkComponentImpl::boxed(options.message_pipe.0.clone());
+        let network = NetworkComponentImpl::boxed_with_event_pipe(
+            options.message_pipe.0.clone(),
+            options.event_pipe,
+        );
+        let initial_state = State {
+            scene: options.initial_scene.unwrap_or_default(),
+            ..Default::default()
+        };
         let engine = Engine::builder()
+            .with_inital_state(initial_state)
+            .with_scene_store(options.scene_store)
             .with_network_component(network)
+            .with_offline_policy(options.offline_policy)
+            .with_heartbeat(options.heartbeat)
             .build()
             .unwrap();
 
@@ -34,7 +62,7 @@ impl RuntimeService {
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
         let future = tokio::spawn(async move {
             let mut message_buffer: Vec<Message> = Vec::with_capacity(1024);
-            let mut interval = tokio::time::interval(Duration::from_millis(16));
+            let mut interval = tokio::time::interval(options.tick_interval);
             loop {
                 tokio::select! {
                     _ = shutdown_rx.recv() => {
@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::Stream;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use warp::Filter;
+
+use crate::auth::Authenticator;
+use crate::data::Mode;
+use crate::events::{error_code, EventPipeRx, EventPipeTx, NackEvent};
+use crate::{Event, EventBody, Name, State};
+
+use super::api::authorize;
+
+#[cfg(test)]
+#[path = "sse_test.rs"]
+mod sse_test;
+
+/// How often state events are streamed when no rate is configured.
+pub const DEFAULT_STATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often an idle stream sends a comment to keep proxies from closing it.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// An event streamed to server-sent event subscribers.
+///
+/// The variant is the SSE event name and the fields are its JSON data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum StreamEvent {
+    State(State),
+    Mode {
+        mode: Mode,
+    },
+    Error {
+        connection: Option<usize>,
+        /// The error type, like `invalid_mode`.
+        code: String,
+        command: Option<&'static str>,
+        error: String,
+    },
+    ConnectionOpened {
+        connection: usize,
+    },
+    ConnectionClosed {
+        connection: usize,
+    },
+    ControllerJoined {
+        name: Name,
+    },
+    ControllerLeft {
+        name: Name,
+    },
+    History {
+        undo: Vec<String>,
+        redo: Vec<String>,
+    },
+    Scenes {
+        scenes: Vec<Name>,
+    },
+    /// The subscriber fell behind and missed events.
+    Lagged {
+        missed: u64,
+    },
+}
+
+impl StreamEvent {
+    /// The name of the SSE event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::State(_) => "state",
+            Self::Mode { .. } => "mode",
+            Self::Error { .. } => "error",
+            Self::ConnectionOpened { .. } => "connection_opened",
+            Self::ConnectionClosed { .. } => "connection_closed",
+            Self::ControllerJoined { .. } => "controller_joined",
+            Self::ControllerLeft { .. } => "controller_left",
+            Self::History { .. } => "history",
+            Self::Scenes { .. } => "scenes",
+            Self::Lagged { .. } => "lagged",
+        }
+    }
+
+    fn to_sse(&self) -> warp::sse::Event {
+        let event = warp::sse::Event::default().event(self.name());
+        match serde_json::to_string(self) {
+            Ok(data) => event.data(data),
+            Err(err) => {
+                tracing::error!(%err, "failed to serialize a streamed event");
+                event.data("{}")
+            }
+        }
+    }
+}
+
+/// Streams engine events to HTTP clients as server-sent events on `GET /events`.
+///
+/// Subscribers read the broadcast event pipe like connections do. State
+/// events are sent at most once per state interval and only when the state
+/// changed, heartbeats and other per-connection traffic are left out.
+#[derive(Clone)]
+pub struct EventStream {
+    event_pipe: EventPipeTx,
+    auth: Arc<Authenticator>,
+    state_interval: Duration,
+}
+
+impl EventStream {
+    pub fn new(event_pipe: EventPipeTx, auth: Arc<Authenticator>) -> Self {
+        Self {
+            event_pipe,
+            auth,
+            state_interval: DEFAULT_STATE_INTERVAL,
+        }
+    }
+
+    /// Set the minimum time between state events.
+    pub fn with_state_interval(mut self, state_interval: Duration) -> Self {
+        self.state_interval = state_interval;
+        self
+    }
+
+    pub fn routes(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let stream = self.clone();
+        warp::get()
+            .and(warp::path!("events"))
+            .and(authorize(Arc::clone(&self.auth)))
+            .map(move |_| {
+                let events = throttle(stream.event_pipe.subscribe(), stream.state_interval);
+                let events =
+                    futures::StreamExt::map(events, |event| Ok::<_, Infallible>(event.to_sse()));
+                warp::sse::reply(
+                    warp::sse::keep_alive()
+                        .interval(KEEP_ALIVE_INTERVAL)
+                        .stream(events),
+                )
+            })
+    }
+}
+
+/// Turn the engine events into streamed events, sending at most one state
+/// event per interval.
+///
+/// A state that arrives too early is held back and replaced by newer ones
+/// until the interval elapsed, so the latest state is always delivered.
+pub fn throttle(events: EventPipeRx, state_interval: Duration) -> impl Stream<Item = StreamEvent> {
+    let throttle = Throttle {
+        events,
+        state_interval,
+        queue: VecDeque::new(),
+        pending: None,
+        last_state: None,
+        last_state_at: None,
+        mode: None,
+        closed: false,
+    };
+    futures::stream::unfold(throttle, |mut throttle| async move {
+        let event = throttle.next().await?;
+        Some((event, throttle))
+    })
+}
+
+struct Throttle {
+    events: EventPipeRx,
+    state_interval: Duration,
+    queue: VecDeque<StreamEvent>,
+    /// The newest state that could not be sent yet.
+    pending: Option<State>,
+    last_state: Option<State>,
+    last_state_at: Option<Instant>,
+    mode: Option<Mode>,
+    closed: bool,
+}
+
+impl Throttle {
+    async fn next(&mut self) -> Option<StreamEvent> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(event);
+            }
+            if self.closed {
+                return self.pending.take().map(StreamEvent::State);
+            }
+            let deadline = match (&self.pending, self.last_state_at) {
+                (Some(_), Some(at)) => Some(at + self.state_interval),
+                _ => None,
+            };
+            tokio::select! {
+                _ = sleep_until(deadline) => {
+                    if let Some(state) = self.pending.take() {
+                        self.send_state(state);
+                    }
+                }
+                event = self.events.recv() => match event {
+                    Ok(event) => self.push(event),
+                    Err(RecvError::Lagged(missed)) => {
+                        self.queue.push_back(StreamEvent::Lagged { missed })
+                    }
+                    Err(RecvError::Closed) => self.closed = true,
+                },
+            }
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        let streamed = match event.body {
+            EventBody::StateChanged(change) => return self.push_state(change.0),
+            EventBody::Error(err) | EventBody::Nack(NackEvent { error: err, .. }) => {
+                StreamEvent::Error {
+                    connection: event.target,
+                    code: error_code(&err.error),
+                    command: err.command,
+                    error: err.error.to_string(),
+                }
+            }
+            EventBody::ConnectionOpened(_) => match event.target {
+                Some(connection) => StreamEvent::ConnectionOpened { connection },
+                None => return,
+            },
+            EventBody::ConnectionClosed(closed) => StreamEvent::ConnectionClosed {
+                connection: closed.0,
+            },
+            EventBody::ControllerJoined(joined) => StreamEvent::ControllerJoined { name: joined.0 },
+            EventBody::ControllerLeft(left) => StreamEvent::ControllerLeft { name: left.0 },
+            EventBody::HistoryChanged(history) => StreamEvent::History {
+                undo: history.undo,
+                redo: history.redo,
+            },
+            // Lists answered to a single connection are left out.
+            EventBody::SceneList(list) => match event.target {
+                Some(_) => return,
+                None => StreamEvent::Scenes { scenes: list.0 },
+            },
+            EventBody::Echo(_)
+            | EventBody::Ping(_)
+            | EventBody::TimeSync(_)
+            | EventBody::Ack(_) => return,
+        };
+        self.queue.push_back(streamed);
+    }
+
+    fn push_state(&mut self, state: State) {
+        // Mode changes are never throttled.
+        if self.mode.is_some_and(|mode| mode != state.mode) {
+            self.queue.push_back(StreamEvent::Mode { mode: state.mode });
+        }
+        self.mode = Some(state.mode);
+
+        if self.last_state.as_ref() == Some(&state) {
+            self.pending = None;
+            return;
+        }
+        match self.last_state_at {
+            Some(at) if at.elapsed() < self.state_interval => self.pending = Some(state),
+            _ => self.send_state(state),
+        }
+    }
+
+    fn send_state(&mut self, state: State) {
+        self.pending = None;
+        self.last_state = Some(state.clone());
+        self.last_state_at = Some(Instant::now());
+        self.queue.push_back(StreamEvent::State(state));
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending().await,
+    }
+}
@@ -0,0 +1,82 @@
+use futures::StreamExt;
+
+use super::*;
+use crate::events::{event_pipe, ConnectionClosedEvent, PingEvent, StateChangeEvent};
+
+fn state(mode: Mode) -> Event {
+    Event {
+        target: None,
+        body: StateChangeEvent(State {
+            mode,
+            ..Default::default()
+        })
+        .into(),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttle_holds_back_states() {
+    let pipe = event_pipe();
+    let mut stream = Box::pin(throttle(pipe.subscribe(), Duration::from_millis(100)));
+
+    pipe.send(state(Mode::Idle)).unwrap();
+    assert_eq!(
+        stream.next().await,
+        Some(StreamEvent::State(State::default()))
+    );
+
+    // A state within the interval is held back until it elapses, the mode
+    // change is streamed right away.
+    pipe.send(state(Mode::Live)).unwrap();
+    let start = Instant::now();
+    assert_eq!(
+        stream.next().await,
+        Some(StreamEvent::Mode { mode: Mode::Live })
+    );
+    let Some(StreamEvent::State(live)) = stream.next().await else {
+        panic!("expected a state event");
+    };
+    assert_eq!(live.mode, Mode::Live);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttle_skips_unchanged_states_and_heartbeats() {
+    let pipe = event_pipe();
+    let mut stream = Box::pin(throttle(pipe.subscribe(), Duration::from_millis(100)));
+
+    pipe.send(state(Mode::Idle)).unwrap();
+    tokio::time::advance(Duration::from_millis(200)).await;
+    pipe.send(state(Mode::Idle)).unwrap();
+    pipe.send(Event::new(1, PingEvent(0).into())).unwrap();
+    pipe.send(Event {
+        target: None,
+        body: ConnectionClosedEvent(1).into(),
+    })
+    .unwrap();
+    drop(pipe);
+
+    let events: Vec<_> = stream.by_ref().collect().await;
+    assert_eq!(
+        events,
+        vec![
+            StreamEvent::State(State::default()),
+            StreamEvent::ConnectionClosed { connection: 1 },
+        ]
+    );
+}
+
+#[test]
+fn test_stream_event_data() {
+    let event = StreamEvent::Error {
+        connection: Some(2),
+        code: "invalid_mode".into(),
+        command: Some("ChangeMode"),
+        error: "bad".into(),
+    };
+    assert_eq!(event.name(), "error");
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"connection":2,"code":"invalid_mode","command":"ChangeMode","error":"bad"}"#
+    );
+}
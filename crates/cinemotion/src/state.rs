@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
 use cinemotion_proto as proto;
+use serde::{Deserialize, Serialize};
 
 use crate::data::*;
 use crate::Name;
 use crate::Scene;
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct State {
     pub scene: Scene,
     pub controllers: HashMap<Name, controllers::Controller>,
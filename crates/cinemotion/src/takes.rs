@@ -0,0 +1,124 @@
+//! Takes record the property values of the scene objects on every tick while
+//! the engine is recording, so a run can be downloaded once it ends.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::Value;
+use crate::{Error, Name, Result, Scene};
+
+#[cfg(test)]
+#[path = "takes_test.rs"]
+mod takes_test;
+
+/// The scene object property values captured from entering until leaving the
+/// recording mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Take {
+    pub name: Name,
+    /// The server time the take started in microseconds since the unix epoch.
+    pub started_at: i64,
+    pub frames: Vec<TakeFrame>,
+}
+
+/// The property values of every scene object at one tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakeFrame {
+    /// The microseconds since the take started.
+    pub time: i64,
+    pub objects: HashMap<Name, HashMap<Name, Value>>,
+}
+
+/// What is listed about a take without its frames.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakeSummary {
+    pub name: Name,
+    pub started_at: i64,
+    /// The time of the last frame in microseconds.
+    pub duration_us: i64,
+    pub frames: usize,
+}
+
+impl From<&Take> for TakeSummary {
+    fn from(value: &Take) -> Self {
+        Self {
+            name: value.name.clone(),
+            started_at: value.started_at,
+            duration_us: value.frames.last().map_or(0, |frame| frame.time),
+            frames: value.frames.len(),
+        }
+    }
+}
+
+/// Captures takes and keeps the finished ones in memory while the server runs.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    current: Option<Take>,
+    takes: Vec<Take>,
+}
+
+impl Recorder {
+    /// Start a new take, named `take-<n>` by the number of takes recorded.
+    ///
+    /// A take that is still recording is finished first.
+    pub fn start(&mut self, now: i64) -> Name {
+        self.finish();
+        let name = Name::from(format!("take-{}", self.takes.len() + 1));
+        self.current = Some(Take {
+            name: name.clone(),
+            started_at: now,
+            frames: Vec::new(),
+        });
+        name
+    }
+
+    /// Whether a take is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Add the property values of the scene to the current take, if any.
+    pub fn capture(&mut self, scene: &Scene, now: i64) {
+        let Some(take) = &mut self.current else {
+            return;
+        };
+        let objects = scene
+            .objects()
+            .iter()
+            .map(|(name, object)| {
+                let values = object
+                    .properties()
+                    .iter()
+                    .map(|(property, link)| (property.clone(), link.value().clone()))
+                    .collect();
+                (name.clone(), values)
+            })
+            .collect();
+        take.frames.push(TakeFrame {
+            time: now.saturating_sub(take.started_at),
+            objects,
+        });
+    }
+
+    /// Finish the current take and keep it, returning its summary.
+    pub fn finish(&mut self) -> Option<TakeSummary> {
+        let take = self.current.take()?;
+        let summary = TakeSummary::from(&take);
+        self.takes.push(take);
+        Some(summary)
+    }
+
+    /// The finished takes in the order they were recorded.
+    pub fn list(&self) -> Vec<TakeSummary> {
+        self.takes.iter().map(Into::into).collect()
+    }
+
+    /// A finished take by name.
+    pub fn get(&self, name: &Name) -> Result<&Take> {
+        self.takes
+            .iter()
+            .find(|take| &take.name == name)
+            .ok_or_else(|| Error::UnknownTake(name.to_string()))
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+use crate::data::PropertyLink;
+use crate::{name, SceneObject};
+
+fn scene(focal_length: f64) -> Scene {
+    let mut scene = Scene::default();
+    scene.objects_mut().insert(
+        name!("camera"),
+        SceneObject::new(
+            name!("camera"),
+            HashMap::from([(
+                name!("focal_length"),
+                PropertyLink::unbound(focal_length.into()),
+            )]),
+        ),
+    );
+    scene
+}
+
+#[test]
+fn test_record_takes() {
+    let mut recorder = Recorder::default();
+    assert!(!recorder.is_recording());
+    // Nothing is captured outside of a take.
+    recorder.capture(&scene(35.0), 0);
+
+    assert_eq!(recorder.start(1_000), name!("take-1"));
+    assert!(recorder.is_recording());
+    recorder.capture(&scene(35.0), 1_000);
+    recorder.capture(&scene(50.0), 17_666);
+    assert!(recorder.list().is_empty());
+
+    let summary = recorder.finish().expect("a take was recording");
+    assert_eq!(
+        summary,
+        TakeSummary {
+            name: name!("take-1"),
+            started_at: 1_000,
+            duration_us: 16_666,
+            frames: 2,
+        }
+    );
+    assert!(!recorder.is_recording());
+    assert_eq!(recorder.finish(), None);
+
+    let take = recorder.get(&name!("take-1")).unwrap();
+    assert_eq!(take.frames[0].time, 0);
+    assert_eq!(
+        take.frames[1].objects[&name!("camera")][&name!("focal_length")],
+        50.0.into()
+    );
+
+    assert_eq!(recorder.start(20_000), name!("take-2"));
+    recorder.finish();
+    assert_eq!(recorder.list().len(), 2);
+    assert!(matches!(
+        recorder.get(&name!("take-3")),
+        Err(Error::UnknownTake(_))
+    ));
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "tls_test.rs"]
+mod tls_test;
+
+/// The file name of the persisted server certificate.
+pub const CERT_FILE: &str = "cert.pem";
+/// The file name of the persisted server private key.
+pub const KEY_FILE: &str = "key.pem";
+
+/// The directory cinemotion keeps its configuration and identity in.
+///
+/// `CINEMOTION_CONFIG_DIR` takes precedence, then `$XDG_CONFIG_HOME/cinemotion`
+/// and finally `$HOME/.config/cinemotion`.
+pub fn config_dir() -> Option<PathBuf> {
+    let from_env = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(dir) = from_env("CINEMOTION_CONFIG_DIR") {
+        return Some(dir.into());
+    }
+    if let Some(dir) = from_env("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(dir).join("cinemotion"));
+    }
+    from_env("HOME").map(|home| PathBuf::from(home).join(".config").join("cinemotion"))
+}
+
+/// The certificate and key the server presents to clients.
+#[derive(Clone)]
+pub struct Identity {
+    pub cert_chain: Vec<rustls::Certificate>,
+    pub key: rustls::PrivateKey,
+}
+
+impl Identity {
+    /// Load the identity persisted in the directory, or generate a new
+    /// self-signed one and persist it there.
+    ///
+    /// Keeping the identity across restarts keeps its fingerprint stable so
+    /// clients can pin it.
+    pub fn load_or_generate(dir: &Path) -> Result<Self> {
+        let (cert_path, key_path) = (dir.join(CERT_FILE), dir.join(KEY_FILE));
+        if cert_path.exists() && key_path.exists() {
+            return Self::from_pem_files(&cert_path, &key_path);
+        }
+
+        tracing::info!("generating a new server identity in {}", dir.display());
+        let cert = rcgen::generate_simple_self_signed(certificate_names())
+            .map_err(|err| Error::IdentityFailed(err.to_string()))?;
+        let cert_pem = cert
+            .serialize_pem()
+            .map_err(|err| Error::IdentityFailed(err.to_string()))?;
+        let key_pem = cert.serialize_private_key_pem();
+
+        fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+        write_private(&key_path, &key_pem)?;
+        fs::write(&cert_path, &cert_pem).map_err(|err| io_error(&cert_path, err))?;
+        Self::from_pem(cert_pem.as_bytes(), key_pem.as_bytes())
+    }
+
+    /// Load a user provided certificate chain and private key.
+    pub fn from_pem_files(cert_path: &Path, key_path: &Path) -> Result<Self> {
+        let cert_pem = fs::read(cert_path).map_err(|err| io_error(cert_path, err))?;
+        let key_pem = fs::read(key_path).map_err(|err| io_error(key_path, err))?;
+        Self::from_pem(&cert_pem, &key_pem)
+    }
+
+    /// Parse a PEM encoded certificate chain and private key.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+        let cert_chain: Vec<_> = rustls_pemfile::certs(&mut BufReader::new(cert_pem))
+            .map_err(|err| Error::IdentityFailed(err.to_string()))?
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect();
+        if cert_chain.is_empty() {
+            return Err(Error::IdentityFailed("no certificate found".into()));
+        }
+
+        let key = rustls_pemfile::read_all(&mut BufReader::new(key_pem))
+            .map_err(|err| Error::IdentityFailed(err.to_string()))?
+            .into_iter()
+            .find_map(|item| match item {
+                rustls_pemfile::Item::PKCS8Key(key)
+                | rustls_pemfile::Item::RSAKey(key)
+                | rustls_pemfile::Item::ECKey(key) => Some(rustls::PrivateKey(key)),
+                _ => None,
+            })
+            .ok_or_else(|| Error::IdentityFailed("no private key found".into()))?;
+
+        Ok(Self { cert_chain, key })
+    }
+
+    /// The fingerprint of the leaf certificate that clients pin.
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.cert_chain[0])
+    }
+}
+
+/// The SHA-256 fingerprint of a certificate as colon separated hex bytes.
+pub fn fingerprint(cert: &rustls::Certificate) -> String {
+    Sha256::digest(&cert.0)
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Verifies the server by the fingerprint of its certificate instead of a
+/// certificate authority, for clients that pinned an advertised fingerprint.
+pub struct PinnedCertVerifier {
+    fingerprint: String,
+}
+
+impl PinnedCertVerifier {
+    pub fn new(fingerprint: impl Into<String>) -> Self {
+        Self {
+            fingerprint: fingerprint.into(),
+        }
+    }
+}
+
+impl rustls::client::ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> std::result::Result<rustls::client::ServerCertVerified, rustls::Error> {
+        match fingerprint(end_entity).eq_ignore_ascii_case(&self.fingerprint) {
+            true => Ok(rustls::client::ServerCertVerified::assertion()),
+            false => Err(rustls::Error::General(
+                "server certificate does not match the pinned fingerprint".into(),
+            )),
+        }
+    }
+}
+
+/// The names a generated certificate is issued for.
+///
+/// Clients verify the server by fingerprint, so the names going stale when
+/// the addresses of the host change does not invalidate a persisted identity.
+fn certificate_names() -> Vec<String> {
+    let mut names = vec!["0.0.0.0".into(), "127.0.0.1".into(), "localhost".into()];
+    if let Ok(name) = hostname::get() {
+        if let Ok(name) = name.into_string() {
+            names.push(name);
+        }
+    }
+    match get_if_addrs::get_if_addrs() {
+        Ok(ifaces) => {
+            for iface in ifaces {
+                // Check if the IP is v4 and the interface is not a loopback
+                if !iface.is_loopback() && iface.ip().is_ipv4() {
+                    names.push(iface.ip().to_string());
+                }
+            }
+        }
+        Err(err) => tracing::warn!(%err, "failed to list the network interfaces"),
+    }
+    names
+}
+
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|err| io_error(path, err))
+    }
+    #[cfg(not(unix))]
+    fs::write(path, contents).map_err(|err| io_error(path, err))
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::IdentityFailed(format!("{}: {err}", path.display()))
+}
@@ -0,0 +1,55 @@
+use super::*;
+use rustls::client::ServerCertVerifier;
+
+#[test]
+fn test_identity_is_persisted() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().join("identity");
+
+    let first = Identity::load_or_generate(&dir).expect("identity should be generated");
+    assert!(dir.join(CERT_FILE).exists());
+    assert!(dir.join(KEY_FILE).exists());
+    let second = Identity::load_or_generate(&dir).expect("identity should be loaded");
+    assert_eq!(first.fingerprint(), second.fingerprint());
+    assert_eq!(first.key, second.key);
+}
+
+#[test]
+fn test_from_pem_requires_a_key() {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let cert_pem = cert.serialize_pem().unwrap();
+    assert!(matches!(
+        Identity::from_pem(cert_pem.as_bytes(), b""),
+        Err(Error::IdentityFailed(_))
+    ));
+    assert!(matches!(
+        Identity::from_pem(b"", cert.serialize_private_key_pem().as_bytes()),
+        Err(Error::IdentityFailed(_))
+    ));
+}
+
+#[test]
+fn test_pinned_cert_verifier() {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let identity = Identity::from_pem(
+        cert.serialize_pem().unwrap().as_bytes(),
+        cert.serialize_private_key_pem().as_bytes(),
+    )
+    .unwrap();
+    let fingerprint = identity.fingerprint();
+    assert_eq!(fingerprint.len(), 32 * 3 - 1);
+
+    let server_name = rustls::ServerName::try_from("localhost").unwrap();
+    let verify = |verifier: PinnedCertVerifier| {
+        verifier.verify_server_cert(
+            &identity.cert_chain[0],
+            &[],
+            &server_name,
+            &mut std::iter::empty(),
+            &[],
+            std::time::SystemTime::now(),
+        )
+    };
+    assert!(verify(PinnedCertVerifier::new(fingerprint.to_lowercase())).is_ok());
+    assert!(verify(PinnedCertVerifier::new("00:11")).is_err());
+}
@@ -10,20 +10,24 @@ use async_trait::async_trait;
 use futures::lock::Mutex;
 use std::sync::Arc;
 use webrtc::{
-    api::{media_engine::MediaEngine, APIBuilder},
     data_channel::{data_channel_init::RTCDataChannelInit, RTCDataChannel},
+    ice_transport::ice_candidate::RTCIceCandidate,
     peer_connection::{
-        configuration::RTCConfiguration, peer_connection_state::RTCPeerConnectionState,
+        peer_connection_state::RTCPeerConnectionState,
         sdp::session_description::RTCSessionDescription, RTCPeerConnection,
     },
 };
 
 use crate::connection::ConnectionAgent;
 
+use super::sdpfrag::Fragment;
+use super::WebRTCOptions;
+
 pub struct WebRTCAgent {
     peer_connection: Arc<RTCPeerConnection>,
     send_handler: Arc<ArcSwapOption<Mutex<SendHandlerFn>>>,
     main_channel: Option<Arc<RTCDataChannel>>,
+    local_candidates: Arc<std::sync::Mutex<Fragment>>,
 }
 
 impl WebRTCAgent {
@@ -33,15 +37,23 @@ impl WebRTCAgent {
     pub async fn new(
         desc: WebRTCSessionDescriptor,
         message_pipe: MessagePipeTx,
+        options: &WebRTCOptions,
     ) -> Result<(WebRTCSessionDescriptor, Self)> {
-        let m = MediaEngine::default();
-        let api = APIBuilder::new().with_media_engine(m).build();
-
-        let config = RTCConfiguration {
-            ..Default::default()
-        };
+        let api = options.api()?;
+        let peer_connection = Arc::new(api.new_peer_connection(options.configuration()).await?);
 
-        let peer_connection = Arc::new(api.new_peer_connection(config).await?);
+        // Record the gathered candidates so they can be trickled to the peer.
+        let local_candidates: Arc<std::sync::Mutex<Fragment>> = Default::default();
+        let shared_candidates = Arc::clone(&local_candidates);
+        peer_connection.on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+            let mut candidates = shared_candidates.lock().unwrap();
+            match candidate.map(|candidate| candidate.to_json()) {
+                Some(Ok(candidate)) => candidates.candidates.push(candidate),
+                Some(Err(err)) => tracing::error!("failed to encode local candidate. err={err}"),
+                None => candidates.end_of_candidates = true,
+            }
+            Box::pin(async {})
+        }));
 
         let offer = RTCSessionDescription::offer(desc.decode()?)?;
 
@@ -57,9 +69,11 @@ impl WebRTCAgent {
         // Sets the LocalDescription, and starts our UDP listeners
         peer_connection.set_local_description(answer).await?;
 
-        // Block until ICE Gathering is complete, disabling trickle ICE
-        // we do this because we only can exchange one signaling message
-        let _ = gather_complete.recv().await;
+        // Unless the peer trickles candidates, block until ICE gathering is
+        // complete so the answer carries all of them.
+        if !desc.trickle {
+            let _ = gather_complete.recv().await;
+        }
 
         let local_desc = match peer_connection.local_description().await {
             Some(desc) => WebRTCSessionDescriptor::new(&desc.sdp),
@@ -76,9 +90,20 @@ impl WebRTCAgent {
                 peer_connection,
                 send_handler: Default::default(),
                 main_channel: None,
+                local_candidates,
             },
         ))
     }
+
+    /// The underlying peer connection.
+    pub fn peer_connection(&self) -> Arc<RTCPeerConnection> {
+        Arc::clone(&self.peer_connection)
+    }
+
+    /// The local candidates gathered so far.
+    pub fn local_candidates(&self) -> Arc<std::sync::Mutex<Fragment>> {
+        Arc::clone(&self.local_candidates)
+    }
 }
 
 #[async_trait]
@@ -111,7 +136,7 @@ impl ConnectionAgent for WebRTCAgent {
                 if let Some(handler) = &*shared_send_fn.load() {
                     let mut f = handler.lock().await;
                     let init = messages::OpenConnection {};
-                    let _ = f(init.into());
+                    let _ = f(init.into(), None);
                 }
             })
         }));
@@ -126,7 +151,7 @@ impl ConnectionAgent for WebRTCAgent {
                 tracing::debug!("agent data channel closed");
                 if let Some(handler) = &*shared_send_fn.load() {
                     let mut f = handler.lock().await;
-                    let _ = f(command.into());
+                    let _ = f(command.into(), None);
                 }
             })
         }));
@@ -139,11 +164,23 @@ impl ConnectionAgent for WebRTCAgent {
             let shared_send_fn = Arc::clone(&shared_send_fn);
             let shared_channel = Arc::clone(&shared_channel);
             Box::pin(async move {
-                let command = match Payload::from_protobuf_bytes(msg.data) {
+                let (command, request_id) = match cinemotion_proto::Command::try_from(msg.data) {
+                    Ok(command) => {
+                        let request_id = command.request_id;
+                        (Payload::from_protobuf(command), request_id)
+                    }
+                    Err(err) => (
+                        Err(Error::BadCommand(format!(
+                            "failed to decode command: {err}"
+                        ))),
+                        None,
+                    ),
+                };
+                let command = match command {
                     Ok(command) => command,
                     Err(err) => {
                         tracing::error!("failed to decode command. err={err}");
-                        let error = make_error_event(err);
+                        let error = make_error_event(err, request_id);
                         convert_and_send(&shared_channel, error).await;
                         return;
                     }
@@ -151,7 +188,7 @@ impl ConnectionAgent for WebRTCAgent {
 
                 if let Some(handler) = &*shared_send_fn.load() {
                     let mut f = handler.lock().await;
-                    let _ = f(command);
+                    let _ = f(command, request_id);
                 }
             })
         }));
@@ -198,10 +235,11 @@ impl ConnectionAgent for WebRTCAgent {
     }
 }
 
-fn make_error_event(err: Error) -> crate::Event {
+fn make_error_event(err: Error, request_id: Option<u64>) -> crate::Event {
+    let error = crate::events::ErrorEvent::from(err);
     crate::Event {
         target: Some(0),
-        body: crate::events::ErrorEvent(err).into(),
+        body: crate::events::EventBody::rejection(error, request_id),
     }
 }
 
@@ -1,5 +1,8 @@
 pub mod agent;
+pub mod options;
+pub mod sdpfrag;
 pub mod signaling;
 
 pub use agent::WebRTCAgent;
+pub use options::WebRTCOptions;
 pub use signaling::SignalingRelay;
@@ -0,0 +1,176 @@
+//! The network settings of the WebRTC peer connections.
+
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use webrtc::api::media_engine::MediaEngine;
+use webrtc::api::setting_engine::SettingEngine;
+use webrtc::api::{APIBuilder, API};
+use webrtc::ice::udp_network::{EphemeralUDP, UDPNetwork};
+use webrtc::ice::url::{SchemeType, Url};
+use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "options_test.rs"]
+mod options_test;
+
+/// How peer connections gather candidates and which addresses they offer.
+///
+/// The defaults gather host candidates on every interface and any UDP port,
+/// which only works while the peers share a network.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebRTCOptions {
+    /// The STUN and TURN servers used to gather reflexive and relayed candidates.
+    pub ice_servers: Vec<IceServer>,
+    /// The UDP ports candidates are bound to, any ephemeral port when not set.
+    pub udp_ports: Option<PortRange>,
+    /// The names of the network interfaces to gather candidates on, all of
+    /// them when empty.
+    pub interfaces: Vec<String>,
+    /// The public addresses of a 1:1 NAT, offered in place of the host addresses.
+    pub nat_ips: Vec<IpAddr>,
+}
+
+/// A STUN or TURN server.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IceServer {
+    /// URLs like `stun:stun.example.com:3478` or `turn:turn.example.com?transport=udp`.
+    pub urls: Vec<String>,
+    /// The username of a TURN server.
+    pub username: String,
+    /// The password of a TURN server.
+    pub credential: String,
+}
+
+/// An inclusive range of ports, written as `<min>-<max>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PortRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl WebRTCOptions {
+    /// Check the options before any peer connection uses them.
+    pub fn validate(&self) -> Result<()> {
+        for server in &self.ice_servers {
+            server.validate()?;
+        }
+        if self.interfaces.iter().any(String::is_empty) {
+            return Err(Error::WebRTCError(
+                "network interface names must not be empty".to_string(),
+            ));
+        }
+        self.api().map(|_| ())
+    }
+
+    /// The API peer connections are created with.
+    pub fn api(&self) -> Result<API> {
+        let mut settings = SettingEngine::default();
+        if let Some(ports) = self.udp_ports {
+            let ports = EphemeralUDP::new(ports.min, ports.max)
+                .map_err(|err| Error::WebRTCError(format!("invalid udp ports {ports}: {err}")))?;
+            settings.set_udp_network(UDPNetwork::Ephemeral(ports));
+        }
+        if !self.interfaces.is_empty() {
+            let interfaces = self.interfaces.clone();
+            settings.set_interface_filter(Box::new(move |name: &str| {
+                interfaces.iter().any(|interface| interface == name)
+            }));
+        }
+        if !self.nat_ips.is_empty() {
+            settings.set_nat_1to1_ips(
+                self.nat_ips.iter().map(ToString::to_string).collect(),
+                RTCIceCandidateType::Host,
+            );
+        }
+        Ok(APIBuilder::new()
+            .with_media_engine(MediaEngine::default())
+            .with_setting_engine(settings)
+            .build())
+    }
+
+    /// The configuration of each peer connection.
+    pub fn configuration(&self) -> RTCConfiguration {
+        RTCConfiguration {
+            ice_servers: self.ice_servers.iter().map(IceServer::to_rtc).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl IceServer {
+    /// A server that needs no credentials, like a STUN server.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            urls: vec![url.into()],
+            ..Default::default()
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.urls.is_empty() {
+            return Err(Error::WebRTCError(
+                "ice servers need at least one url".to_string(),
+            ));
+        }
+        for url in &self.urls {
+            let parsed = Url::parse_url(url).map_err(|err| {
+                Error::WebRTCError(format!("invalid ice server url {url}: {err}"))
+            })?;
+            let turn = matches!(parsed.scheme, SchemeType::Turn | SchemeType::Turns);
+            if turn && (self.username.is_empty() || self.credential.is_empty()) {
+                return Err(Error::WebRTCError(format!(
+                    "the turn server {url} needs a username and credential"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn to_rtc(&self) -> RTCIceServer {
+        RTCIceServer {
+            urls: self.urls.clone(),
+            username: self.username.clone(),
+            credential: self.credential.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid port range {s:?}, expected <min>-<max>");
+        let (min, max) = s.split_once('-').ok_or_else(invalid)?;
+        let min: u16 = min.trim().parse().map_err(|_| invalid())?;
+        let max: u16 = max.trim().parse().map_err(|_| invalid())?;
+        if min == 0 || min > max {
+            return Err(invalid());
+        }
+        Ok(Self { min, max })
+    }
+}
+
+impl TryFrom<String> for PortRange {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.min, self.max)
+    }
+}
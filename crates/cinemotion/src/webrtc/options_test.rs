@@ -0,0 +1,135 @@
+use super::*;
+
+/// Gather the candidates of an offer made with the options, as `(address, port)`.
+async fn gather(options: &WebRTCOptions) -> Vec<(String, u16)> {
+    let peer = options
+        .api()
+        .unwrap()
+        .new_peer_connection(options.configuration())
+        .await
+        .unwrap();
+    let _channel = peer.create_data_channel("data", None).await.unwrap();
+    let offer = peer.create_offer(None).await.unwrap();
+    let mut gather_complete = peer.gathering_complete_promise().await;
+    peer.set_local_description(offer).await.unwrap();
+    let _ = gather_complete.recv().await;
+    let sdp = peer.local_description().await.unwrap().sdp;
+    peer.close().await.unwrap();
+
+    sdp.lines()
+        .filter_map(|line| line.strip_prefix("a=candidate:"))
+        .map(|candidate| {
+            let fields: Vec<&str> = candidate.split_whitespace().collect();
+            (fields[4].to_string(), fields[5].parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_port_range() {
+    assert_eq!(
+        "50000-50100".parse(),
+        Ok(PortRange {
+            min: 50000,
+            max: 50100
+        })
+    );
+    assert_eq!(
+        "50000-50000".parse::<PortRange>().unwrap().to_string(),
+        "50000-50000"
+    );
+    assert!("50000".parse::<PortRange>().is_err());
+    assert!("50100-50000".parse::<PortRange>().is_err());
+    assert!("0-100".parse::<PortRange>().is_err());
+    assert!("a-b".parse::<PortRange>().is_err());
+}
+
+#[test]
+fn test_validate() {
+    assert!(WebRTCOptions::default().validate().is_ok());
+
+    let with_server = |server: IceServer| WebRTCOptions {
+        ice_servers: vec![server],
+        ..Default::default()
+    };
+    assert!(with_server(IceServer::new("stun:stun.example.com:3478"))
+        .validate()
+        .is_ok());
+    assert!(with_server(IceServer::new("http://stun.example.com"))
+        .validate()
+        .is_err());
+    assert!(with_server(IceServer::default()).validate().is_err());
+    assert!(with_server(IceServer::new("turn:turn.example.com"))
+        .validate()
+        .is_err());
+    assert!(with_server(IceServer {
+        urls: vec!["turn:turn.example.com".into()],
+        username: "stage".into(),
+        credential: "secret".into(),
+    })
+    .validate()
+    .is_ok());
+
+    let empty_interface = WebRTCOptions {
+        interfaces: vec![String::new()],
+        ..Default::default()
+    };
+    assert!(empty_interface.validate().is_err());
+}
+
+#[test]
+fn test_configuration() {
+    let options = WebRTCOptions {
+        ice_servers: vec![IceServer::new("stun:stun.example.com:3478")],
+        ..Default::default()
+    };
+    let config = options.configuration();
+    assert_eq!(config.ice_servers.len(), 1);
+    assert_eq!(
+        config.ice_servers[0].urls,
+        vec!["stun:stun.example.com:3478"]
+    );
+}
+
+#[tokio::test]
+async fn test_udp_ports() {
+    let options = WebRTCOptions {
+        udp_ports: Some(PortRange {
+            min: 52000,
+            max: 52100,
+        }),
+        ..Default::default()
+    };
+    let candidates = gather(&options).await;
+    assert!(!candidates.is_empty());
+    for (_, port) in candidates {
+        assert!((52000..=52100).contains(&port), "port {port} out of range");
+    }
+}
+
+#[tokio::test]
+async fn test_interfaces() {
+    let options = WebRTCOptions {
+        interfaces: vec!["no-such-interface".into()],
+        ..Default::default()
+    };
+    assert!(gather(&options).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_nat_ips() {
+    let options = WebRTCOptions {
+        nat_ips: vec!["203.0.113.7".parse().unwrap()],
+        ..Default::default()
+    };
+    // The mapping only replaces addresses of the same family.
+    let candidates: Vec<_> = gather(&options)
+        .await
+        .into_iter()
+        .filter(|(address, _)| address.parse::<std::net::Ipv4Addr>().is_ok())
+        .collect();
+    assert!(!candidates.is_empty());
+    for (address, _) in candidates {
+        assert_eq!(address, "203.0.113.7");
+    }
+}
@@ -0,0 +1,75 @@
+//! Reading and writing the `application/trickle-ice-sdpfrag` bodies
+//! (RFC 8840) that carry trickled ICE candidates.
+
+use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
+
+use crate::{Error, Result};
+
+#[cfg(test)]
+#[path = "sdpfrag_test.rs"]
+mod sdpfrag_test;
+
+/// The media type of trickle ICE bodies.
+pub const CONTENT_TYPE: &str = "application/trickle-ice-sdpfrag";
+
+/// The candidates of an SDP fragment.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Fragment {
+    pub candidates: Vec<RTCIceCandidateInit>,
+    /// Whether the sender gathered all of its candidates.
+    pub end_of_candidates: bool,
+}
+
+impl Fragment {
+    /// Parse the candidates of a fragment, attributing them to the media
+    /// section they follow.
+    pub fn parse(frag: &str) -> Result<Self> {
+        let mut fragment = Self::default();
+        let mut mid = None;
+        let mut mline_index: Option<u16> = None;
+        for line in frag.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with("m=") {
+                mline_index = Some(mline_index.map_or(0, |index| index + 1));
+                mid = None;
+            } else if let Some(value) = line.strip_prefix("a=mid:") {
+                mid = Some(value.to_string());
+            } else if let Some(candidate) = line.strip_prefix("a=candidate:") {
+                fragment.candidates.push(RTCIceCandidateInit {
+                    candidate: format!("candidate:{candidate}"),
+                    sdp_mid: mid.clone(),
+                    sdp_mline_index: mline_index,
+                    username_fragment: None,
+                });
+            } else if line == "a=end-of-candidates" {
+                fragment.end_of_candidates = true;
+            } else if !(line.starts_with("a=") || line.starts_with("m=")) {
+                return Err(Error::BadRTCDescriptor(format!(
+                    "invalid sdp fragment line: {line}"
+                )));
+            }
+        }
+        Ok(fragment)
+    }
+
+    /// Write the fragment with the candidates grouped by media section.
+    pub fn to_sdp(&self) -> String {
+        let mut sdp = String::new();
+        let mut mid = None;
+        for candidate in &self.candidates {
+            if candidate.sdp_mid.is_some() && candidate.sdp_mid != mid {
+                mid = candidate.sdp_mid.clone();
+                sdp.push_str("m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n");
+                sdp.push_str(&format!("a=mid:{}\r\n", mid.as_deref().unwrap_or_default()));
+            }
+            let value = candidate
+                .candidate
+                .strip_prefix("candidate:")
+                .unwrap_or(&candidate.candidate);
+            sdp.push_str(&format!("a=candidate:{value}\r\n"));
+        }
+        if self.end_of_candidates {
+            sdp.push_str("a=end-of-candidates\r\n");
+        }
+        sdp
+    }
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+const CANDIDATE: &str = "1 1 udp 2130706431 192.168.1.2 50000 typ host";
+
+#[test]
+fn test_parse() {
+    let frag = format!(
+        "a=ice-ufrag:abcd\r\na=ice-pwd:secret\r\nm=audio 9 RTP/AVP 0\r\na=mid:0\r\nm=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\na=mid:1\r\na=candidate:{CANDIDATE}\r\na=end-of-candidates\r\n"
+    );
+    let fragment = Fragment::parse(&frag).unwrap();
+    assert!(fragment.end_of_candidates);
+    assert_eq!(
+        fragment.candidates,
+        vec![RTCIceCandidateInit {
+            candidate: format!("candidate:{CANDIDATE}"),
+            sdp_mid: Some("1".into()),
+            sdp_mline_index: Some(1),
+            username_fragment: None,
+        }]
+    );
+}
+
+#[test]
+fn test_parse_rejects_garbage() {
+    assert!(Fragment::parse("not sdp").is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    let fragment = Fragment {
+        candidates: vec![RTCIceCandidateInit {
+            candidate: format!("candidate:{CANDIDATE}"),
+            sdp_mid: Some("0".into()),
+            sdp_mline_index: Some(0),
+            username_fragment: None,
+        }],
+        end_of_candidates: true,
+    };
+    assert_eq!(Fragment::parse(&fragment.to_sdp()).unwrap(), fragment);
+}
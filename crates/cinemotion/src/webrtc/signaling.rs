@@ -1,32 +1,89 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+use rand::Rng;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::RTCPeerConnection;
+
+use crate::auth::Authenticator;
 use crate::connection::LOCAL_CONN_ID;
-use crate::messages::{AddConnection, Message, MessagePipeTx};
+use crate::messages::{AddConnection, CloseConnection, Message, MessagePipeTx};
 use crate::{Error, Result};
 
 use crate::data::WebRTCSessionDescriptor;
 
-use super::WebRTCAgent;
+use super::sdpfrag::Fragment;
+use super::{WebRTCAgent, WebRTCOptions};
+
+#[cfg(test)]
+#[path = "signaling_test.rs"]
+mod signaling_test;
+
+/// A signaling session, addressed by its id for trickling candidates and
+/// leaving.
+struct Session {
+    connection_id: usize,
+    peer_connection: Arc<RTCPeerConnection>,
+    local_candidates: Arc<Mutex<Fragment>>,
+    /// How many local candidates the peer was sent so far.
+    sent_candidates: usize,
+    /// Whether the peer was told that all local candidates were sent.
+    sent_end_of_candidates: bool,
+}
 
 pub struct SignalingRelay {
     sender: MessagePipeTx,
+    auth: Arc<Authenticator>,
+    options: WebRTCOptions,
+    sessions: Mutex<HashMap<String, Session>>,
 }
 
 impl SignalingRelay {
     pub fn new(sender: MessagePipeTx) -> Self {
-        SignalingRelay { sender }
+        SignalingRelay {
+            sender,
+            auth: Arc::new(Authenticator::disabled()),
+            options: Default::default(),
+            sessions: Default::default(),
+        }
     }
 
+    /// Require peers to present a credential accepted by the authenticator.
+    pub fn with_authenticator(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Set the network settings of the peer connections.
+    pub fn with_options(mut self, options: WebRTCOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Answer the offer of a peer and add its connection to the runtime.
+    ///
+    /// The answer carries the id of the session for `trickle` and `delete`. The
+    /// address of the peer, when known, limits how often it may present a wrong
+    /// credential.
     pub async fn create(
         &self,
         session_desc: WebRTCSessionDescriptor,
+        peer: Option<IpAddr>,
     ) -> Result<WebRTCSessionDescriptor> {
+        let role = self.auth.verify_peer(peer, session_desc.token.as_deref())?;
         let (ack_pipe, ack_pipe_rx) = tokio::sync::oneshot::channel();
 
-        let (remote_desc, session) = WebRTCAgent::new(session_desc, self.sender.clone()).await?;
+        let (mut remote_desc, session) =
+            WebRTCAgent::new(session_desc, self.sender.clone(), &self.options).await?;
+        let peer_connection = session.peer_connection();
+        let local_candidates = session.local_candidates();
         let session = Box::new(session);
         let message = Message::with_command(
             LOCAL_CONN_ID,
             AddConnection {
                 agent: session,
+                role,
                 ack_pipe,
             },
         );
@@ -38,18 +95,101 @@ impl SignalingRelay {
         }
 
         match ack_pipe_rx.await {
-            Ok(result) => {
-                if let Err(err) = result {
-                    tracing::error!("failed to complete signaling message with: {err}");
-                    return Err(Error::SignalingFailed(format!(
-                        "runtime responded to message with error, {err}"
-                    )));
-                }
+            Ok(Ok(connection_id)) => {
+                let id = session_id();
+                let mut sessions = self.sessions.lock().unwrap();
+                prune(&mut sessions);
+                sessions.insert(
+                    id.clone(),
+                    Session {
+                        connection_id,
+                        peer_connection,
+                        local_candidates,
+                        sent_candidates: 0,
+                        sent_end_of_candidates: false,
+                    },
+                );
+                remote_desc.session_id = Some(id);
                 Ok(remote_desc)
             }
+            Ok(Err(err)) => {
+                tracing::error!("failed to complete signaling message with: {err}");
+                Err(Error::SignalingFailed(format!(
+                    "runtime responded to message with error, {err}"
+                )))
+            }
             Err(_) => Err(Error::SignalingFailed(
                 "lost connection to runtime while setting up session.".to_string(),
             )),
         }
     }
+
+    /// Add the candidates the peer trickled and return the local candidates
+    /// gathered since the last call, if there are any.
+    pub async fn trickle(&self, id: &str, frag: &str) -> Result<Option<Fragment>> {
+        let remote = Fragment::parse(frag)?;
+        let peer_connection = {
+            let mut sessions = self.sessions.lock().unwrap();
+            prune(&mut sessions);
+            let session = sessions
+                .get(id)
+                .ok_or_else(|| Error::UnknownSession(id.to_string()))?;
+            Arc::clone(&session.peer_connection)
+        };
+        for candidate in remote.candidates {
+            peer_connection
+                .add_ice_candidate(candidate)
+                .await
+                .map_err(|err| Error::BadRTCDescriptor(format!("invalid candidate: {err}")))?;
+        }
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Error::UnknownSession(id.to_string()))?;
+        let local = session.local_candidates.lock().unwrap().clone();
+        let fresh = Fragment {
+            candidates: local.candidates[session.sent_candidates..].to_vec(),
+            end_of_candidates: local.end_of_candidates && !session.sent_end_of_candidates,
+        };
+        if fresh.candidates.is_empty() && !fresh.end_of_candidates {
+            return Ok(None);
+        }
+        session.sent_candidates = local.candidates.len();
+        session.sent_end_of_candidates = local.end_of_candidates;
+        Ok(Some(fresh))
+    }
+
+    /// Close the connection of a session.
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| Error::UnknownSession(id.to_string()))?;
+        let message = Message::with_command(session.connection_id, CloseConnection {});
+        if self.sender.send(message).is_err() {
+            tracing::warn!("runtime is gone, closing the peer connection directly");
+        }
+        session.peer_connection.close().await?;
+        Ok(())
+    }
+}
+
+/// Forget the sessions of peers that left without deleting them.
+fn prune(sessions: &mut HashMap<String, Session>) {
+    sessions.retain(|_, session| {
+        !matches!(
+            session.peer_connection.connection_state(),
+            RTCPeerConnectionState::Closed | RTCPeerConnectionState::Failed
+        )
+    });
+}
+
+/// A random id that is hard to guess, since it is all a peer needs to
+/// address its session.
+fn session_id() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
 }
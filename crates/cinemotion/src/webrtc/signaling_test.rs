@@ -0,0 +1,23 @@
+use super::*;
+use crate::messages::message_pipe;
+
+#[tokio::test]
+async fn test_unknown_session() {
+    let (sender, _receiver) = message_pipe();
+    let relay = SignalingRelay::new(sender);
+    assert!(matches!(
+        relay.trickle("missing", "").await,
+        Err(Error::UnknownSession(_))
+    ));
+    assert!(matches!(
+        relay.delete("missing").await,
+        Err(Error::UnknownSession(_))
+    ));
+}
+
+#[test]
+fn test_session_ids_are_unique() {
+    let id = session_id();
+    assert_eq!(id.len(), 32);
+    assert_ne!(id, session_id());
+}
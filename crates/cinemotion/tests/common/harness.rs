@@ -91,7 +91,7 @@ pub struct Task {
 }
 
 pub enum Action {
-    Message(messages::Message),
+    Message(Box<messages::Message>),
     ExpectEvents(Vec<Event>),
     ExpectEvent(Box<dyn FnMut(&Event) -> bool>),
     ExpectState(Box<dyn FnMut(&mut State)>),
@@ -110,7 +110,7 @@ impl Debug for Action {
 
 impl From<messages::Message> for Action {
     fn from(message: messages::Message) -> Self {
-        Action::Message(message)
+        Action::Message(Box::new(message))
     }
 }
 
@@ -119,7 +119,7 @@ macro_rules! message {
     ($description:expr, $message:expr) => {
         Task {
             description: $description.to_string(),
-            action: Action::Message($message.into()),
+            action: Action::Message(Box::new($message.into())),
         }
     };
 }
@@ -159,7 +159,7 @@ pub async fn run_harness(harness: &mut EngineTestHarness, tasks: Vec<Task>) {
         println!("⏵ {}", task.description);
         match task.action {
             Action::Message(message) => harness
-                .send_message(message)
+                .send_message(*message)
                 .await
                 .expect("message should not fail to send"),
             Action::ExpectEvents(expected_events) => {
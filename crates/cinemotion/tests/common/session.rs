@@ -29,6 +29,7 @@ impl FakeSessionComponent {
             context: connection::Context {
                 uid: 1,
                 name: Some(name!("test")),
+                ..Default::default()
             },
             spy: Arc::new(Mutex::new(SpySessionComponent {
                 create_session_called: false,
@@ -55,6 +56,10 @@ impl NetworkComponent for FakeSessionComponent {
         Some(&self.context)
     }
 
+    fn contexts(&self) -> Vec<&connection::Context> {
+        vec![&self.context]
+    }
+
     async fn add_connection(&mut self, options: AddConnection) -> Result<()> {
         let mut spy = self.spy.lock().unwrap();
         spy.create_session_called = true;
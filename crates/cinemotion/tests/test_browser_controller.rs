@@ -0,0 +1,239 @@
+//! Joins a session the way the browser controller page does, with a native
+//! WebRTC peer standing in for the browser.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cinemotion::auth::Authenticator;
+use cinemotion::connection::Role;
+use cinemotion::data::{Value, WebRTCSessionDescriptor};
+use cinemotion::messages::{self, ClientCommand};
+use cinemotion::services::api::RestApi;
+use cinemotion::services::runtime::{RuntimeOptions, RuntimeService, DEFAULT_TICK_INTERVAL};
+use cinemotion::webrtc::SignalingRelay;
+use cinemotion::{data, events, name};
+use cinemotion_proto as proto;
+use webrtc::api::APIBuilder;
+use webrtc::data_channel::RTCDataChannel;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn vec3(x: f64, y: f64, z: f64) -> proto::PropertyValue {
+    proto::PropertyValue {
+        value: Some(proto::property_value::Value::Vec3Value(proto::Vec3 {
+            x,
+            y,
+            z,
+        })),
+    }
+}
+
+fn encode(payload: proto::command::Payload) -> bytes::Bytes {
+    proto::Command {
+        payload: Some(payload),
+        request_id: None,
+    }
+    .try_into()
+    .unwrap()
+}
+
+/// The init command of the page, see `PROPERTIES` and `CAPABILITIES` in `controller.js`.
+fn init_command() -> bytes::Bytes {
+    let schema = |unit: &str, semantic: &str| proto::PropertySchema {
+        range: None,
+        unit: unit.into(),
+        semantic: semantic.into(),
+    };
+    encode(proto::command::Payload::Init(proto::InitCommand {
+        controller: Some(proto::ControllerDef {
+            name: "phone".into(),
+            properties: HashMap::from([
+                ("orientation".into(), vec3(0.0, 0.0, 0.0)),
+                ("acceleration".into(), vec3(0.0, 0.0, 0.0)),
+            ]),
+            schemas: HashMap::from([
+                ("orientation".into(), schema("deg", "rotation")),
+                ("acceleration".into(), schema("m/s2", "acceleration")),
+            ]),
+            device_id: "device-1".into(),
+        }),
+        protocol_version: 1,
+        capabilities: vec!["value:vec3".into()],
+    }))
+}
+
+fn sample_command() -> bytes::Bytes {
+    encode(proto::command::Payload::SendSample(proto::SendSample {
+        sample: Some(proto::Sample {
+            properties: HashMap::from([("orientation".into(), vec3(90.0, 45.0, -10.0))]),
+            timestamp: 0,
+        }),
+    }))
+}
+
+#[tokio::test]
+async fn test_browser_controller_session() {
+    let (sender, receiver) = messages::message_pipe();
+    let _runtime = RuntimeService::new(RuntimeOptions {
+        message_pipe: (sender.clone(), receiver),
+        offline_policy: Default::default(),
+        heartbeat: Default::default(),
+        tick_interval: DEFAULT_TICK_INTERVAL,
+        event_pipe: events::event_pipe(),
+        scene_store: Default::default(),
+        initial_scene: None,
+    });
+    let auth = Arc::new(Authenticator::with_tokens(vec![(
+        "secret".to_string(),
+        Role::Controller,
+    )]));
+    let relay = SignalingRelay::new(sender.clone()).with_authenticator(Arc::clone(&auth));
+    let api = RestApi::new(sender, auth);
+
+    // Like the page, offer a bootstrap channel and wait for the server's main channel.
+    let peer = Arc::new(
+        APIBuilder::new()
+            .build()
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+            .unwrap(),
+    );
+    let _bootstrap = peer.create_data_channel("bootstrap", None).await.unwrap();
+    let (channel_tx, channel_rx) = tokio::sync::oneshot::channel::<Arc<RTCDataChannel>>();
+    let channel_tx = Arc::new(Mutex::new(Some(channel_tx)));
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel::<proto::Event>();
+    peer.on_data_channel(Box::new(move |channel: Arc<RTCDataChannel>| {
+        if channel.label() == "main" {
+            let event_tx = event_tx.clone();
+            channel.on_message(Box::new(move |message| {
+                if let Ok(event) = proto::Event::try_from(message.data) {
+                    let _ = event_tx.send(event);
+                }
+                Box::pin(async {})
+            }));
+            if let Some(channel_tx) = channel_tx.lock().unwrap().take() {
+                let _ = channel_tx.send(channel);
+            }
+        }
+        Box::pin(async {})
+    }));
+
+    let offer = peer.create_offer(None).await.unwrap();
+    let mut gather_complete = peer.gathering_complete_promise().await;
+    peer.set_local_description(offer).await.unwrap();
+    let _ = gather_complete.recv().await;
+    let local = peer.local_description().await.unwrap();
+
+    let rejected = relay
+        .create(
+            WebRTCSessionDescriptor::new(&local.sdp).with_token("wrong"),
+            None,
+        )
+        .await;
+    assert!(matches!(rejected, Err(cinemotion::Error::Unauthorized(_))));
+
+    let answer = relay
+        .create(
+            WebRTCSessionDescriptor::new(&local.sdp).with_token("secret"),
+            None,
+        )
+        .await
+        .unwrap();
+    peer.set_remote_description(RTCSessionDescription::answer(answer.decode().unwrap()).unwrap())
+        .await
+        .unwrap();
+
+    let channel = tokio::time::timeout(TIMEOUT, channel_rx)
+        .await
+        .expect("the main channel was not opened")
+        .unwrap();
+    let opened = tokio::time::timeout(TIMEOUT, async {
+        while let Some(event) = event_rx.recv().await {
+            if let Some(proto::event::Payload::ConnectionOpened(opened)) = event.payload {
+                return opened;
+            }
+        }
+        panic!("the event channel closed");
+    })
+    .await
+    .expect("the connection was not opened");
+    assert_eq!(opened.server_version, cinemotion::VERSION);
+    assert!(opened.protocol_versions.contains(&1));
+    assert!(opened.capabilities.contains(&"value:vec3".to_string()));
+
+    channel.send(&init_command()).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while !api
+            .query()
+            .await
+            .unwrap()
+            .state
+            .controllers
+            .contains_key(&name!("phone"))
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the controller did not join");
+    let connections = api.query().await.unwrap().connections;
+    assert_eq!(connections[0].protocol_version, Some(1));
+    assert_eq!(connections[0].capabilities, vec!["value:vec3"]);
+
+    // Samples are only applied while live.
+    api.submit(
+        Role::Operator,
+        ClientCommand::ChangeMode(messages::ChangeMode(data::Mode::Live)),
+    )
+    .await
+    .unwrap();
+    channel.send(&sample_command()).await.unwrap();
+
+    let expected = Value::Vec3((90.0, 45.0, -10.0).into());
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let state = api.query().await.unwrap().state;
+            let value = &state.controllers[&name!("phone")].properties[&name!("orientation")].value;
+            if *value == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the sample was not applied");
+
+    // Controllers may not change the mode, the request is answered with a nack.
+    let change_mode = proto::Command {
+        payload: Some(proto::command::Payload::ChangeMode(proto::ChangeMode {
+            mode: proto::change_mode::Mode::Idle.into(),
+        })),
+        request_id: Some(9),
+    };
+    channel
+        .send(&change_mode.try_into().unwrap())
+        .await
+        .unwrap();
+    let nack = tokio::time::timeout(TIMEOUT, async {
+        while let Some(event) = event_rx.recv().await {
+            if let Some(proto::event::Payload::Nack(nack)) = event.payload {
+                return nack;
+            }
+        }
+        panic!("the event channel closed");
+    })
+    .await
+    .expect("the command was not answered");
+    assert_eq!(nack.request_id, 9);
+    let error = nack.error.unwrap();
+    assert_eq!(
+        error.r#type(),
+        proto::error_event::ErrorType::PermissionDenied
+    );
+    assert_eq!(error.command, "ChangeMode");
+
+    peer.close().await.unwrap();
+}
@@ -16,8 +16,10 @@ harness!(connection_setup, { State::default() }, {
             "create connection",
             messages::Message {
                 source_id: LOCAL_CONN_ID,
+                request_id: None,
                 command: messages::AddConnection {
                     agent: Box::<common::session::DummyAgent>::default(),
+                    role: Default::default(),
                     ack_pipe,
                 }
                 .into(),
@@ -27,6 +29,7 @@ harness!(connection_setup, { State::default() }, {
             "open connection",
             messages::Message {
                 source_id: 1, // Hardcoded Id that should be set.
+                request_id: None,
                 command: messages::OpenConnection {}.into(),
             }
         ),
@@ -41,6 +44,7 @@ harness!(connection_setup, { State::default() }, {
             "initial connection session",
             messages::Message {
                 source_id: 1,
+                request_id: None,
                 command: messages::Init {
                     peer: data::Controller {
                         name: name!("test"),
@@ -51,7 +55,11 @@ harness!(connection_setup, { State::default() }, {
                         .into_iter()
                         .map(|p| (p.name.clone(), p))
                         .collect(),
-                    }
+                        device_id: None,
+                        status: data::ControllerStatus::Connected,
+                        sampled_at: None,
+                    },
+                    handshake: Default::default(),
                 }
                 .into(),
             }
@@ -71,6 +79,9 @@ harness!(connection_setup, { State::default() }, {
                         .into_iter()
                         .map(|p| (p.name.clone(), p))
                         .collect(),
+                        device_id: None,
+                        status: data::ControllerStatus::Connected,
+                        sampled_at: None,
                     },
                 );
                 state.controllers = controllers;
@@ -81,3 +92,67 @@ harness!(connection_setup, { State::default() }, {
         }),
     ]
 });
+
+harness!(
+    connection_lifecycle_events,
+    {
+        let mut state = State::default();
+        state.controllers.insert(
+            name!("test"),
+            data::Controller::new(name!("test"), HashMap::new()),
+        );
+        state
+    },
+    {
+        vec![
+            message!(
+                "initialize the controller on the connection",
+                messages::Message {
+                    source_id: 1,
+                    request_id: None,
+                    command: messages::Init {
+                        peer: data::Controller::new(name!("test"), HashMap::new()),
+                        handshake: Default::default(),
+                    }
+                    .into(),
+                }
+            ),
+            events!(
+                "expect the controller to be announced to everyone",
+                Event {
+                    target: None,
+                    body: events::ControllerJoinedEvent(name!("test")).into(),
+                }
+            ),
+            message!(
+                "close the connection",
+                messages::Message {
+                    source_id: 1,
+                    request_id: None,
+                    command: messages::CloseConnection {}.into(),
+                }
+            ),
+            events!(
+                "expect the connection and controller to be announced as gone",
+                Event {
+                    target: None,
+                    body: events::ConnectionClosedEvent(1).into(),
+                },
+                Event {
+                    target: None,
+                    body: events::ControllerLeftEvent(name!("test")).into(),
+                }
+            ),
+            state!(
+                "expect the controller to be marked as disconnected",
+                |state: &mut State| {
+                    state
+                        .controllers
+                        .get_mut(&name!("test"))
+                        .expect("controller should exist")
+                        .status = data::ControllerStatus::Disconnected;
+                }
+            ),
+        ]
+    }
+);
@@ -2,7 +2,7 @@ use cinemotion::data::PropertyLink;
 use paste::paste;
 use std::collections::HashMap;
 
-use cinemotion::{data, messages, name, scene, State};
+use cinemotion::{data, messages, name, scene, Error, Event, EventBody, State};
 
 mod common;
 use common::*;
@@ -24,6 +24,9 @@ harness!(
                 .into_iter()
                 .map(|p| (p.name.clone(), p))
                 .collect(),
+                device_id: None,
+                status: data::ControllerStatus::Connected,
+                sampled_at: None,
             },
         );
 
@@ -53,6 +56,7 @@ harness!(
                 "update the motion mode to be live",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::ChangeMode(data::Mode::Live).into(),
                 }
             ),
@@ -63,6 +67,7 @@ harness!(
                 "send a motion sample",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::SampleMotion(data::Sample::new(HashMap::from([(
                         name!("position"),
                         data::Value::Vec3((0.0, 1.0, 0.0).into())
@@ -118,6 +123,9 @@ harness!(
                 .into_iter()
                 .map(|p| (p.name.clone(), p))
                 .collect(),
+                device_id: None,
+                status: data::ControllerStatus::Connected,
+                sampled_at: None,
             },
         );
 
@@ -147,6 +155,7 @@ harness!(
                 "send a motion sample",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::SampleMotion(data::Sample::new(HashMap::from([(
                         name!("position"),
                         data::Value::Vec3((0.0, 1.0, 0.0).into())
@@ -202,10 +211,14 @@ harness!(
                     name: name!("position"),
                     default_value: data::Value::Vec3((0.0, 0.0, 0.0).into()),
                     value: data::Value::Vec3((0.0, 10.0, 0.0).into()),
+                    schema: Default::default(),
                 }]
                 .into_iter()
                 .map(|p| (p.name.clone(), p))
                 .collect(),
+                device_id: None,
+                status: data::ControllerStatus::Connected,
+                sampled_at: None,
             },
         );
 
@@ -235,6 +248,7 @@ harness!(
                 "change mode to idle",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::ChangeMode(data::Mode::Idle).into(),
                 }
             ),
@@ -268,3 +282,108 @@ harness!(
         ]
     }
 );
+
+harness!(
+    motion_sampling_rejects_invalid_samples,
+    {
+        let mut state = State {
+            mode: data::Mode::Live,
+            ..Default::default()
+        };
+        let mut controllers = HashMap::new();
+
+        controllers.insert(
+            name!("test"),
+            data::Controller {
+                name: name!("test"),
+                properties: vec![data::Property::with_schema(
+                    name!("position"),
+                    data::Value::Vec3((0.0, 0.0, 0.0).into()),
+                    data::PropertySchema {
+                        range: Some(data::Range::new(-1.0, 1.0)),
+                        unit: Some("m".into()),
+                        semantic: Some("position".into()),
+                    },
+                )]
+                .into_iter()
+                .map(|p| (p.name.clone(), p))
+                .collect(),
+                device_id: None,
+                status: data::ControllerStatus::Connected,
+                sampled_at: None,
+            },
+        );
+
+        state.controllers = controllers;
+        state
+    },
+    {
+        vec![
+            message!(
+                "send a sample with an undeclared property",
+                messages::Message {
+                    source_id: 1,
+                    request_id: None,
+                    command: messages::SampleMotion(data::Sample::new(HashMap::from([
+                        (name!("position"), data::Value::Vec3((0.0, 0.5, 0.0).into())),
+                        (name!("rotation"), data::Value::Float(1.0)),
+                    ])))
+                    .into(),
+                }
+            ),
+            event!(
+                "expect an unknown property error to the sender",
+                |event: &Event| {
+                    event.target == Some(1)
+                        && matches!(
+                            &event.body,
+                            EventBody::Error(error) if matches!(error.error, Error::UnknownProperty(_))
+                        )
+                }
+            ),
+            message!(
+                "send a sample with an out of range value",
+                messages::Message {
+                    source_id: 1,
+                    request_id: None,
+                    command: messages::SampleMotion(data::Sample::new(HashMap::from([(
+                        name!("position"),
+                        data::Value::Vec3((0.0, 2.0, 0.0).into())
+                    ),])))
+                    .into(),
+                }
+            ),
+            event!(
+                "expect an out of range error to the sender",
+                |event: &Event| {
+                    event.target == Some(1)
+                        && matches!(
+                            &event.body,
+                            EventBody::Error(error) if matches!(error.error, Error::ValueOutOfRange(_))
+                        )
+                }
+            ),
+            state!(
+                "verify that no part of the invalid samples was applied",
+                |state: &mut State| {
+                    // The closure sees the observed state, so check the
+                    // controller directly rather than comparing it to itself.
+                    let controller = state
+                        .controllers
+                        .get(&name!("test"))
+                        .expect("controller should exist");
+                    assert_eq!(
+                        controller
+                            .properties
+                            .get(&name!("position"))
+                            .expect("property must exist")
+                            .value,
+                        data::Value::Vec3((0.0, 0.0, 0.0).into()),
+                        "the position should still have its default value"
+                    );
+                    assert_eq!(controller.sampled_at, None);
+                }
+            ),
+        ]
+    }
+);
@@ -23,6 +23,9 @@ harness!(
                 .into_iter()
                 .map(|p| (p.name.clone(), p))
                 .collect(),
+                device_id: None,
+                status: data::ControllerStatus::Connected,
+                sampled_at: None,
             },
         );
         state.controllers = controllers;
@@ -34,6 +37,7 @@ harness!(
                 "attempt to update a scene object that does not exist",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::UpdateSceneObject(scene::SceneObject::new(
                         name!("doesnotexist"),
                         HashMap::from([(
@@ -52,7 +56,7 @@ harness!(
                 match event.target {
                     Some(1) => match &event.body {
                         EventBody::Error(event) => {
-                            matches!(event.0, Error::InvalidSceneObject(_))
+                            matches!(event.error, Error::InvalidSceneObject(_))
                         }
                         _ => false,
                     },
@@ -64,6 +68,7 @@ harness!(
                 "add a new scene object.",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::AddSceneObject(scene::SceneObject::new(
                         name!("object1"),
                         HashMap::from([(
@@ -93,6 +98,7 @@ harness!(
                 "try to add a existing scene object.",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::AddSceneObject(scene::SceneObject::new(
                         name!("object1"),
                         HashMap::from([(
@@ -107,7 +113,7 @@ harness!(
                 match event.target {
                     Some(1) => match &event.body {
                         EventBody::Error(event) => {
-                            matches!(event.0, Error::InvalidSceneObject(_))
+                            matches!(event.error, Error::InvalidSceneObject(_))
                         }
                         _ => false,
                     },
@@ -115,10 +121,45 @@ harness!(
                     _ => false,
                 }
             }),
+            message!(
+                "attempt to bind a property to a controller property of another type",
+                messages::Message {
+                    source_id: 1,
+                    request_id: None,
+                    command: messages::AddSceneObject(scene::SceneObject::new(
+                        name!("object2"),
+                        HashMap::from([(
+                            name!("focus"),
+                            data::PropertyLink::bind(
+                                name!("test"),
+                                name!("position"),
+                                data::Value::Float(0.0)
+                            ),
+                        )])
+                    ))
+                    .into(),
+                }
+            ),
+            event!(
+                "expect an invalid binding error to be emitted",
+                |event: &Event| {
+                    match event.target {
+                        Some(1) => match &event.body {
+                            EventBody::Error(event) => {
+                                matches!(event.error, Error::InvalidBinding(_))
+                            }
+                            _ => false,
+                        },
+
+                        _ => false,
+                    }
+                }
+            ),
             message!(
                 "update the root scene object to map controller property to object",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::UpdateSceneObject(scene::SceneObject::new(
                         name!("default"),
                         HashMap::from([(
@@ -156,6 +197,7 @@ harness!(
                 "delete object in the scene",
                 messages::Message {
                     source_id: 1,
+                    request_id: None,
                     command: messages::DeleteSceneObject(name!("object1"),).into(),
                 }
             ),
@@ -0,0 +1,138 @@
+//! Connects over WebRTC with trickled candidates and leaves by deleting the
+//! signaling session.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cinemotion::data::WebRTCSessionDescriptor;
+use cinemotion::events;
+use cinemotion::messages;
+use cinemotion::services::api::RestApi;
+use cinemotion::services::runtime::{RuntimeOptions, RuntimeService, DEFAULT_TICK_INTERVAL};
+use cinemotion::webrtc::sdpfrag::Fragment;
+use cinemotion::webrtc::SignalingRelay;
+use cinemotion_proto as proto;
+use webrtc::api::APIBuilder;
+use webrtc::data_channel::RTCDataChannel;
+use webrtc::ice_transport::ice_candidate::RTCIceCandidate;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn test_trickle_ice_session() {
+    let (sender, receiver) = messages::message_pipe();
+    let _runtime = RuntimeService::new(RuntimeOptions {
+        message_pipe: (sender.clone(), receiver),
+        offline_policy: Default::default(),
+        heartbeat: Default::default(),
+        tick_interval: DEFAULT_TICK_INTERVAL,
+        event_pipe: events::event_pipe(),
+        scene_store: Default::default(),
+        initial_scene: None,
+    });
+    let relay = SignalingRelay::new(sender.clone());
+    let api = RestApi::new(sender, Default::default());
+
+    let peer = Arc::new(
+        APIBuilder::new()
+            .build()
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+            .unwrap(),
+    );
+    let local = Arc::new(Mutex::new(Fragment::default()));
+    let shared_local = Arc::clone(&local);
+    peer.on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+        let mut local = shared_local.lock().unwrap();
+        match candidate {
+            Some(candidate) => local.candidates.push(candidate.to_json().unwrap()),
+            None => local.end_of_candidates = true,
+        }
+        Box::pin(async {})
+    }));
+    let _bootstrap = peer.create_data_channel("bootstrap", None).await.unwrap();
+    let (opened_tx, mut opened_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    peer.on_data_channel(Box::new(move |channel: Arc<RTCDataChannel>| {
+        let opened_tx = opened_tx.clone();
+        channel.on_message(Box::new(move |message| {
+            if let Ok(proto::Event {
+                payload: Some(proto::event::Payload::ConnectionOpened(_)),
+            }) = proto::Event::try_from(message.data)
+            {
+                let _ = opened_tx.send(());
+            }
+            Box::pin(async {})
+        }));
+        Box::pin(async {})
+    }));
+
+    // Offer right away, the candidates follow over the session.
+    let offer = peer.create_offer(None).await.unwrap();
+    peer.set_local_description(offer).await.unwrap();
+    let offer = peer.local_description().await.unwrap();
+    let answer = relay
+        .create(
+            WebRTCSessionDescriptor::new(&offer.sdp).with_trickle(),
+            None,
+        )
+        .await
+        .unwrap();
+    let session_id = answer
+        .session_id
+        .clone()
+        .expect("the answer has a session id");
+    peer.set_remote_description(RTCSessionDescription::answer(answer.decode().unwrap()).unwrap())
+        .await
+        .unwrap();
+
+    // Trickle until the connection opens, sending new local candidates and
+    // adding the server candidates from each response.
+    let mut sent = 0;
+    let mut added = HashSet::new();
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let frag = {
+                let local = local.lock().unwrap();
+                let fragment = Fragment {
+                    candidates: local.candidates[sent..].to_vec(),
+                    end_of_candidates: local.end_of_candidates,
+                };
+                sent = local.candidates.len();
+                fragment.to_sdp()
+            };
+            if let Some(remote) = relay.trickle(&session_id, &frag).await.unwrap() {
+                // Each response only carries candidates that were not sent yet.
+                for candidate in remote.candidates {
+                    assert!(added.insert(candidate.candidate.clone()));
+                    peer.add_ice_candidate(candidate).await.unwrap();
+                }
+            }
+            if let Ok(Some(())) =
+                tokio::time::timeout(Duration::from_millis(50), opened_rx.recv()).await
+            {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("the connection was not opened");
+    assert_eq!(api.query().await.unwrap().connections.len(), 1);
+
+    relay.delete(&session_id).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while !api.query().await.unwrap().connections.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the connection was not closed");
+    assert!(matches!(
+        relay.delete(&session_id).await,
+        Err(cinemotion::Error::UnknownSession(_))
+    ));
+
+    peer.close().await.unwrap();
+}